@@ -13,7 +13,7 @@
 //   limitations under the License.
 
 use anyhow::Result;
-use northstar_client::Client;
+use northstar_client::{model::Notify, Client};
 use std::time::Duration;
 use tokio::time;
 
@@ -26,6 +26,9 @@ async fn main() -> Result<()> {
     let ident = client.ident().await?;
     println!("We are {ident}");
 
+    // Report that the startup is done
+    client.notify(Notify::Ready).await?;
+
     // List repositories
     println!(
         "Listing repositories is denied: {:?}",
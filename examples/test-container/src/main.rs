@@ -1,6 +1,9 @@
 use anyhow::{anyhow, Context, Error, Result};
 use clap::Parser;
-use nix::libc;
+use nix::{
+    libc,
+    sys::signal::{SigSet, Signal},
+};
 use std::{
     fs,
     io::{self, Write},
@@ -49,6 +52,8 @@ enum Command {
         path: PathBuf,
     },
     Crash,
+    /// Crash upon SIGUSR1
+    CrashOnSignal,
     Exit {
         code: i32,
     },
@@ -78,6 +83,7 @@ fn main() -> Result<()> {
         Command::CallDeleteModule { flags } => call_delete_module(flags)?,
         Command::Cat { path } => cat(&path)?,
         Command::Crash => crash(),
+        Command::CrashOnSignal => crash_on_signal()?,
         Command::Socket { socket } => sockets::run(&socket)?,
         Command::Exit { code } => exit(code),
         Command::Inspect => inspect::run(),
@@ -118,6 +124,16 @@ fn crash() {
     panic!("witness me!");
 }
 
+fn crash_on_signal() -> Result<()> {
+    let mut signals = SigSet::empty();
+    signals.add(Signal::SIGUSR1);
+    signals.thread_block().context("failed to block SIGUSR1")?;
+    println!("Waiting for SIGUSR1...");
+    signals.wait().context("failed to wait for SIGUSR1")?;
+    crash();
+    Ok(())
+}
+
 fn print(message: &str, io: &Io) {
     match io {
         Io::Stdout => println!("{message}"),
@@ -15,16 +15,16 @@ use northstar_runtime::{
     api::{
         codec,
         model::{
-            ConnectNack, Container, ContainerData, InspectResult, InstallResult, Message,
-            MountResult, Notification, RepositoryId, Request, Response, Token, UmountResult,
-            VerificationResult,
+            ConnectNack, Container, ContainerData, HistoryResult, InspectResult, InstallResult,
+            Message, MountResult, Notification, RepositoryId, Request, Response, Token,
+            UmountResult, VerificationResult,
         },
     },
     common::non_nul_string::NonNulString,
 };
 use tokio::{
     fs,
-    io::{self, AsyncRead, AsyncWrite, BufWriter},
+    io::{self, AsyncRead, AsyncWrite, AsyncWriteExt, BufWriter},
 };
 
 /// Client errors
@@ -211,6 +211,46 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         }
     }
 
+    /// Send a heartbeat to the runtime. Containers with a heartbeat health probe must
+    /// send a heartbeat at least once per probe interval.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.heartbeat().await.expect("failed to send heartbeat");
+    /// # }
+    /// ```
+    pub async fn heartbeat(&mut self) -> Result<(), Error> {
+        match self.request(Request::Heartbeat).await? {
+            Response::Heartbeat => Ok(()),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on heartbeat should be heartbeat"),
+        }
+    }
+
+    /// Report the readiness or status of this container. Containers with `ready: notify`
+    /// must report their readiness within the ready timeout of the manifest.
+    ///
+    /// ```no_run
+    /// # use northstar_client::{Client, model::Notify};
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.notify(Notify::Ready).await.expect("failed to notify readiness");
+    /// # }
+    /// ```
+    pub async fn notify(&mut self, notify: model::Notify) -> Result<(), Error> {
+        match self.request(Request::Notify { notify }).await? {
+            Response::Notify => Ok(()),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on notify should be notify"),
+        }
+    }
+
     /// Request a list of installed containers
     ///
     /// ```no_run
@@ -253,6 +293,47 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         }
     }
 
+    /// Request the usage of the admission budgets by the started containers
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// let budgets = client.budgets().await.expect("failed to request budgets");
+    /// println!("{:#?}", budgets);
+    /// # }
+    /// ```
+    pub async fn budgets(&mut self) -> Result<model::Budgets, Error> {
+        match self.request(Request::Budgets).await? {
+            Response::Budgets(budgets) => Ok(budgets),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on budgets should be budgets"),
+        }
+    }
+
+    /// Request the next runs of the containers with a schedule
+    ///
+    /// ```no_run
+    /// # use futures::StreamExt;
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// let schedules = client.schedules().await.expect("failed to request schedules");
+    /// println!("{:#?}", schedules);
+    /// # }
+    /// ```
+    pub async fn schedules(&mut self) -> Result<HashMap<Container, model::Schedule>, Error> {
+        match self.request(Request::Schedules).await? {
+            Response::Schedules(schedules) => Ok(schedules),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on schedules should be schedules"),
+        }
+    }
+
     /// Start container with name
     ///
     /// ```no_run
@@ -281,6 +362,67 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         .await
     }
 
+    /// Start container with name and wait until it is ready. Containers with `ready: notify`
+    /// are ready once they reported their readiness. Other containers are ready once they
+    /// are started. Fails if the container exits before it is ready.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.start_ready("hello:0.0.1").await.expect("failed to start \"hello\"");
+    /// # }
+    /// ```
+    pub async fn start_ready<C>(&mut self, container: C) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.start_request(
+            container,
+            Option::<&str>::None,
+            empty::<&str>(),
+            empty::<(&str, &str)>(),
+            true,
+            None,
+        )
+        .await
+    }
+
+    /// Start container with name and stop it gracefully after `max_runtime` seconds.
+    /// Overrides the maximum run time of the manifest.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.start_max_runtime("hello:0.0.1", 60).await.expect("failed to start \"hello\"");
+    /// # }
+    /// ```
+    pub async fn start_max_runtime<C>(
+        &mut self,
+        container: C,
+        max_runtime: u64,
+    ) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.start_request(
+            container,
+            Option::<&str>::None,
+            empty::<&str>(),
+            empty::<(&str, &str)>(),
+            false,
+            Some(max_runtime),
+        )
+        .await
+    }
+
     /// Start container name and pass init, arguments and set additional env variables.
     ///
     /// ```no_run
@@ -291,94 +433,496 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
     /// # #[tokio::main(flavor = "current_thread")]
     /// # async fn main() {
     /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
-    /// let mut env = HashMap::new();
-    /// env.insert("FOO", "blah");
-    /// client.start_command("hello:0.0.1", Some("/bin/hello"), ["--dump", "-v"], env).await.expect("failed to start \"hello\"");
-    /// // Print start notification
-    /// println!("{:#?}", client.next().await);
+    /// let mut env = HashMap::new();
+    /// env.insert("FOO", "blah");
+    /// client.start_command("hello:0.0.1", Some("/bin/hello"), ["--dump", "-v"], env).await.expect("failed to start \"hello\"");
+    /// // Print start notification
+    /// println!("{:#?}", client.next().await);
+    /// # }
+    /// ```
+    pub async fn start_command<C, A, E, K>(
+        &mut self,
+        container: C,
+        init: Option<A>,
+        args: impl IntoIterator<Item = A>,
+        env: impl IntoIterator<Item = (E, K)>,
+    ) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+        A: TryInto<NonNulString>,
+        A::Error: std::error::Error + Send + Sync + 'static,
+        E: TryInto<NonNulString>,
+        E::Error: std::error::Error + Send + Sync + 'static,
+        K: TryInto<NonNulString>,
+        K::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.start_request(container, init, args, env, false, None)
+            .await
+    }
+
+    /// Send a start request that is replied once the container is started or ready. The
+    /// container is stopped after `max_runtime` seconds if set.
+    async fn start_request<C, A, E, K>(
+        &mut self,
+        container: C,
+        init: Option<A>,
+        args: impl IntoIterator<Item = A>,
+        env: impl IntoIterator<Item = (E, K)>,
+        wait_ready: bool,
+        max_runtime: Option<u64>,
+    ) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+        A: TryInto<NonNulString>,
+        A::Error: std::error::Error + Send + Sync + 'static,
+        E: TryInto<NonNulString>,
+        E::Error: std::error::Error + Send + Sync + 'static,
+        K: TryInto<NonNulString>,
+        K::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+
+        let init = if let Some(init) = init {
+            Some(
+                init.try_into()
+                    .map_err(|e| Error::InvalidArgument(format!("invalid init: {e}")))?,
+            )
+        } else {
+            None
+        };
+
+        let mut args_converted = vec![];
+        for arg in args {
+            args_converted.push(
+                arg.try_into()
+                    .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?,
+            );
+        }
+
+        let mut env_converted = HashMap::new();
+        for (key, value) in env {
+            let key = key
+                .try_into()
+                .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?;
+            let value = value
+                .try_into()
+                .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?;
+            env_converted.insert(key, value);
+        }
+
+        let arguments = args_converted;
+        let environment = env_converted;
+        let request = Request::Start {
+            container,
+            init,
+            arguments,
+            environment,
+            wait_ready,
+            max_runtime,
+        };
+
+        match self.request(request).await? {
+            Response::Start(model::StartResult::Ok { .. }) => Ok(()),
+            Response::Start(model::StartResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on start should be ok or error"),
+        }
+    }
+
+    /// Stop container with name. The stop signal of the containers manifest is sent
+    /// and the container is killed if it does not exit within `timeout` seconds or
+    /// the stop timeout of the manifest. Returns once the container exited.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.stop("hello:0.0.1", Some(5)).await.expect("failed to stop \"hello\"");
+    /// # }
+    /// ```
+    pub async fn stop<C>(&mut self, container: C, timeout: Option<u64>) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        match self.request(Request::Stop { container, timeout }).await? {
+            Response::Stop(model::StopResult::Ok { .. }) => Ok(()),
+            Response::Stop(model::StopResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on stop should be ok or error"),
+        }
+    }
+
+    /// Kill container with name
+    ///
+    /// ```no_run
+    /// # use futures::StreamExt;
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.kill("hello:0.0.1", 15).await.expect("failed to start \"hello\"");
+    /// // Print stop notification
+    /// println!("{:#?}", client.next().await);
+    /// # }
+    /// ```
+    pub async fn kill<C>(&mut self, container: C, signal: i32) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        match self.request(Request::Kill { container, signal }).await? {
+            Response::Kill(model::KillResult::Ok { .. }) => Ok(()),
+            Response::Kill(model::KillResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on kill should be ok or error"),
+        }
+    }
+
+    /// Pause container with name. All processes of the container are frozen.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.pause("hello:0.0.1").await.expect("failed to pause \"hello\"");
+    /// # }
+    /// ```
+    pub async fn pause<C>(&mut self, container: C) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        match self.request(Request::Pause { container }).await? {
+            Response::Pause(model::PauseResult::Ok { .. }) => Ok(()),
+            Response::Pause(model::PauseResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on pause should be ok or error"),
+        }
+    }
+
+    /// Resume container with name
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.resume("hello:0.0.1").await.expect("failed to resume \"hello\"");
+    /// # }
+    /// ```
+    pub async fn resume<C>(&mut self, container: C) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        match self.request(Request::Resume { container }).await? {
+            Response::Resume(model::ResumeResult::Ok { .. }) => Ok(()),
+            Response::Resume(model::ResumeResult::Error { error, .. }) => {
+                Err(Error::Runtime(error))
+            }
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on resume should be ok or error"),
+        }
+    }
+
+    /// Execute `path` with `args` in the started container `container`. The process
+    /// is started with the manifest env if `env` is empty. Use [Client::stdin] and
+    /// [Client::io] to interact with the process until [model::Io::Exit] is received.
+    /// The runtime closes the connection instead if the container exits before the
+    /// process because the exit status of the process is lost.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// # use northstar_client::model::Io;
+    /// # use std::collections::HashMap;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// let env = HashMap::<String, String>::new();
+    /// client.exec("hello:0.0.1", "/bin/ls", ["-l"], env, false).await.expect("failed to exec");
+    /// loop {
+    ///     match client.io().await.expect("failed to receive io") {
+    ///         Io::Stdout(data) => println!("{}", String::from_utf8_lossy(&data)),
+    ///         Io::Exit(status) => break println!("{:?}", status),
+    ///         _ => (),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn exec<C, A, E, K>(
+        &mut self,
+        container: C,
+        path: A,
+        args: impl IntoIterator<Item = A>,
+        env: impl IntoIterator<Item = (E, K)>,
+        tty: bool,
+    ) -> Result<model::Pid, Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+        A: TryInto<NonNulString>,
+        A::Error: std::error::Error + Send + Sync + 'static,
+        E: TryInto<NonNulString>,
+        E::Error: std::error::Error + Send + Sync + 'static,
+        K: TryInto<NonNulString>,
+        K::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        let path = path
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(format!("invalid path: {e}")))?;
+
+        let mut args_converted = vec![];
+        for arg in args {
+            args_converted.push(
+                arg.try_into()
+                    .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?,
+            );
+        }
+
+        let mut env_converted = HashMap::new();
+        for (key, value) in env {
+            let key = key
+                .try_into()
+                .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?;
+            let value = value
+                .try_into()
+                .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?;
+            env_converted.insert(key, value);
+        }
+
+        let request = Request::Exec {
+            container,
+            path,
+            args: args_converted,
+            env: env_converted,
+            tty,
+        };
+        match self.request(request).await? {
+            Response::Exec(model::ExecResult::Ok { pid, .. }) => Ok(pid),
+            Response::Exec(model::ExecResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on exec should be ok or error"),
+        }
+    }
+
+    /// Send `data` to the standard input of the process started with [Client::exec] or
+    /// to the terminal attached with [Client::attach]. An empty buffer closes the standard input.
+    pub async fn stdin(&mut self, data: &[u8]) -> Result<(), Error> {
+        let io = model::Io::Stdin(data.to_vec());
+        self.connection.send(Message::Io { io }).await?;
+        Ok(())
+    }
+
+    /// Receive the next output or the exit status of the process started with [Client::exec]
+    /// or the next output of the terminal attached with [Client::attach]
+    pub async fn io(&mut self) -> Result<model::Io, Error> {
+        loop {
+            let message = self
+                .connection
+                .next()
+                .await
+                .ok_or(Error::ConnectionClosed)??;
+
+            match message {
+                Message::Io { io } => break Ok(io),
+                Message::Notification { notification } => self.push_notification(notification)?,
+                _ => unreachable!("invalid message {:?}", message),
+            }
+        }
+    }
+
+    /// Attach to the terminal of the started container `container`. Use [Client::stdin],
+    /// [Client::resize] and [Client::io] to interact with the terminal until
+    /// [model::Io::Detach] is received. Call [Client::detach] to detach.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// # use northstar_client::model::Io;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.attach("console:0.0.1").await.expect("failed to attach");
+    /// client.stdin(b"ls\n").await.expect("failed to send input");
+    /// client.detach().await.expect("failed to detach");
+    /// loop {
+    ///     match client.io().await.expect("failed to receive io") {
+    ///         Io::Stdout(data) => print!("{}", String::from_utf8_lossy(&data)),
+    ///         Io::Detach => break,
+    ///         _ => (),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn attach<C>(&mut self, container: C) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        match self.request(Request::Attach { container }).await? {
+            Response::Attach(model::AttachResult::Ok { .. }) => Ok(()),
+            Response::Attach(model::AttachResult::Error { error, .. }) => {
+                Err(Error::Runtime(error))
+            }
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on attach should be ok or error"),
+        }
+    }
+
+    /// Set the window size of the terminal attached with [Client::attach]
+    pub async fn resize(&mut self, rows: u16, cols: u16) -> Result<(), Error> {
+        let io = model::Io::Resize { rows, cols };
+        self.connection.send(Message::Io { io }).await?;
+        Ok(())
+    }
+
+    /// Detach from the terminal attached with [Client::attach]. The runtime confirms
+    /// with [model::Io::Detach].
+    pub async fn detach(&mut self) -> Result<(), Error> {
+        let io = model::Io::Detach;
+        self.connection.send(Message::Io { io }).await?;
+        Ok(())
+    }
+
+    /// Captured output of `container` limited to the last `tail` lines and lines
+    /// since `since` milliseconds after the unix epoch. If `follow` is set, use
+    /// [Client::log] to receive the lines written afterwards until [model::Log::End].
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// for line in client.logs("hello:0.0.1", false, Some(10), None).await.expect("failed to get logs") {
+    ///     println!("{}", line.line);
+    /// }
     /// # }
     /// ```
-    pub async fn start_command<C, A, E, K>(
+    pub async fn logs<C>(
         &mut self,
         container: C,
-        init: Option<A>,
-        args: impl IntoIterator<Item = A>,
-        env: impl IntoIterator<Item = (E, K)>,
-    ) -> Result<(), Error>
+        follow: bool,
+        tail: Option<usize>,
+        since: Option<u64>,
+    ) -> Result<Vec<model::LogLine>, Error>
     where
         C: TryInto<Container>,
         C::Error: std::error::Error + Send + Sync + 'static,
-        A: TryInto<NonNulString>,
-        A::Error: std::error::Error + Send + Sync + 'static,
-        E: TryInto<NonNulString>,
-        E::Error: std::error::Error + Send + Sync + 'static,
-        K: TryInto<NonNulString>,
-        K::Error: std::error::Error + Send + Sync + 'static,
     {
         let container = container
             .try_into()
             .map_err(|e| Error::InvalidArgument(e.to_string()))?;
-
-        let init = if let Some(init) = init {
-            Some(
-                init.try_into()
-                    .map_err(|e| Error::InvalidArgument(format!("invalid init: {e}")))?,
-            )
-        } else {
-            None
+        let request = Request::Logs {
+            container,
+            follow,
+            tail,
+            since,
         };
-
-        let mut args_converted = vec![];
-        for arg in args {
-            args_converted.push(
-                arg.try_into()
-                    .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?,
-            );
+        match self.request(request).await? {
+            Response::Logs(model::LogsResult::Ok { lines, .. }) => Ok(lines),
+            Response::Logs(model::LogsResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on logs should be ok or error"),
         }
+    }
 
-        let mut env_converted = HashMap::new();
-        for (key, value) in env {
-            let key = key
-                .try_into()
-                .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?;
-            let value = value
-                .try_into()
-                .map_err(|e| Error::InvalidArgument(format!("invalid argument: {e}")))?;
-            env_converted.insert(key, value);
-        }
+    /// Receive the next line of a container followed with [Client::logs]
+    pub async fn log(&mut self) -> Result<model::Log, Error> {
+        loop {
+            let message = self
+                .connection
+                .next()
+                .await
+                .ok_or(Error::ConnectionClosed)??;
 
-        let arguments = args_converted;
-        let environment = env_converted;
-        let request = Request::Start {
-            container,
-            init,
-            arguments,
-            environment,
-        };
+            match message {
+                Message::Log { log } => break Ok(log),
+                Message::Notification { notification } => self.push_notification(notification)?,
+                _ => unreachable!("invalid message {:?}", message),
+            }
+        }
+    }
 
-        match self.request(request).await? {
-            Response::Start(model::StartResult::Ok { .. }) => Ok(()),
-            Response::Start(model::StartResult::Error { error, .. }) => Err(Error::Runtime(error)),
+    /// Core dumps of a container starting with the oldest
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// for core_dump in client.core_dumps("hello:0.0.1").await.unwrap() {
+    ///     println!("{} {}", core_dump.name, core_dump.size);
+    /// }
+    /// # }
+    /// ```
+    pub async fn core_dumps<C>(&mut self, container: C) -> Result<Vec<model::CoreDump>, Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        match self.request(Request::CoreDumps { container }).await? {
+            Response::CoreDumps(model::CoreDumpsResult::Ok { core_dumps, .. }) => Ok(core_dumps),
+            Response::CoreDumps(model::CoreDumpsResult::Error { error, .. }) => {
+                Err(Error::Runtime(error))
+            }
             Response::PermissionDenied(_) => Err(Error::PermissionDenied),
-            _ => unreachable!("response on start should be ok or error"),
+            _ => unreachable!("response on core dumps should be ok or error"),
         }
     }
 
-    /// Kill container with name
+    /// Download the core dump `name` of a container to `output` and return its size
     ///
     /// ```no_run
-    /// # use futures::StreamExt;
     /// # use northstar_client::Client;
     /// #
     /// # #[tokio::main(flavor = "current_thread")]
     /// # async fn main() {
     /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
-    /// client.kill("hello:0.0.1", 15).await.expect("failed to start \"hello\"");
-    /// // Print stop notification
-    /// println!("{:#?}", client.next().await);
+    /// let mut output = tokio::fs::File::create("core").await.unwrap();
+    /// client.core_dump("hello:0.0.1", "core.hello.1", &mut output).await.unwrap();
     /// # }
     /// ```
-    pub async fn kill<C>(&mut self, container: C, signal: i32) -> Result<(), Error>
+    pub async fn core_dump<C>(
+        &mut self,
+        container: C,
+        name: &str,
+        mut output: impl AsyncWrite + Unpin,
+    ) -> Result<u64, Error>
     where
         C: TryInto<Container>,
         C::Error: std::error::Error + Send + Sync + 'static,
@@ -386,12 +930,43 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         let container = container
             .try_into()
             .map_err(|e| Error::InvalidArgument(e.to_string()))?;
-        match self.request(Request::Kill { container, signal }).await? {
-            Response::Kill(model::KillResult::Ok { .. }) => Ok(()),
-            Response::Kill(model::KillResult::Error { error, .. }) => Err(Error::Runtime(error)),
-            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
-            _ => unreachable!("response on kill should be ok or error"),
+        let request = Request::CoreDump {
+            container,
+            name: name.to_string(),
+        };
+        match self.request(request).await? {
+            Response::CoreDump(model::CoreDumpResult::Ok { .. }) => (),
+            Response::CoreDump(model::CoreDumpResult::Error { error, .. }) => {
+                return Err(Error::Runtime(error))
+            }
+            Response::PermissionDenied(_) => return Err(Error::PermissionDenied),
+            _ => unreachable!("response on core dump should be ok or error"),
+        }
+
+        let mut size = 0;
+        loop {
+            let message = self
+                .connection
+                .next()
+                .await
+                .ok_or(Error::ConnectionClosed)??;
+
+            match message {
+                Message::Download {
+                    download: model::Download::Chunk(chunk),
+                } => {
+                    output.write_all(&chunk).await?;
+                    size += chunk.len() as u64;
+                }
+                Message::Download {
+                    download: model::Download::End,
+                } => break,
+                Message::Notification { notification } => self.push_notification(notification)?,
+                _ => unreachable!("invalid message {:?}", message),
+            }
         }
+        output.flush().await?;
+        Ok(size)
     }
 
     /// Install a npk from path
@@ -470,6 +1045,117 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         }
     }
 
+    /// Update the started container `from` to `to` with the npk at `npk`. See [Client::update].
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// # use std::path::Path;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// let npk = Path::new("hello-0.0.2.npk");
+    /// client.update_file("hello:0.0.1", "hello:0.0.2", npk, "default", 30).await.expect("failed to update \"hello\"");
+    /// # }
+    /// ```
+    pub async fn update_file<C, D>(
+        &mut self,
+        from: C,
+        to: D,
+        npk: &Path,
+        repository: &str,
+        probation: u64,
+    ) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+        D: TryInto<Container>,
+        D::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let file = fs::File::open(npk).await?;
+        let size = file.metadata().await?.len();
+
+        self.update(from, to, file, size, repository, probation)
+            .await
+    }
+
+    /// Update the started container `from` to `to` with a npk. The npk is installed into
+    /// `repository`, `from` is stopped and `to` is started with the arguments `from` was
+    /// started with. If `to` exits or becomes unhealthy within `probation` seconds, `to` is
+    /// uninstalled and `from` is started again.
+    /// The progress is reported with [model::Notification::Update] notifications.
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// # use tokio::fs;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// let npk = fs::File::open("hello-0.0.2.npk").await.expect("failed to open \"hello-0.0.2.npk\"");
+    /// let size = npk.metadata().await.unwrap().len();
+    /// client.update("hello:0.0.1", "hello:0.0.2", npk, size, "default", 30).await.expect("failed to update \"hello\"");
+    /// # }
+    /// ```
+    pub async fn update<C, D>(
+        &mut self,
+        from: C,
+        to: D,
+        npk: impl AsyncRead + Unpin,
+        size: u64,
+        repository: &str,
+        probation: u64,
+    ) -> Result<(), Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+        D: TryInto<Container>,
+        D::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let from = from
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        let to = to
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        let request = Request::Update {
+            from,
+            to,
+            repository: repository.into(),
+            size,
+            probation,
+        };
+        let message = Message::Request { request };
+        self.connection.send(message).await?;
+        self.connection.flush().await?;
+        debug_assert!(self.connection.write_buffer().is_empty());
+
+        let mut reader = io::BufReader::with_capacity(BUFFER_SIZE, npk);
+        let mut writer = BufWriter::with_capacity(BUFFER_SIZE, self.connection.get_mut());
+        io::copy_buf(&mut reader, &mut writer).await?;
+
+        loop {
+            let message = self
+                .connection
+                .next()
+                .await
+                .ok_or(Error::ConnectionClosed)??;
+
+            match message {
+                Message::Response { response } => match response {
+                    Response::Update(model::UpdateResult::Ok { .. }) => break Ok(()),
+                    Response::Update(model::UpdateResult::Error { error, .. }) => {
+                        break Err(Error::Runtime(error))
+                    }
+                    Response::PermissionDenied(_) => break Err(Error::PermissionDenied),
+                    _ => unreachable!("response on update should be ok or error"),
+                },
+                Message::Notification { notification } => self.push_notification(notification)?,
+                _ => unreachable!("invalid response"),
+            }
+        }
+    }
+
     /// Uninstall a npk and optionally wipe the containers persistent dir
     ///
     /// ```no_run
@@ -507,6 +1193,68 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         self.request(Request::Shutdown).await.ok();
     }
 
+    /// Desired state journal of the runtime
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// let journal = client.journal().await.expect("failed to request the journal");
+    /// println!("{:#?}", journal);
+    /// # }
+    /// ```
+    pub async fn journal(&mut self) -> Result<model::Journal, Error> {
+        self.request_journal(false).await
+    }
+
+    /// Clear the desired state journal of the runtime and return the previous content
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.clear_journal().await.expect("failed to clear the journal");
+    /// # }
+    /// ```
+    pub async fn clear_journal(&mut self) -> Result<model::Journal, Error> {
+        self.request_journal(true).await
+    }
+
+    async fn request_journal(&mut self, clear: bool) -> Result<model::Journal, Error> {
+        match self.request(Request::Journal { clear }).await? {
+            Response::Journal(model::JournalResult::Ok { journal }) => Ok(journal),
+            Response::Journal(model::JournalResult::Error { error }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on journal should be ok or error"),
+        }
+    }
+
+    /// Reload the runtime configuration from the configuration file
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// #   let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// client.reload_config().await.expect("failed to reload the configuration");
+    /// # }
+    /// ```
+    pub async fn reload_config(&mut self) -> Result<(), Error> {
+        match self.request(Request::ReloadConfig).await? {
+            Response::ReloadConfig(model::ReloadConfigResult::Ok) => Ok(()),
+            Response::ReloadConfig(model::ReloadConfigResult::Error { error }) => {
+                Err(Error::Runtime(error))
+            }
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on reload config should be ok or error"),
+        }
+    }
+
     /// Mount a container
     /// ```no_run
     /// # use northstar_client::Client;
@@ -646,6 +1394,35 @@ impl<'a, T: AsyncRead + AsyncWrite + Unpin> Client<T> {
         }
     }
 
+    /// Terminated runs of a container starting with the oldest run
+    ///
+    /// ```no_run
+    /// # use northstar_client::Client;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// # let mut client = Client::new(tokio::net::TcpStream::connect("localhost:4200").await.unwrap(), None).await.unwrap();
+    /// for run in client.history("hello:0.0.1").await.unwrap() {
+    ///     println!("{:?}", run);
+    /// }
+    /// # }
+    /// ```
+    pub async fn history<C>(&mut self, container: C) -> Result<Vec<model::Run>, Error>
+    where
+        C: TryInto<Container>,
+        C::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let container = container
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(format!("invalid container: {e}")))?;
+        match self.request(Request::History { container }).await? {
+            Response::History(HistoryResult::Ok { runs, .. }) => Ok(runs),
+            Response::History(HistoryResult::Error { error, .. }) => Err(Error::Runtime(error)),
+            Response::PermissionDenied(_) => Err(Error::PermissionDenied),
+            _ => unreachable!("response on history should be history"),
+        }
+    }
+
     /// Create a token
     ///
     /// The `target` parameter must be the container name (without version) of the container that
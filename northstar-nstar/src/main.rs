@@ -9,13 +9,20 @@ use clap::{self, Parser};
 use futures::StreamExt;
 use itertools::Itertools;
 use northstar_client::{
-    model::{Container, Token},
+    model::{Container, ExitStatus, Io, Log, LogLine, LogStream, Token},
     Client, Name, VERSION,
 };
-use std::{collections::HashMap, convert::TryFrom, path::PathBuf, process};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    path::PathBuf,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use tokio::{
-    io::{AsyncRead, AsyncWrite},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     net::{TcpStream, UnixStream},
+    signal::unix::{signal, SignalKind},
     time,
 };
 use tokio_util::either::Either;
@@ -24,11 +31,15 @@ use trace::Trace;
 mod completion;
 mod pretty;
 mod seccomp;
+mod terminal;
 mod trace;
 
 /// Default nstar address
 const DEFAULT_HOST: &str = "tcp://localhost:4200";
 
+/// Key that detaches from the terminal of a container (Ctrl-])
+const DETACH_KEY: u8 = 0x1d;
+
 /// About string for CLI
 fn about() -> &'static str {
     Box::leak(Box::new(format!("Northstar API version {VERSION}")))
@@ -43,6 +54,10 @@ enum Subcommand {
     /// List configured repositories
     #[command(alias = "repos")]
     Repositories,
+    /// List the next runs of the containers with a schedule
+    Schedules,
+    /// Show the usage of the admission budgets
+    Budgets,
     /// Mount a container
     Mount {
         /// Container name and optional version
@@ -65,7 +80,46 @@ enum Subcommand {
         #[arg(trailing_var_arg = true)]
         command: Option<Vec<String>>,
     },
-    /// Stop a container
+    /// Stop a container gracefully
+    Stop {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+        /// Seconds until the container is killed. Defaults to the stop timeout of the manifest.
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+    /// Pause a container
+    Pause {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+    },
+    /// Resume a paused container
+    Resume {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+    },
+    /// Execute a command in a started container
+    Exec {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+        /// Allocate a pseudo terminal
+        #[arg(short, long)]
+        tty: bool,
+        /// Path to the binary and arguments
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Attach to the terminal of a container. Detach with Ctrl-]
+    Attach {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+    },
+    /// Send a signal to a container
     Kill {
         /// Container name and optional version
         #[arg(value_name = "name[:version]")]
@@ -73,6 +127,24 @@ enum Subcommand {
         /// Signal
         signal: Option<i32>,
     },
+    /// Print the captured output of a container
+    Logs {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+        /// Print new lines until the container exits
+        #[arg(short, long)]
+        follow: bool,
+        /// Number of lines to print from the end
+        #[arg(short = 'n', long)]
+        tail: Option<usize>,
+        /// Print lines newer than a duration e.g. 10m
+        #[arg(long, value_parser = humantime::parse_duration)]
+        since: Option<time::Duration>,
+        /// Prefix lines with their timestamp
+        #[arg(short = 'T', long)]
+        timestamps: bool,
+    },
     /// Install a npk
     Install {
         /// Path to the .npk file
@@ -80,6 +152,22 @@ enum Subcommand {
         /// Target repository
         repository: String,
     },
+    /// Update a started container to a new version and roll back on failure
+    Update {
+        /// Started container name and optional version
+        #[arg(value_name = "name[:version]")]
+        from: String,
+        /// New container name and version
+        #[arg(value_name = "name:version")]
+        to: String,
+        /// Path to the .npk file
+        npk: PathBuf,
+        /// Target repository
+        repository: String,
+        /// Seconds the new version must run without failure
+        #[arg(short, long, default_value = "30")]
+        probation: u64,
+    },
     /// Uninstall a container
     Uninstall {
         /// Wipe containers persistent dir (if present)
@@ -91,6 +179,14 @@ enum Subcommand {
     },
     /// Shutdown Northstar
     Shutdown,
+    /// Reload the runtime configuration
+    ReloadConfig,
+    /// Show the desired state journal
+    Journal {
+        /// Clear the journal
+        #[arg(short, long)]
+        clear: bool,
+    },
     /// Notifications
     Notifications {
         /// Exit after n notifications
@@ -112,6 +208,29 @@ enum Subcommand {
         #[arg(value_name = "name[:version]")]
         container: String,
     },
+    /// List the core dumps of a container
+    CoreDumps {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+    },
+    /// Download a core dump of a container
+    CoreDump {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+        /// Name of the core dump
+        name: String,
+        /// Output file. Defaults to the name of the core dump.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Show the terminated runs of a container
+    History {
+        /// Container name and optional version
+        #[arg(value_name = "name[:version]")]
+        container: String,
+    },
     /// Create a token
     Token {
         /// Token target
@@ -194,6 +313,20 @@ where
     Ok(container)
 }
 
+/// Print a captured line of a container to stdout or stderr
+fn print_log_line(line: &LogLine, timestamps: bool) {
+    let prefix = if timestamps {
+        let timestamp = UNIX_EPOCH + time::Duration::from_millis(line.timestamp);
+        format!("{} ", humantime::format_rfc3339_millis(timestamp))
+    } else {
+        String::new()
+    };
+    match line.stream {
+        LogStream::Stdout => println!("{prefix}{}", line.line),
+        LogStream::Stderr => eprintln!("{prefix}{}", line.line),
+    }
+}
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
     let opt = Opt::parse();
@@ -271,6 +404,18 @@ async fn main() -> Result<()> {
                 pretty::repositories(&repositories);
             }
         }
+        Subcommand::Budgets => {
+            let budgets = client.budgets().await?;
+            if !opt.json {
+                pretty::budgets(&budgets);
+            }
+        }
+        Subcommand::Schedules => {
+            let schedules = client.schedules().await?;
+            if !opt.json {
+                pretty::schedules(&schedules);
+            }
+        }
         Subcommand::Mount { containers } => {
             let mut converted = Vec::with_capacity(containers.len());
             for container in containers {
@@ -327,6 +472,123 @@ async fn main() -> Result<()> {
                 );
             }
         }
+        Subcommand::Stop { container, timeout } => {
+            let container = resolve_container(&container, &mut client).await?;
+            client.stop(container.clone(), timeout).await?;
+            if !opt.json {
+                println!("stopped {container}");
+            }
+        }
+        Subcommand::Pause { container } => {
+            let container = resolve_container(&container, &mut client).await?;
+            client.pause(container.clone()).await?;
+            if !opt.json {
+                println!("paused {container}");
+            }
+        }
+        Subcommand::Resume { container } => {
+            let container = resolve_container(&container, &mut client).await?;
+            client.resume(container.clone()).await?;
+            if !opt.json {
+                println!("resumed {container}");
+            }
+        }
+        Subcommand::Exec {
+            container,
+            tty,
+            command,
+        } => {
+            let container = resolve_container(&container, &mut client).await?;
+            let path = command[0].clone();
+            let args = command[1..].to_vec();
+            let env = HashMap::<String, String>::new();
+            client.exec(container, path, args, env, tty).await?;
+
+            // Forward stdin until EOF and the output until the process exits
+            let mut stdin = tokio::io::stdin();
+            let mut stdout = tokio::io::stdout();
+            let mut stderr = tokio::io::stderr();
+            let mut stdin_closed = false;
+            let mut buffer = [0u8; 4096];
+            let exit_status = loop {
+                let input = tokio::select! {
+                    io = client.io() => match io? {
+                        Io::Stdout(data) => {
+                            stdout.write_all(&data).await?;
+                            stdout.flush().await?;
+                            continue;
+                        }
+                        Io::Stderr(data) => {
+                            stderr.write_all(&data).await?;
+                            stderr.flush().await?;
+                            continue;
+                        }
+                        Io::Exit(exit_status) => break exit_status,
+                        Io::Stdin(_) | Io::Resize { .. } | Io::Detach => continue,
+                    },
+                    n = stdin.read(&mut buffer), if !stdin_closed => n?,
+                };
+                // An empty buffer closes the stdin of the process
+                stdin_closed = input == 0;
+                client.stdin(&buffer[..input]).await?;
+            };
+
+            match exit_status {
+                ExitStatus::Exit { code } => process::exit(code),
+                ExitStatus::Signalled { signal } => process::exit(128 + signal as i32),
+            }
+        }
+        Subcommand::Attach { container } => {
+            let container = resolve_container(&container, &mut client).await?;
+            client.attach(container.clone()).await?;
+            eprintln!("attached to {container}, detach with Ctrl-]");
+
+            // Forward the input in raw mode and the window size until detached
+            let raw_mode = terminal::RawMode::enable()?;
+            if let Some((rows, cols)) = terminal::window_size() {
+                client.resize(rows, cols).await?;
+            }
+            let mut window_change = signal(SignalKind::window_change())?;
+            let mut stdin = tokio::io::stdin();
+            let mut stdout = tokio::io::stdout();
+            let mut detached = false;
+            let mut buffer = [0u8; 4096];
+            loop {
+                tokio::select! {
+                    io = client.io() => match io? {
+                        Io::Stdout(data) | Io::Stderr(data) => {
+                            stdout.write_all(&data).await?;
+                            stdout.flush().await?;
+                        }
+                        Io::Detach => break,
+                        _ => (),
+                    },
+                    _ = window_change.recv() => {
+                        if let Some((rows, cols)) = terminal::window_size() {
+                            client.resize(rows, cols).await?;
+                        }
+                    }
+                    n = stdin.read(&mut buffer), if !detached => {
+                        let input = &buffer[..n?];
+                        match input.iter().position(|b| *b == DETACH_KEY) {
+                            Some(n) => {
+                                if n > 0 {
+                                    client.stdin(&input[..n]).await?;
+                                }
+                                client.detach().await?;
+                                detached = true;
+                            }
+                            // An empty buffer sends an end of transmission
+                            None => client.stdin(input).await?,
+                        }
+                    }
+                }
+            }
+            drop(raw_mode);
+            eprintln!();
+            eprintln!("detached from {container}");
+            process::exit(0);
+        }
         Subcommand::Kill { container, signal } => {
             let container = resolve_container(&container, &mut client).await?;
             let signal = signal.unwrap_or(15);
@@ -335,12 +597,53 @@ async fn main() -> Result<()> {
                 println!("signalled {container} with signal {signal}");
             }
         }
+        Subcommand::Logs {
+            container,
+            follow,
+            tail,
+            since,
+            timestamps,
+        } => {
+            let container = resolve_container(&container, &mut client).await?;
+            let since = since
+                .map(|since| SystemTime::now() - since)
+                .map(|since| {
+                    since
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_millis() as u64)
+                })
+                .transpose()?;
+            for line in client.logs(container, follow, tail, since).await? {
+                print_log_line(&line, timestamps);
+            }
+            if follow {
+                while let Log::Line(line) = client.log().await? {
+                    print_log_line(&line, timestamps);
+                }
+            }
+        }
         Subcommand::Install { npk, repository } => {
             client.install_file(&npk, &repository).await?;
             if !opt.json {
                 println!("installed {} into {repository}", npk.display());
             }
         }
+        Subcommand::Update {
+            from,
+            to,
+            npk,
+            repository,
+            probation,
+        } => {
+            let from = resolve_container(&from, &mut client).await?;
+            let to = Container::try_from(to.as_str())?;
+            client
+                .update_file(from.clone(), to.clone(), &npk, &repository, probation)
+                .await?;
+            if !opt.json {
+                println!("updating {from} to {to} with a probation of {probation}s");
+            }
+        }
         Subcommand::Uninstall { container, wipe } => {
             let container = resolve_container(&container, &mut client).await?;
             client.uninstall(container.clone(), wipe).await?;
@@ -354,6 +657,22 @@ async fn main() -> Result<()> {
                 println!("shutdown");
             }
         }
+        Subcommand::Journal { clear } => {
+            let journal = if clear {
+                client.clear_journal().await?
+            } else {
+                client.journal().await?
+            };
+            if !opt.json {
+                println!("{}", serde_json::to_string_pretty(&journal)?);
+            }
+        }
+        Subcommand::ReloadConfig => {
+            client.reload_config().await?;
+            if !opt.json {
+                println!("reloaded configuration");
+            }
+        }
         Subcommand::Inspect { container } => {
             let container = resolve_container(&container, &mut client).await?;
             let inspect = Client::inspect(&mut client, container).await?;
@@ -361,6 +680,35 @@ async fn main() -> Result<()> {
                 println!("{}", serde_json::to_string_pretty(&inspect)?);
             }
         }
+        Subcommand::CoreDumps { container } => {
+            let container = resolve_container(&container, &mut client).await?;
+            let core_dumps = client.core_dumps(container).await?;
+            if !opt.json {
+                pretty::core_dumps(&core_dumps);
+            }
+        }
+        Subcommand::CoreDump {
+            container,
+            name,
+            output,
+        } => {
+            let container = resolve_container(&container, &mut client).await?;
+            let output = output.unwrap_or_else(|| PathBuf::from(&name));
+            let file = tokio::fs::File::create(&output)
+                .await
+                .with_context(|| format!("failed to create {}", output.display()))?;
+            let size = client.core_dump(container, &name, file).await?;
+            if !opt.json {
+                println!("downloaded {} ({} bytes)", output.display(), size);
+            }
+        }
+        Subcommand::History { container } => {
+            let container = resolve_container(&container, &mut client).await?;
+            let runs = client.history(container).await?;
+            if !opt.json {
+                pretty::history(&runs);
+            }
+        }
         Subcommand::Token { target, shared } => {
             let target = Name::try_from(target)?;
             let shared = shared.as_bytes().to_vec();
@@ -1,7 +1,7 @@
 use itertools::Itertools;
 use northstar_client::model::{
-    self, Container, ContainerData, ExitStatus, MountResult, Notification, RepositoryId,
-    UmountResult,
+    self, BudgetUsage, Budgets, Container, ContainerData, CriticalAction, ExitStatus, MountResult,
+    Notification, RepositoryId, Schedule, ScheduledRun, UmountResult, UpdateProgress,
 };
 use prettytable::{format, Attr, Cell, Row, Table};
 use std::collections::{HashMap, HashSet};
@@ -12,16 +12,56 @@ pub fn notification(notification: &Notification) {
         Notification::CGroup(container, notification) => {
             println!("container {container} memory event {notification:?}")
         }
-        Notification::Exit(container, status) => println!(
-            "container {} exited with status {}",
+        Notification::Critical { container, action } => println!(
+            "critical container {container} failed: {}",
+            match action {
+                CriticalAction::Restart { retry } => format!("restarting (retry {retry})"),
+                CriticalAction::RestartRuntime => "restarting the runtime".to_string(),
+                CriticalAction::FailureHandler { container } => {
+                    format!("starting failure handler {container}")
+                }
+                CriticalAction::Reboot => "rebooting".to_string(),
+                CriticalAction::Exit => "exiting".to_string(),
+            }
+        ),
+        Notification::Exit(container, status, reason) => println!(
+            "container {} exited with status {} ({})",
             container,
             match status {
                 ExitStatus::Exit { code } => format!("exit code {code}"),
                 ExitStatus::Signalled { signal } => format!("signalled {signal}"),
-            }
+            },
+            reason
         ),
         Notification::Install(container) => println!("installed {container}"),
+        Notification::Paused(container) => println!("paused {container}"),
+        Notification::Ready(container) => println!("{container} is ready"),
+        Notification::Resumed(container) => println!("resumed {container}"),
+        Notification::RestartExhausted(container, retries) => {
+            println!("gave up restarting {container} after {retries} retries")
+        }
+        Notification::Health(container, health) => {
+            println!("{container} is {}", format!("{health:?}").to_lowercase())
+        }
         Notification::Uninstall(container) => println!("uninstalled {container}"),
+        Notification::Update { from, to, progress } => println!(
+            "update of {from} to {to} {}",
+            match progress {
+                UpdateProgress::Stopping => "is stopping the previous version",
+                UpdateProgress::Probation => "is on probation",
+                UpdateProgress::Completed => "completed",
+                UpdateProgress::RolledBack => "is rolled back",
+            }
+        ),
+        Notification::Schedule { container, run } => println!(
+            "scheduled run of {container} {}",
+            match run {
+                ScheduledRun::Started => "started".to_string(),
+                ScheduledRun::Skipped => "skipped: still running".to_string(),
+                ScheduledRun::Deferred => "deferred until exit".to_string(),
+                ScheduledRun::Failed { error } => format!("failed: {}", format_err(error)),
+            }
+        ),
         Notification::Started(container) => println!("started {container}"),
         Notification::Shutdown => println!("shutting down"),
     }
@@ -90,6 +130,81 @@ pub fn repositories(repositories: &HashSet<RepositoryId>) {
     print_table(["Name"], iter);
 }
 
+pub fn budgets(budgets: &Budgets) {
+    let usage = |name: String, usage: &BudgetUsage| {
+        [
+            Cell::new(&name).with_style(Attr::Bold),
+            Cell::new(&usage.used.to_string()),
+            Cell::new(
+                &usage
+                    .limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_else(|| "-".into()),
+            ),
+        ]
+    };
+    let iter = [
+        usage("memory (bytes)".into(), &budgets.memory),
+        usage("cpu (millicpus)".into(), &budgets.cpu),
+        usage("containers".into(), &budgets.containers),
+    ]
+    .into_iter()
+    .chain(
+        budgets
+            .repositories
+            .iter()
+            .sorted_by_key(|(repository, _)| (*repository).clone())
+            .map(|(repository, u)| usage(format!("containers of {repository}"), u)),
+    );
+    print_table(["Budget", "Used", "Limit"], iter);
+}
+
+pub fn schedules(schedules: &HashMap<Container, Schedule>) {
+    let iter = schedules
+        .iter()
+        .sorted_by_key(|(_, schedule)| schedule.next)
+        .map(|(container, schedule)| {
+            let next = std::time::UNIX_EPOCH + time::Duration::from_millis(schedule.next);
+            [
+                Cell::new(container.name().as_ref()).with_style(Attr::Bold),
+                Cell::new(&container.version().to_string()),
+                Cell::new(&humantime::format_rfc3339_seconds(next).to_string()),
+                Cell::new(if schedule.deferred { "yes" } else { "no" }),
+            ]
+        });
+    print_table(["Name", "Version", "Next run", "Deferred"], iter);
+}
+
+pub fn core_dumps(core_dumps: &[model::CoreDump]) {
+    let iter = core_dumps.iter().map(|core_dump| {
+        let created = std::time::UNIX_EPOCH + time::Duration::from_millis(core_dump.created);
+        [
+            Cell::new(&core_dump.name).with_style(Attr::Bold),
+            Cell::new(&core_dump.size.to_string()),
+            Cell::new(&humantime::format_rfc3339_seconds(created).to_string()),
+        ]
+    });
+    print_table(["Name", "Size", "Created"], iter);
+}
+
+pub fn history(runs: &[model::Run]) {
+    let iter = runs.iter().map(|run| {
+        let started = std::time::UNIX_EPOCH + time::Duration::from_millis(run.started);
+        let duration = time::Duration::from_secs(run.duration / 1000);
+        [
+            Cell::new(&humantime::format_rfc3339_seconds(started).to_string()),
+            Cell::new(&humantime::format_duration(duration).to_string()),
+            Cell::new(&run.exit_status.to_string()),
+            Cell::new(&run.reason.to_string()),
+            Cell::new(&run.oom_kills.to_string()),
+        ]
+    });
+    print_table(
+        ["Started", "Duration", "Exit status", "Reason", "OOM kills"],
+        iter,
+    );
+}
+
 pub fn mounts(mounts: &[MountResult]) {
     let iter = mounts.iter().map(|r| match r {
         MountResult::Ok { container } => [
@@ -143,18 +258,58 @@ fn format_err(err: &model::Error) -> String {
                 "failed to start container {container}: missing resource {resource} version {version}",
             )
         }
+        model::Error::StartContainerMissingDependency {
+            container,
+            dependency,
+            version,
+        } => {
+            format!(
+                "failed to start container {container}: missing dependency {dependency} version {version}",
+            )
+        }
         model::Error::StartContainerFailed { container, error } => {
             format!("failed to start container {container}: {error}")
         }
         model::Error::StopContainerNotStarted { container } => {
             format!("failed to stop container {container}: not started")
         }
+        model::Error::ContainerNotStarted { container } => {
+            format!("container {container} is not started")
+        }
+        model::Error::ContainerPaused { container } => {
+            format!("container {container} is paused")
+        }
+        model::Error::ContainerNotPaused { container } => {
+            format!("container {container} is not paused")
+        }
+        model::Error::ContainerNoTerminal { container } => {
+            format!("container {container} has no terminal")
+        }
         model::Error::InvalidRepository { repository } => {
             format!("invalid repository {repository}")
         }
         model::Error::InstallDuplicate { container } => {
             format!("failed to install {container}: installed")
         }
+        model::Error::DependencyCycle { container } => {
+            format!("dependency cycle in {container}")
+        }
+        model::Error::HookFailed {
+            container,
+            hook,
+            error,
+        } => {
+            format!("hook {hook} of {container} failed: {error}")
+        }
+        model::Error::BudgetExceeded {
+            container,
+            budget,
+            requested,
+            used,
+            limit,
+        } => {
+            format!("{container} exceeds the {budget} budget: requested {requested}, used {used} of {limit}")
+        }
         model::Error::CriticalContainer { container, status } => {
             format!(
                 "critical container {} exited with: {}",
@@ -0,0 +1,46 @@
+use anyhow::{Context, Result};
+use nix::{
+    libc,
+    sys::termios::{self, SetArg, Termios},
+    unistd,
+};
+use std::{io, os::unix::io::AsRawFd};
+
+/// Puts the terminal of stdin into raw mode. The previous mode is restored on drop.
+pub struct RawMode {
+    termios: Termios,
+}
+
+impl RawMode {
+    /// Enable raw mode if stdin is a terminal
+    pub fn enable() -> Result<Option<RawMode>> {
+        let stdin = io::stdin();
+        if !unistd::isatty(stdin.as_raw_fd()).unwrap_or(false) {
+            return Ok(None);
+        }
+        let termios = termios::tcgetattr(&stdin).context("failed to get terminal attributes")?;
+        let mut raw = termios.clone();
+        termios::cfmakeraw(&mut raw);
+        termios::tcsetattr(&stdin, SetArg::TCSANOW, &raw)
+            .context("failed to set terminal attributes")?;
+        Ok(Some(RawMode { termios }))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        termios::tcsetattr(&io::stdin(), SetArg::TCSANOW, &self.termios).ok();
+    }
+}
+
+/// Rows and columns of the terminal of stdin
+pub fn window_size() -> Option<(u16, u16)> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let result = unsafe { libc::ioctl(libc::STDIN_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0).then_some((size.ws_row, size.ws_col))
+}
@@ -75,7 +75,7 @@ impl Encoder<model::Message> for Codec {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::model::{Message, Notification, Request};
+    use crate::api::model::{ExitReason, ExitStatus, Message, Notification, Request};
     use bytes::BytesMut;
     use proptest::{prelude::Just, prop_oneof, proptest, strategy::Strategy};
 
@@ -96,6 +96,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_exit_notification_without_reason() {
+        let mut message = BytesMut::from(
+            "{\"notification\":{\"exit\":[\"hello:0.0.1\",{\"exit\":{\"code\":1}}]}}\n",
+        );
+        let message = Codec::default()
+            .decode(&mut message)
+            .expect("failed to decode");
+        let notification = Notification::Exit(
+            "hello:0.0.1".try_into().expect("invalid container"),
+            ExitStatus::Exit { code: 1 },
+            ExitReason::Exited,
+        );
+        assert_eq!(message, Some(Message::Notification { notification }));
+    }
+
+    #[test]
+    fn encode_exit_notification() {
+        let notification = Notification::Exit(
+            "hello:0.0.1".try_into().expect("invalid container"),
+            ExitStatus::Signalled { signal: 15 },
+            ExitReason::MaxRuntime,
+        );
+        let mut buffer = BytesMut::new();
+        Codec::default()
+            .encode(Message::Notification { notification }, &mut buffer)
+            .expect("failed to encode");
+        assert_eq!(
+            buffer,
+            "{\"notification\":{\"exit\":[\"hello:0.0.1\",{\"signalled\":{\"signal\":15}},\"max_runtime\"]}}\n"
+        );
+    }
+
     fn mk_message() -> impl Strategy<Value = Message> {
         prop_oneof![
             Just(Message::Request {
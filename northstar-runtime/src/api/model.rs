@@ -33,6 +33,85 @@ pub enum Message {
     Request { request: Request },
     Response { response: Response },
     Notification { notification: Notification },
+    Io { io: Io },
+    Log { log: Log },
+    Download { download: Download },
+}
+
+/// Process io exchanged on a connection after a successful exec request until
+/// the process exited or after a successful attach request until detached
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Io {
+    /// Data for the standard input of the process. An empty buffer closes the standard input.
+    Stdin(#[serde(with = "base64")] Vec<u8>),
+    /// Window size of the terminal of the client
+    Resize {
+        /// Number of rows
+        rows: u16,
+        /// Number of columns
+        cols: u16,
+    },
+    /// Standard output of the process
+    Stdout(#[serde(with = "base64")] Vec<u8>),
+    /// Standard error of the process
+    Stderr(#[serde(with = "base64")] Vec<u8>),
+    /// The process exited. The connection is ready for the next request.
+    Exit(ExitStatus),
+    /// Detach from the terminal of a container. Sent by the client to detach and by the
+    /// runtime once the terminal is closed. The connection is ready for the next request.
+    Detach,
+}
+
+/// Output stream of a container
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// Captured output line of a container
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LogLine {
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    /// Stream the line was written to
+    pub stream: LogStream,
+    /// Line without the newline
+    pub line: String,
+}
+
+/// Log lines sent on a connection after a logs request with `follow` set
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Log {
+    /// Line written by the container
+    Line(LogLine),
+    /// The container exited. The connection is ready for the next request.
+    End,
+}
+
+/// Content of a file sent on a connection after a successful core dump request
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Download {
+    /// Next chunk of the file
+    Chunk(#[serde(with = "base64")] Vec<u8>),
+    /// The file is complete. The connection is ready for the next request.
+    End,
+}
+
+/// Core dump of a container
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CoreDump {
+    /// File name of the core dump
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Milliseconds since the unix epoch when the core dump was written
+    pub created: u64,
 }
 
 /// Notification / Event
@@ -41,11 +120,162 @@ pub enum Message {
 #[allow(missing_docs)]
 pub enum Notification {
     CGroup(Container, CgroupNotification),
-    Exit(Container, ExitStatus),
+    Critical {
+        container: Container,
+        action: CriticalAction,
+    },
+    /// The reason is appended to the tuple of runtimes without exit reasons and
+    /// defaults to `Exited` if missing.
+    Exit(Container, ExitStatus, #[serde(default)] ExitReason),
+    Health(Container, Health),
     Install(Container),
+    Paused(Container),
+    Ready(Container),
+    RestartExhausted(Container, u32),
+    Resumed(Container),
+    Schedule {
+        container: Container,
+        run: ScheduledRun,
+    },
     Shutdown,
     Started(Container),
     Uninstall(Container),
+    Update {
+        from: Container,
+        to: Container,
+        progress: UpdateProgress,
+    },
+}
+
+/// Progress of a container update
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateProgress {
+    /// The new version is installed and the previous version is stopped
+    Stopping,
+    /// The new version is started and watched for the probation period
+    Probation,
+    /// The new version passed the probation period and the previous version is uninstalled
+    Completed,
+    /// The new version failed and is uninstalled. The previous version is started again.
+    RolledBack,
+}
+
+/// Admission budget of the runtime configuration
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Budget {
+    /// Sum of the memory hard limits of the started containers in bytes
+    Memory,
+    /// Sum of the cpu quotas of the started containers in thousandths of a cpu
+    Cpu,
+    /// Number of started containers
+    Containers,
+    /// Number of started containers of a repository
+    RepositoryContainers {
+        /// Repository
+        repository: RepositoryId,
+    },
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Budget::Memory => write!(f, "memory"),
+            Budget::Cpu => write!(f, "cpu"),
+            Budget::Containers => write!(f, "containers"),
+            Budget::RepositoryContainers { repository } => {
+                write!(f, "containers of repository {repository}")
+            }
+        }
+    }
+}
+
+/// Usage of the admission budgets by the started containers
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Budgets {
+    /// Memory hard limits in bytes
+    pub memory: BudgetUsage,
+    /// Cpu quotas in thousandths of a cpu
+    pub cpu: BudgetUsage,
+    /// Started containers
+    pub containers: BudgetUsage,
+    /// Started containers per repository
+    pub repositories: HashMap<RepositoryId, BudgetUsage>,
+}
+
+/// Usage of a single budget
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BudgetUsage {
+    /// Amount used by the started containers
+    pub used: u64,
+    /// Configured limit. Unlimited if not set.
+    pub limit: Option<u64>,
+}
+
+/// State of a service reported by a container
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Notify {
+    /// The container finished its startup and is ready
+    Ready,
+    /// Free form status of the container
+    Status {
+        /// Status text
+        status: String,
+    },
+    /// The container is shutting down
+    Stopping,
+}
+
+/// Outcome of a scheduled run
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledRun {
+    /// The container is started
+    Started,
+    /// The container is still running and the run is skipped
+    Skipped,
+    /// The container is still running and started once it exited
+    Deferred,
+    /// The container failed to start
+    Failed {
+        /// Start error
+        error: Error,
+    },
+}
+
+/// Schedule of a container
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Time of the next run in milliseconds since the unix epoch
+    pub next: u64,
+    /// Set if a run is deferred until the container exited
+    pub deferred: bool,
+}
+
+/// Action taken after a critical container failed
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CriticalAction {
+    /// The container is restarted. Restart number `retry` starting at 1.
+    Restart {
+        /// Number of the restart
+        retry: u32,
+    },
+    /// All containers are stopped and the runtime is restarted
+    RestartRuntime,
+    /// The failure handler container is started
+    FailureHandler {
+        /// Failure handler container
+        container: Container,
+    },
+    /// The system is rebooted
+    Reboot,
+    /// The runtime exits
+    Exit,
 }
 
 /// Cgroup event
@@ -99,6 +329,28 @@ pub enum ConnectNack {
 #[serde(rename_all = "snake_case")]
 #[allow(missing_docs)]
 pub enum Request {
+    Attach {
+        container: Container,
+    },
+    Budgets,
+    CoreDump {
+        container: Container,
+        name: String,
+    },
+    CoreDumps {
+        container: Container,
+    },
+    Exec {
+        container: Container,
+        path: NonNulString,
+        args: Vec<NonNulString>,
+        env: HashMap<NonNulString, NonNulString>,
+        tty: bool,
+    },
+    Heartbeat,
+    History {
+        container: Container,
+    },
     Inspect {
         container: Container,
     },
@@ -107,21 +359,58 @@ pub enum Request {
         repository: RepositoryId,
         size: u64,
     },
+    Journal {
+        /// Clear the journal after it is returned
+        clear: bool,
+    },
     Kill {
         container: Container,
         signal: i32,
     },
     List,
+    Logs {
+        container: Container,
+        /// Stream new lines until the container exits
+        follow: bool,
+        /// Limit the output to the last n lines
+        tail: Option<usize>,
+        /// Limit the output to lines since this time in milliseconds since the unix epoch
+        since: Option<u64>,
+    },
     Mount {
         containers: Vec<Container>,
     },
+    Notify {
+        notify: Notify,
+    },
+    Pause {
+        container: Container,
+    },
+    ReloadConfig,
     Repositories,
+    Resume {
+        container: Container,
+    },
+    Schedules,
     Shutdown,
     Start {
         container: Container,
         init: Option<NonNulString>,
         arguments: Vec<NonNulString>,
         environment: HashMap<NonNulString, NonNulString>,
+        /// Reply once the container is ready instead of once it is started
+        #[serde(default)]
+        wait_ready: bool,
+        /// Seconds after which the container is stopped. Overrides the maximum run
+        /// time of the manifest.
+        #[serde(default)]
+        max_runtime: Option<u64>,
+    },
+    Stop {
+        container: Container,
+        /// Seconds until the container is killed. Defaults to the stop timeout
+        /// of the manifest.
+        timeout: Option<u64>,
     },
     TokenCreate {
         target: Name,
@@ -141,6 +430,16 @@ pub enum Request {
         container: Container,
         wipe: bool,
     },
+    Update {
+        /// Started container that is replaced
+        from: Container,
+        /// Container of the npk streamed after the request
+        to: Container,
+        repository: RepositoryId,
+        size: u64,
+        /// Seconds the new version must run without failure
+        probation: u64,
+    },
 }
 
 /// Token
@@ -184,6 +483,32 @@ pub struct ContainerData {
     pub mounted: bool,
     /// Process if the container is started
     pub process: Option<Process>,
+    /// Restart information if the container has a restart policy
+    pub restart: Option<Restart>,
+    /// Health if the container is started and has a health check
+    pub health: Option<Health>,
+}
+
+/// Container health
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    /// No probe result yet
+    Unknown,
+    /// Probes succeed
+    Healthy,
+    /// The failure threshold is reached
+    Unhealthy,
+}
+
+/// Restart information
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Restart {
+    /// Number of consecutive restarts
+    pub retries: u32,
+    /// Time until the next scheduled restart in nanoseconds
+    pub next: Option<u64>,
 }
 
 /// Process information
@@ -194,6 +519,12 @@ pub struct Process {
     pub pid: Pid,
     /// Process uptime in nanoseconds
     pub uptime: u64,
+    /// Set if the process is frozen
+    pub paused: bool,
+    /// Set if the container is ready
+    pub ready: bool,
+    /// Last status reported by the container
+    pub status: Option<String>,
     /// Container statistics
     pub statistics: ContainerStats,
 }
@@ -234,6 +565,66 @@ pub enum KillResult {
     Error { container: Container, error: Error },
 }
 
+/// Attach result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum AttachResult {
+    Ok { container: Container },
+    Error { container: Container, error: Error },
+}
+
+/// Exec result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum ExecResult {
+    Ok { container: Container, pid: Pid },
+    Error { container: Container, error: Error },
+}
+
+/// Logs result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum LogsResult {
+    Ok {
+        container: Container,
+        lines: Vec<LogLine>,
+    },
+    Error {
+        container: Container,
+        error: Error,
+    },
+}
+
+/// Pause result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum PauseResult {
+    Ok { container: Container },
+    Error { container: Container, error: Error },
+}
+
+/// Resume result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum ResumeResult {
+    Ok { container: Container },
+    Error { container: Container, error: Error },
+}
+
+/// Stop result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum StopResult {
+    Ok { container: Container },
+    Error { container: Container, error: Error },
+}
+
 /// Installation result
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -252,6 +643,65 @@ pub enum UninstallResult {
     Error { container: Container, error: Error },
 }
 
+/// Configuration reload result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum ReloadConfigResult {
+    Ok,
+    Error { error: Error },
+}
+
+/// Journal result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum JournalResult {
+    Ok { journal: Journal },
+    Error { error: Error },
+}
+
+/// Desired state of the containers that is restored when the runtime starts
+#[derive(Clone, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Journal {
+    /// Installed containers and their desired state
+    pub containers: HashMap<Container, JournalEntry>,
+}
+
+/// Desired state of a container
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Repository the container is installed in
+    pub repository: RepositoryId,
+    /// The container is mounted
+    pub mounted: bool,
+    /// Arguments of the start if the container is started
+    pub started: Option<JournalStart>,
+}
+
+/// Arguments of a container start
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct JournalStart {
+    /// Init that overrides the init of the manifest
+    pub init: Option<NonNulString>,
+    /// Arguments that override the arguments of the manifest
+    pub arguments: Vec<NonNulString>,
+    /// Environment that overrides the environment of the manifest
+    pub environment: HashMap<NonNulString, NonNulString>,
+    /// Seconds after which the container is stopped
+    #[serde(default)]
+    pub max_runtime: Option<u64>,
+}
+
+/// Update result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum UpdateResult {
+    Ok { from: Container, to: Container },
+    Error { container: Container, error: Error },
+}
+
 /// Inspect result
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -267,25 +717,103 @@ pub enum InspectResult {
     },
 }
 
+/// Core dump result. A successful result is followed by the content of the core dump.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum CoreDumpResult {
+    Ok {
+        container: Container,
+        core_dump: CoreDump,
+    },
+    Error {
+        container: Container,
+        error: Error,
+    },
+}
+
+/// Core dumps result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum CoreDumpsResult {
+    Ok {
+        container: Container,
+        core_dumps: Vec<CoreDump>,
+    },
+    Error {
+        container: Container,
+        error: Error,
+    },
+}
+
+/// History result
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum HistoryResult {
+    Ok {
+        container: Container,
+        runs: Vec<Run>,
+    },
+    Error {
+        container: Container,
+        error: Error,
+    },
+}
+
+/// Terminated run of a container
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Run {
+    /// Milliseconds since the unix epoch when the container was started
+    pub started: u64,
+    /// Milliseconds the container was running
+    pub duration: u64,
+    /// Exit status of the init process
+    pub exit_status: ExitStatus,
+    /// Reason of the termination
+    pub reason: ExitReason,
+    /// Number of processes killed by the OOM killer
+    pub oom_kills: u64,
+    /// Arguments of the start
+    pub arguments: JournalStart,
+}
+
 /// Response
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[allow(missing_docs)]
 pub enum Response {
+    Attach(AttachResult),
+    Budgets(Budgets),
+    CoreDump(CoreDumpResult),
+    CoreDumps(CoreDumpsResult),
+    Exec(ExecResult),
+    Heartbeat,
+    History(HistoryResult),
     Ident(Container),
     Inspect(InspectResult),
     Install(InstallResult),
+    Journal(JournalResult),
     Kill(KillResult),
     List(Vec<Container>),
+    Logs(LogsResult),
     Mount(Vec<MountResult>),
+    Notify,
+    Pause(PauseResult),
     PermissionDenied(Request),
+    ReloadConfig(ReloadConfigResult),
     Repositories(HashSet<RepositoryId>),
+    Resume(ResumeResult),
+    Schedules(HashMap<Container, Schedule>),
     Shutdown,
     Start(StartResult),
+    Stop(StopResult),
     Token(Token),
     TokenVerification(VerificationResult),
     Umount(Vec<UmountResult>),
     Uninstall(UninstallResult),
+    Update(UpdateResult),
 }
 
 /// Container exit status
@@ -332,6 +860,32 @@ impl std::fmt::Display for ExitStatus {
     }
 }
 
+/// Reason of the termination of a container
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    /// The container exited on its own
+    #[default]
+    Exited,
+    /// The container was stopped by a request or the runtime
+    Stopped,
+    /// The container was stopped after its maximum run time elapsed
+    MaxRuntime,
+    /// The container was killed because it did not report readiness in time
+    NotReady,
+}
+
+impl std::fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitReason::Exited => write!(f, "exited"),
+            ExitReason::Stopped => write!(f, "stopped"),
+            ExitReason::MaxRuntime => write!(f, "max runtime"),
+            ExitReason::NotReady => write!(f, "not ready"),
+        }
+    }
+}
+
 /// API error
 #[derive(Clone, Eq, thiserror::Error, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -366,6 +920,11 @@ pub enum Error {
         resource: Name,
         version: String,
     },
+    StartContainerMissingDependency {
+        container: Container,
+        dependency: Name,
+        version: String,
+    },
     StartContainerFailed {
         container: Container,
         error: String,
@@ -373,16 +932,43 @@ pub enum Error {
     StopContainerNotStarted {
         container: Container,
     },
+    ContainerNotStarted {
+        container: Container,
+    },
+    ContainerPaused {
+        container: Container,
+    },
+    ContainerNotPaused {
+        container: Container,
+    },
+    ContainerNoTerminal {
+        container: Container,
+    },
     InvalidRepository {
         repository: RepositoryId,
     },
     InstallDuplicate {
         container: Container,
     },
+    DependencyCycle {
+        container: Container,
+    },
+    HookFailed {
+        container: Container,
+        hook: String,
+        error: String,
+    },
     CriticalContainer {
         container: Container,
         status: ExitStatus,
     },
+    BudgetExceeded {
+        container: Container,
+        budget: Budget,
+        requested: u64,
+        used: u64,
+        limit: u64,
+    },
     Unexpected {
         error: String,
     },
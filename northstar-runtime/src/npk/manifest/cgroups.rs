@@ -14,6 +14,13 @@ pub struct CGroups {
     pub cpu: Option<CpuResources>,
     /// Memory controller
     pub memory: Option<MemoryResources>,
+    /// Create a cgroup namespace with the cgroup of the container as root
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub namespace: bool,
+    /// Delegate the cgroup of the container. The container can create sub groups within
+    /// the limits of its cgroup. Requires a cgroup namespace and cgroups v2.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub delegate: bool,
 }
 
 /// Bkio device resource
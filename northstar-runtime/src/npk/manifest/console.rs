@@ -22,28 +22,56 @@ pub struct Console {
 #[derive(Clone, Eq, EnumIter, EnumCount, PartialEq, Debug, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Permission {
+    /// Attach to the terminal of a container
+    Attach,
+    /// Show the usage of the admission budgets
+    Budgets,
+    /// List and download the core dumps of a container
+    CoreDumps,
+    /// Execute a process in a started container
+    Exec,
+    /// Health heartbeat
+    Heartbeat,
+    /// Show the terminated runs of a container
+    History,
     /// Identification
     Ident,
     /// Inspect a container
     Inspect,
     /// Install a container
     Install,
+    /// Inspect or clear the desired state journal
+    Journal,
     /// Send a singal to a container
     Kill,
     /// List all containers
     List,
+    /// Read the output of a container
+    Logs,
     /// Notifications
     Notifications,
+    /// Report the readiness and status of the container
+    Notify,
     /// Mount a container
     Mount,
+    /// Pause a container
+    Pause,
+    /// Reload the runtime configuration
+    ReloadConfig,
     /// List repositories
     Repositories,
+    /// Resume a paused container
+    Resume,
+    /// List the schedules of the containers
+    Schedules,
     /// Shutdown the runtime
     Shutdown,
     /// Start a container
     Start,
     /// Start a container with custom init, args and env
     StartCommand,
+    /// Stop a container
+    Stop,
     /// Token creation
     TokenCreate,
     /// Token verification
@@ -52,6 +80,8 @@ pub enum Permission {
     Umount,
     /// Uninstall a container
     Uninstall,
+    /// Update a started container to a new version
+    Update,
 }
 
 #[allow(clippy::unwrap_used)]
@@ -0,0 +1,26 @@
+use crate::common::{name::Name, version::VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// Action taken when a container with autostart `critical` exits with a non zero exit
+/// code and is not restarted by its restart policy. The runtime exits if no policy is
+/// configured or the action cannot be carried out.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Critical {
+    /// Restart the container up to `max_retries` times before the runtime exits.
+    Restart {
+        /// Maximum number of restarts
+        max_retries: u32,
+    },
+    /// Stop all containers and restart the runtime.
+    RestartRuntime,
+    /// Start the failure handler container `container`.
+    FailureHandler {
+        /// Name of the failure handler container
+        container: Name,
+        /// Version requirement of the failure handler container
+        version: VersionReq,
+    },
+    /// Reboot the system with the command configured in the runtime configuration.
+    Reboot,
+}
@@ -0,0 +1,13 @@
+use crate::common::version::VersionReq;
+use serde::{Deserialize, Serialize};
+
+/// Dependency to another container that is started before this container.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    /// Version requirement
+    pub version: VersionReq,
+    /// Wait until the dependency is ready before starting this container.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ready: bool,
+}
@@ -0,0 +1,122 @@
+use crate::common::non_nul_string::NonNulString;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use validator::{Validate, ValidationError};
+
+/// Health probe
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Probe {
+    /// Execute a binary in the container like an exec request. The probe runs in the
+    /// namespaces and cgroups of the container with its uid and gid. The probe succeeds
+    /// if the binary exits with 0 within the timeout.
+    Exec {
+        /// Path of the binary within the container
+        path: NonNulString,
+        /// Arguments
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<NonNulString>,
+    },
+    /// Connect to one of the stream or seqpacket sockets configured in the manifest. The
+    /// probe succeeds if the container accepts the connection and writes to or closes it
+    /// within the timeout.
+    Socket {
+        /// Socket name
+        name: NonNulString,
+    },
+    /// The container sends a heartbeat request via its console at least once per interval.
+    Heartbeat,
+}
+
+/// Health check configuration
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate"))]
+pub struct Health {
+    /// Probe
+    pub probe: Probe,
+    /// Interval between two probes
+    #[serde(with = "humantime_serde", default = "default_interval")]
+    pub interval: Duration,
+    /// Timeout of a single probe
+    #[serde(with = "humantime_serde", default = "default_timeout")]
+    pub timeout: Duration,
+    /// Number of consecutive failed probes until the container is unhealthy
+    #[serde(default = "default_failure_threshold")]
+    #[validate(range(min = 1, message = "failure threshold must be greater than 0"))]
+    pub failure_threshold: u32,
+    /// Restart the container once it is unhealthy. The settings of the restart section
+    /// apply if present.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub restart: bool,
+}
+
+const fn default_interval() -> Duration {
+    Duration::from_secs(10)
+}
+
+const fn default_timeout() -> Duration {
+    Duration::from_secs(1)
+}
+
+const fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn validate(health: &Health) -> Result<(), ValidationError> {
+    if health.interval.is_zero() {
+        return Err(ValidationError::new("health interval must not be zero"));
+    }
+    if health.timeout > health.interval {
+        return Err(ValidationError::new(
+            "health timeout must not exceed the interval",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Health, Probe};
+    use anyhow::Result;
+    use std::time::Duration;
+    use validator::Validate;
+
+    #[test]
+    fn parse_exec() -> Result<()> {
+        let health = serde_yaml::from_str::<Health>(
+            "probe: !exec\n  path: /bin/check\n  args: [--fast]\ninterval: 5s\nrestart: true",
+        )?;
+        assert!(matches!(health.probe, Probe::Exec { ref args, .. } if args.len() == 1));
+        assert_eq!(health.interval, Duration::from_secs(5));
+        assert_eq!(health.timeout, Duration::from_secs(1));
+        assert_eq!(health.failure_threshold, 3);
+        assert!(health.restart);
+        health.validate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn parse_socket() -> Result<()> {
+        let health = serde_yaml::from_str::<Health>("probe: !socket\n  name: foo")?;
+        assert!(matches!(health.probe, Probe::Socket { ref name } if name.as_str() == "foo"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_heartbeat() -> Result<()> {
+        let health = serde_yaml::from_str::<Health>("probe: heartbeat")?;
+        assert_eq!(health.probe, Probe::Heartbeat);
+        assert!(!health.restart);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid() -> Result<()> {
+        let health = serde_yaml::from_str::<Health>("probe: heartbeat\nfailure_threshold: 0")?;
+        assert!(health.validate().is_err());
+        let health = serde_yaml::from_str::<Health>("probe: heartbeat\ninterval: 1s\ntimeout: 2s")?;
+        assert!(health.validate().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use crate::common::{name::Name, non_nul_string::NonNulString, version::VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Duration};
+
+/// Hooks run around the lifecycle of a container
+#[derive(Clone, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    /// Run before the container is mounted. A failing hook vetoes the mount.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_mount: Vec<Hook>,
+    /// Run before the container is started. A failing hook vetoes the start.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_start: Vec<Hook>,
+    /// Run after the container is started.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_start: Vec<Hook>,
+    /// Run after the container exited.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_stop: Vec<Hook>,
+    /// Run before the container is uninstalled. A failing hook vetoes the uninstall.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_uninstall: Vec<Hook>,
+}
+
+impl Hooks {
+    /// Hooks of `stage`
+    pub fn stage(&self, stage: Stage) -> &[Hook] {
+        match stage {
+            Stage::PreMount => &self.pre_mount,
+            Stage::PreStart => &self.pre_start,
+            Stage::PostStart => &self.post_start,
+            Stage::PostStop => &self.post_stop,
+            Stage::PreUninstall => &self.pre_uninstall,
+        }
+    }
+
+    /// Iterate all hooks of all stages
+    pub fn iter(&self) -> impl Iterator<Item = &Hook> {
+        self.pre_mount
+            .iter()
+            .chain(&self.pre_start)
+            .chain(&self.post_start)
+            .chain(&self.post_stop)
+            .chain(&self.pre_uninstall)
+    }
+
+    /// Returns true if no hook is configured
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+/// Lifecycle stage of a container
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Before the container is mounted
+    PreMount,
+    /// Before the container is started
+    PreStart,
+    /// After the container is started
+    PostStart,
+    /// After the container exited
+    PostStop,
+    /// Before the container is uninstalled
+    PreUninstall,
+}
+
+impl Stage {
+    /// Returns true if a failing hook of this stage vetoes the action
+    pub fn is_veto(&self) -> bool {
+        matches!(
+            self,
+            Stage::PreMount | Stage::PreStart | Stage::PreUninstall
+        )
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::PreMount => write!(f, "pre_mount"),
+            Stage::PreStart => write!(f, "pre_start"),
+            Stage::PostStart => write!(f, "post_start"),
+            Stage::PostStop => write!(f, "post_stop"),
+            Stage::PreUninstall => write!(f, "pre_uninstall"),
+        }
+    }
+}
+
+/// Hook
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Hook {
+    /// Command executed on the host. The hook fails if the command exits with a non
+    /// zero exit code or did not exit within `timeout`. Command hooks can only be
+    /// configured in the runtime configuration.
+    Command {
+        /// Path and arguments of the command
+        command: Vec<NonNulString>,
+        /// Time after which the command is killed
+        #[serde(with = "humantime_serde", default = "default_timeout")]
+        timeout: Duration,
+    },
+    /// Hook container that is started. The hook fails if the container cannot be started.
+    /// The container is killed if it is still running after `timeout`.
+    Container {
+        /// Name of the hook container
+        container: Name,
+        /// Version requirement of the hook container
+        version: VersionReq,
+        /// Arguments passed to the hook container
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<NonNulString>,
+        /// Time after which the hook container is killed
+        #[serde(with = "humantime_serde", default = "default_timeout")]
+        timeout: Duration,
+    },
+}
+
+const fn default_timeout() -> Duration {
+    Duration::from_secs(10)
+}
@@ -8,6 +8,10 @@ pub struct Io {
     pub stdout: Output,
     /// stderr configuration
     pub stderr: Output,
+    /// Allocate a pseudo terminal for stdin, stdout and stderr. The output of the
+    /// terminal is handled according to `stdout` and clients can attach to it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tty: bool,
 }
 
 /// Io redirection for stdout/stderr
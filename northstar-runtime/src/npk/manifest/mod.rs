@@ -11,6 +11,7 @@ use serde_with::{
 use std::{
     collections::{HashMap, HashSet},
     str::FromStr,
+    time::Duration,
 };
 use thiserror::Error;
 use validator::{Validate, ValidationError, ValidationErrors};
@@ -25,22 +26,44 @@ pub mod capabilities;
 pub mod cgroups;
 /// Northstar console configuration
 pub mod console;
+/// Critical container policy
+pub mod critical;
+/// Container dependencies
+pub mod dependency;
+/// Health checks
+pub mod health;
+/// Lifecycle hooks
+pub mod hooks;
 /// Container io
 pub mod io;
 /// Container mounts
 pub mod mount;
 /// Networking
 pub mod network;
+/// Readiness
+pub mod ready;
+/// Restart policy
+pub mod restart;
 /// Linux resource limits
 pub mod rlimit;
 /// Scheduling
 pub mod sched;
+/// Time triggered starts
+pub mod schedule;
 /// Seccomp
 pub mod seccomp;
 /// SE Linux
 pub mod selinux;
+/// Signals
+pub mod signal;
 /// Sockets
 pub mod socket;
+/// Time namespace configuration
+pub mod time_namespace;
+/// User namespace configuration
+pub mod user_namespace;
+/// Hostname and domainname
+pub mod uts;
 
 #[cfg(test)]
 mod test;
@@ -103,11 +126,9 @@ pub struct Manifest {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     #[validate(custom = "validate_env")]
     pub env: HashMap<NonNulString, NonNulString>,
-    /// UID
-    #[validate(range(min = 1, message = "uid must be greater than 0"))]
+    /// UID. Root is only valid in a user namespace.
     pub uid: u16,
-    /// GID
-    #[validate(range(min = 1, message = "gid must be greater than 0"))]
+    /// GID. Root is only valid in a user namespace.
     pub gid: u16,
     /// Scheduling parameter.
     #[validate]
@@ -122,11 +143,56 @@ pub struct Manifest {
     pub mounts: HashMap<mount::MountPoint, mount::Mount>,
     /// Autostart this container upon northstar startup
     pub autostart: Option<autostart::Autostart>,
+    /// Action taken when a critical container fails
+    pub critical: Option<critical::Critical>,
+    /// Containers that are started before this container
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "maps_duplicate_key_is_error::deserialize"
+    )]
+    pub depends_on: HashMap<Name, dependency::Dependency>,
+    /// Health check
+    #[validate]
+    pub health: Option<health::Health>,
+    /// Condition for the container to be ready. Defaults to `started`.
+    pub ready: Option<ready::Ready>,
+    /// Time granted to a container with `ready: notify` to report its readiness before
+    /// it is killed
+    #[serde(default, with = "humantime_serde")]
+    pub ready_timeout: Option<Duration>,
+    /// Restart policy applied when the container exits
+    #[validate]
+    pub restart: Option<restart::Restart>,
+    /// Signal sent to the container upon a stop request. Defaults to `SIGTERM`.
+    pub stop_signal: Option<signal::Signal>,
+    /// Time granted to the container to exit after the stop signal before it is killed
+    #[serde(default, with = "humantime_serde")]
+    pub stop_timeout: Option<Duration>,
+    /// Time after which the container is stopped with the stop signal and stop timeout
+    #[serde(default, with = "humantime_serde")]
+    pub max_runtime: Option<Duration>,
+    /// Time triggered starts of the container
+    #[validate]
+    pub schedule: Option<schedule::Schedule>,
+    /// Hook containers run around the lifecycle of the container
+    #[serde(default, skip_serializing_if = "hooks::Hooks::is_empty")]
+    pub hooks: hooks::Hooks,
     /// CGroup configuration
     pub cgroups: Option<self::cgroups::CGroups>,
     /// Network configuration. Unshare the network if omitted.
     #[validate(custom = "network::validate")]
     pub network: Option<Network>,
+    /// User namespace with uid and gid mappings. The container uses the host ids if omitted.
+    #[validate]
+    pub user_namespace: Option<user_namespace::UserNamespace>,
+    /// Hostname of the container. `<NAME>` and `<VERSION>` are replaced with the name and
+    /// version of the container. The container uses the host name if omitted.
+    pub hostname: Option<NonNulString>,
+    /// Domainname of the container. Supports the same placeholders as `hostname`.
+    pub domainname: Option<NonNulString>,
+    /// Time namespace with clock offsets. The container uses the host clocks if omitted.
+    pub time_namespace: Option<time_namespace::TimeNamespace>,
     /// Seccomp configuration
     #[validate(custom = "seccomp::validate")]
     pub seccomp: Option<Seccomp>,
@@ -217,16 +283,192 @@ fn validate(manifest: &Manifest) -> Result<(), ValidationError> {
             || !manifest.env.is_empty()
             || !manifest.suppl_groups.is_empty()
             || manifest.autostart.is_some()
+            || !manifest.depends_on.is_empty()
+            || manifest.restart.is_some()
+            || manifest.health.is_some()
+            || manifest.stop_signal.is_some()
+            || manifest.stop_timeout.is_some()
+            || manifest.max_runtime.is_some()
+            || !manifest.hooks.is_empty()
+            || manifest.schedule.is_some()
             || manifest.cgroups.is_some()
             || manifest.io.is_some()
+            || manifest.user_namespace.is_some()
+            || manifest.time_namespace.is_some()
+            || manifest.hostname.is_some()
+            || manifest.domainname.is_some()
             || manifest.seccomp.is_some())
     {
         return Err(ValidationError::new(
             "resource containers must not define any of the following manifest entries:\
-                args, env, autostart, cgroups, seccomp, capabilities, suppl_groups, io",
+                args, env, autostart, depends_on, restart, health, stop_signal, stop_timeout, max_runtime, hooks, schedule, cgroups, seccomp, capabilities, suppl_groups, io, user_namespace, time_namespace, hostname, domainname",
         ));
     }
 
+    match &manifest.user_namespace {
+        // Root is reserved to the runtime
+        None if manifest.uid == 0 => {
+            return Err(ValidationError::new("uid must be greater than 0"));
+        }
+        None if manifest.gid == 0 => {
+            return Err(ValidationError::new("gid must be greater than 0"));
+        }
+        None => (),
+        // The container ids and socket ownerships must be mapped to host ids
+        Some(user_namespace) => {
+            let uids = manifest
+                .sockets
+                .values()
+                .filter_map(|socket| socket.uid)
+                .chain(Some(manifest.uid.into()));
+            let gids = manifest
+                .sockets
+                .values()
+                .filter_map(|socket| socket.gid)
+                .chain(Some(manifest.gid.into()));
+            if uids
+                .into_iter()
+                .any(|uid| user_namespace.host_uid(uid).is_none())
+            {
+                return Err(ValidationError::new(
+                    "uid is not mapped in the user namespace",
+                ));
+            }
+            if gids
+                .into_iter()
+                .any(|gid| user_namespace.host_gid(gid).is_none())
+            {
+                return Err(ValidationError::new(
+                    "gid is not mapped in the user namespace",
+                ));
+            }
+        }
+    }
+
+    // A critical policy applies to critical containers only
+    if manifest.critical.is_some() && manifest.autostart != Some(autostart::Autostart::Critical) {
+        return Err(ValidationError::new(
+            "critical policy requires autostart critical",
+        ));
+    }
+
+    // A container cannot be its own failure handler
+    if let Some(critical::Critical::FailureHandler { container, .. }) = &manifest.critical {
+        if container == &manifest.name {
+            return Err(ValidationError::new(
+                "container must not be its own failure handler",
+            ));
+        }
+    }
+
+    // A container cannot depend on itself
+    if manifest.depends_on.contains_key(&manifest.name) {
+        return Err(ValidationError::new("container must not depend on itself"));
+    }
+
+    // Host commands are reserved to the runtime configuration and a container cannot
+    // be its own hook
+    for hook in manifest.hooks.iter() {
+        match hook {
+            hooks::Hook::Command { .. } => {
+                return Err(ValidationError::new(
+                    "hooks in the manifest must be containers",
+                ));
+            }
+            hooks::Hook::Container { container, .. } if container == &manifest.name => {
+                return Err(ValidationError::new("container must not be its own hook"));
+            }
+            hooks::Hook::Container { .. } => (),
+        }
+    }
+
+    // Health probes must reference a configured connection oriented socket or console
+    match manifest.health.as_ref().map(|health| &health.probe) {
+        Some(health::Probe::Socket { name }) => match manifest.sockets.get(name) {
+            None => {
+                return Err(ValidationError::new(
+                    "health probe references an unknown socket",
+                ))
+            }
+            Some(socket) if socket.r#type == socket::Type::Datagram => {
+                return Err(ValidationError::new(
+                    "health probe requires a stream or seqpacket socket",
+                ))
+            }
+            Some(_) => (),
+        },
+        Some(health::Probe::Heartbeat) if manifest.console.is_none() => {
+            return Err(ValidationError::new(
+                "health heartbeat probe requires a console",
+            ));
+        }
+        _ => (),
+    }
+
+    // Readiness is reported with a notify request on the console
+    let notify = manifest.console.as_ref().map_or(false, |console| {
+        console.permissions.contains(&console::Permission::Notify)
+    });
+    match manifest.ready {
+        Some(ready::Ready::Notify) if !notify => {
+            return Err(ValidationError::new(
+                "ready notify requires the console permission notify",
+            ));
+        }
+        Some(ready::Ready::Notify) => (),
+        _ if manifest.ready_timeout.is_some() => {
+            return Err(ValidationError::new("ready timeout requires ready notify"));
+        }
+        _ => (),
+    }
+
+    if manifest.max_runtime == Some(Duration::ZERO) {
+        return Err(ValidationError::new("max runtime must not be zero"));
+    }
+
+    // Hostname and domainname are valid after the placeholders are replaced
+    for template in manifest.hostname.iter().chain(&manifest.domainname) {
+        uts::validate(&uts::render(template, &manifest.name, &manifest.version))?;
+    }
+
+    // The cgroup mount is the root of the cgroup namespace
+    let namespace = manifest
+        .cgroups
+        .as_ref()
+        .map(|c| c.namespace)
+        .unwrap_or(false);
+    if !namespace
+        && manifest
+            .mounts
+            .values()
+            .any(|mount| matches!(mount, mount::Mount::Cgroup))
+    {
+        return Err(ValidationError::new(
+            "cgroup mount requires a cgroup namespace",
+        ));
+    }
+    if !namespace
+        && manifest
+            .cgroups
+            .as_ref()
+            .map(|c| c.delegate)
+            .unwrap_or(false)
+    {
+        return Err(ValidationError::new(
+            "cgroup delegation requires a cgroup namespace",
+        ));
+    }
+
+    // The devpts instance of a terminal is mounted into the dev mount
+    if manifest.io.as_ref().map(|io| io.tty).unwrap_or(false)
+        && !manifest
+            .mounts
+            .values()
+            .any(|mount| matches!(mount, mount::Mount::Dev))
+    {
+        return Err(ValidationError::new("io tty requires a dev mount"));
+    }
+
     Ok(())
 }
 
@@ -58,6 +58,13 @@ pub enum Mount {
     /// Bind mount of a host dir with options
     #[serde(rename = "bind")]
     Bind(Bind),
+    /// Mount the cgroup of this container. Requires a cgroup namespace.
+    #[serde(rename = "cgroup")]
+    Cgroup,
+    /// Mount the core dump directory of this container rw. The size of core dumps is
+    /// limited by the runtime configuration.
+    #[serde(rename = "core_dumps")]
+    CoreDumps,
     /// Use a minimal dev tree
     #[serde(rename = "dev")]
     Dev,
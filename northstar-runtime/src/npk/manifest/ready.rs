@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Condition for a container to be considered ready
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ready {
+    /// The container is ready once it is started
+    #[default]
+    Started,
+    /// The container reports its readiness with a notify request on its console
+    Notify,
+}
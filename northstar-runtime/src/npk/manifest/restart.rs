@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::time::Duration;
+use validator::{Validate, ValidationError};
+
+/// Restart policy
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Policy {
+    /// Never restart the container.
+    Never,
+    /// Restart the container if it exited with a non zero exit code or was terminated by a signal.
+    OnFailure,
+    /// Restart the container regardless of the exit status.
+    Always,
+}
+
+/// Restart configuration. Containers are not restarted if they are stopped via
+/// the console or the runtime shuts down.
+#[skip_serializing_none]
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate"))]
+pub struct Restart {
+    /// Restart policy.
+    pub policy: Policy,
+    /// Maximum number of consecutive restarts. Unlimited if omitted.
+    pub max_retries: Option<u32>,
+    /// Delay before the first restart. The delay is doubled with each consecutive restart.
+    #[serde(with = "humantime_serde", default = "default_backoff")]
+    pub backoff: Duration,
+    /// Upper bound of the delay between two restarts.
+    #[serde(with = "humantime_serde", default = "default_max_backoff")]
+    pub max_backoff: Duration,
+    /// Reset the retry counter if the container ran at least this long.
+    #[serde(with = "humantime_serde", default = "default_reset_after")]
+    pub reset_after: Duration,
+}
+
+impl Restart {
+    /// Returns true if a container that exited with `success` shall be restarted.
+    pub fn applies(&self, success: bool) -> bool {
+        match self.policy {
+            Policy::Never => false,
+            Policy::OnFailure => !success,
+            Policy::Always => true,
+        }
+    }
+
+    /// Delay before restart number `retry` (starting at 0).
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+const fn default_backoff() -> Duration {
+    Duration::from_secs(1)
+}
+
+const fn default_max_backoff() -> Duration {
+    Duration::from_secs(60)
+}
+
+const fn default_reset_after() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn validate(restart: &Restart) -> Result<(), ValidationError> {
+    if restart.backoff > restart.max_backoff {
+        return Err(ValidationError::new(
+            "restart backoff must not exceed max_backoff",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Policy, Restart};
+    use anyhow::Result;
+    use std::time::Duration;
+    use validator::Validate;
+
+    #[test]
+    fn parse_defaults() -> Result<()> {
+        let restart = serde_yaml::from_str::<Restart>("policy: on_failure")?;
+        assert_eq!(restart.policy, Policy::OnFailure);
+        assert_eq!(restart.max_retries, None);
+        assert_eq!(restart.backoff, Duration::from_secs(1));
+        assert_eq!(restart.max_backoff, Duration::from_secs(60));
+        assert_eq!(restart.reset_after, Duration::from_secs(60));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_backoff() -> Result<()> {
+        let restart =
+            serde_yaml::from_str::<Restart>("policy: always\nbackoff: 10s\nmax_backoff: 1s")?;
+        assert!(restart.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn delay() -> Result<()> {
+        let restart = serde_yaml::from_str::<Restart>(
+            "policy: always\nbackoff: 100ms\nmax_backoff: 1s\nmax_retries: 5",
+        )?;
+        assert_eq!(restart.delay(0), Duration::from_millis(100));
+        assert_eq!(restart.delay(1), Duration::from_millis(200));
+        assert_eq!(restart.delay(3), Duration::from_millis(800));
+        assert_eq!(restart.delay(4), Duration::from_secs(1));
+        assert_eq!(restart.delay(100), Duration::from_secs(1));
+        Ok(())
+    }
+
+    #[test]
+    fn applies() {
+        let restart = |policy| Restart {
+            policy,
+            max_retries: None,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(1),
+            reset_after: Duration::from_secs(1),
+        };
+        assert!(!restart(Policy::Never).applies(false));
+        assert!(restart(Policy::OnFailure).applies(false));
+        assert!(!restart(Policy::OnFailure).applies(true));
+        assert!(restart(Policy::Always).applies(true));
+    }
+}
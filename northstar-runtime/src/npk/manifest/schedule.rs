@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use validator::{Validate, ValidationError};
+
+/// Number of days searched for the next match of a cron expression. Covers leap days.
+const MAX_DAYS: u64 = 5 * 366;
+
+/// Time triggered start of a container. The container is started with the arguments
+/// and environment of the manifest.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate"))]
+pub struct Schedule {
+    /// Cron expression evaluated in UTC
+    pub cron: Option<Cron>,
+    /// Fixed interval between two runs
+    #[serde(default, with = "humantime_serde")]
+    pub interval: Option<Duration>,
+    /// Upper bound of a random delay added to each run
+    #[serde(with = "humantime_serde", default)]
+    pub jitter: Duration,
+    /// Skip a run if the container is still running. If not set the run is deferred
+    /// until the container exited.
+    #[serde(default = "default_skip_if_running")]
+    pub skip_if_running: bool,
+}
+
+impl Schedule {
+    /// Time of the next run after `now` without jitter
+    pub fn next(&self, now: SystemTime) -> Option<SystemTime> {
+        match (&self.cron, self.interval) {
+            (Some(cron), _) => cron.next_after(now),
+            (None, Some(interval)) => now.checked_add(interval),
+            (None, None) => None,
+        }
+    }
+}
+
+const fn default_skip_if_running() -> bool {
+    true
+}
+
+fn validate(schedule: &Schedule) -> Result<(), ValidationError> {
+    match (&schedule.cron, schedule.interval) {
+        (Some(_), None) => Ok(()),
+        (None, Some(interval)) if interval > Duration::ZERO => Ok(()),
+        (None, Some(_)) => Err(ValidationError::new("schedule interval must not be zero")),
+        _ => Err(ValidationError::new(
+            "schedule requires either cron or interval",
+        )),
+    }
+}
+
+/// Cron expression with the fields minute, hour, day of month, month and day of week.
+/// Fields are `*`, values, ranges (`1-5`), steps (`*/15`, `0-30/10`) or lists thereof.
+/// If both day of month and day of week are restricted a day matching either field
+/// matches. The shortcuts `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`
+/// are supported.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Cron {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Cron {
+    /// First time strictly after `time` that matches this expression
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let start = secs / 60 + 1;
+        let first = start / 1440;
+        for day in first..first + MAX_DAYS {
+            if !self.matches_day(day) {
+                continue;
+            }
+            let from = if day == first { start % 1440 } else { 0 };
+            for minute in from..1440 {
+                if bit(self.hours, minute / 60) && bit(self.minutes, minute % 60) {
+                    return Some(UNIX_EPOCH + Duration::from_secs((day * 1440 + minute) * 60));
+                }
+            }
+        }
+        None
+    }
+
+    /// Day `day` since the unix epoch matches the month, day of month and day of week
+    fn matches_day(&self, day: u64) -> bool {
+        let (month, day_of_month) = civil_from_days(day);
+        // The unix epoch is a thursday
+        let weekday = (day + 4) % 7;
+        if !bit(self.months, month) {
+            return false;
+        }
+        let day_of_month = bit(self.days, day_of_month);
+        let weekday = bit(self.weekdays, weekday);
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day_of_month || weekday,
+            _ => day_of_month && weekday,
+        }
+    }
+}
+
+impl FromStr for Cron {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" => "0 0 1 1 *",
+            expression => expression,
+        };
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+        if fields.len() != 5 {
+            return Err(format!("invalid cron expression {s}: expected 5 fields"));
+        }
+        let field = |n: usize, min: u64, max: u64| {
+            parse_field(fields[n], min, max)
+                .map_err(|e| format!("invalid cron expression {s}: {e}"))
+        };
+        let weekdays = field(4, 0, 7)?;
+        Ok(Cron {
+            expression: s.to_string(),
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)?,
+            days: field(2, 1, 31)?,
+            months: field(3, 1, 12)?,
+            // 7 is an alias of sunday
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            days_restricted: !fields[2].starts_with('*'),
+            weekdays_restricted: !fields[4].starts_with('*'),
+        })
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl Serialize for Cron {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.expression)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cron {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Cron::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse a comma separated list of values, ranges and steps into a bit set
+fn parse_field(field: &str, min: u64, max: u64) -> Result<u64, String> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid step {step}"))?;
+                if step == 0 {
+                    return Err("step must not be zero".into());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let value = |value: &str| {
+            value
+                .parse::<u64>()
+                .ok()
+                .filter(|value| (min..=max).contains(value))
+                .ok_or_else(|| format!("invalid value {value}"))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // A single value with a step runs until the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(format!("invalid range {range}"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn bit(set: u64, n: u64) -> bool {
+    set & (1 << n) != 0
+}
+
+/// Month and day of month of `days` since the unix epoch
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 2023-03-01T12:00:00Z is a wednesday
+    const WEDNESDAY: u64 = 1677672000;
+
+    fn next(expression: &str, secs: u64) -> u64 {
+        let cron = Cron::from_str(expression).expect("invalid cron expression");
+        cron.next_after(UNIX_EPOCH + Duration::from_secs(secs))
+            .expect("no next run")
+            .duration_since(UNIX_EPOCH)
+            .expect("invalid time")
+            .as_secs()
+    }
+
+    #[test]
+    fn next_after() {
+        assert_eq!(next("* * * * *", WEDNESDAY), WEDNESDAY + 60);
+        assert_eq!(next("*/15 * * * *", WEDNESDAY + 1), WEDNESDAY + 15 * 60);
+        assert_eq!(next("@hourly", WEDNESDAY), WEDNESDAY + 3600);
+        assert_eq!(
+            next("30 2 * * *", WEDNESDAY),
+            WEDNESDAY + 14 * 3600 + 30 * 60
+        );
+        // Next sunday
+        assert_eq!(
+            next("0 0 * * 7", WEDNESDAY),
+            WEDNESDAY + 3 * 86400 + 12 * 3600
+        );
+        // Next first of the month or monday
+        assert_eq!(next("0 12 1 * 1", WEDNESDAY), WEDNESDAY + 5 * 86400);
+        // Leap day
+        assert_eq!(next("0 0 29 2 *", 0), 68169600);
+    }
+
+    #[test]
+    fn invalid() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(Cron::from_str(expression).is_err(), "{expression}");
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Signal sent to a container to request its termination
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Signal {
+    /// `SIGHUP`
+    SIGHUP,
+    /// `SIGINT`
+    SIGINT,
+    /// `SIGQUIT`
+    SIGQUIT,
+    /// `SIGUSR1`
+    SIGUSR1,
+    /// `SIGUSR2`
+    SIGUSR2,
+    /// `SIGTERM`
+    #[default]
+    SIGTERM,
+    /// `SIGKILL`
+    SIGKILL,
+}
+
+impl From<Signal> for nix::sys::signal::Signal {
+    fn from(signal: Signal) -> Self {
+        match signal {
+            Signal::SIGHUP => nix::sys::signal::SIGHUP,
+            Signal::SIGINT => nix::sys::signal::SIGINT,
+            Signal::SIGQUIT => nix::sys::signal::SIGQUIT,
+            Signal::SIGUSR1 => nix::sys::signal::SIGUSR1,
+            Signal::SIGUSR2 => nix::sys::signal::SIGUSR2,
+            Signal::SIGTERM => nix::sys::signal::SIGTERM,
+            Signal::SIGKILL => nix::sys::signal::SIGKILL,
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use super::mount::{Bind, Mount, MountOption, Resource, Tmpfs};
+use super::{
+    mount::{Bind, Mount, MountOption, Resource, Tmpfs},
+    signal::Signal,
+};
 use crate::{common::version::VersionReq, npk::manifest::*, seccomp::SyscallRule};
 use anyhow::Result;
 use std::{
@@ -425,6 +428,29 @@ mounts:
     dir: /bin/foo
     options: rw,nosuid,nodev,noexec
 autostart: relaxed
+depends_on:
+  redis-server:
+    version: '>=0.0.1'
+    ready: true
+  logger:
+    version: '*'
+health:
+  probe: !exec
+    path: /bin/check
+    args:
+      - --verbose
+  interval: 5s
+  timeout: 500ms
+  failure_threshold: 2
+  restart: true
+restart:
+  policy: on_failure
+  max_retries: 5
+  backoff: 500ms
+  max_backoff: 30s
+  reset_after: 1m
+stop_signal: SIGINT
+stop_timeout: 5s
 seccomp:
   allow:
     fork: any
@@ -518,3 +544,426 @@ env:
     assert!(Manifest::from_str(manifest).is_err());
     Ok(())
 }
+
+/// Resource containers cannot be restarted
+#[test]
+fn resource_with_restart() {
+    let manifest = "name: hello\nversion: 0.0.0\nuid: 1000\ngid: 1001
+restart:
+  policy: always";
+    assert!(Manifest::from_str(manifest).is_err());
+}
+
+/// Containers cannot depend on themselves
+#[test]
+fn depends_on_self() {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+depends_on:
+  hello:
+    version: '>=0.0.0'";
+    assert!(Manifest::from_str(manifest).is_err());
+}
+
+/// Socket health probes must reference a stream or seqpacket socket from the manifest
+#[test]
+fn health_socket() {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+sockets:
+  foo:
+    type: stream
+    mode: 0o660
+health:
+  probe: !socket
+    name: foo";
+    assert!(Manifest::from_str(manifest).is_ok());
+
+    // Unknown socket
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+health:
+  probe: !socket
+    name: foo";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    // A datagram socket has no connections
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+sockets:
+  foo:
+    type: datagram
+    mode: 0o660
+health:
+  probe: !socket
+    name: foo";
+    assert!(Manifest::from_str(manifest).is_err());
+}
+
+/// Heartbeat health probes require a console
+#[test]
+fn health_heartbeat_without_console() {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+health:
+  probe: heartbeat";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+console:
+  permissions: [heartbeat]
+health:
+  probe: heartbeat";
+    assert!(Manifest::from_str(manifest).is_ok());
+}
+
+/// A terminal requires a dev mount for its devpts instance
+#[test]
+fn io_tty_without_dev() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+io:
+  stdout: pipe
+  stderr: pipe
+  tty: true";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+mounts:
+  /dev:
+    type: dev
+io:
+  stdout: pipe
+  stderr: pipe
+  tty: true";
+    let manifest = Manifest::from_str(manifest)?;
+    assert!(manifest.io.map(|io| io.tty).unwrap_or_default());
+    Ok(())
+}
+
+/// Stop settings are parsed as signal name and human readable duration
+#[test]
+fn stop_signal_and_timeout() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+stop_signal: SIGUSR1
+stop_timeout: 1m 30s";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(manifest.stop_signal, Some(Signal::SIGUSR1));
+    assert_eq!(manifest.stop_timeout, Some(Duration::from_secs(90)));
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+stop_signal: SIGSEGV";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Critical policies apply to critical containers and name another failure handler
+#[test]
+fn critical() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+autostart: critical
+critical:
+  action: failure_handler
+  container: recovery
+  version: '>=1.0.0'";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(
+        manifest.critical,
+        Some(critical::Critical::FailureHandler {
+            container: "recovery".try_into()?,
+            version: VersionReq::from_str(">=1.0.0")?,
+        })
+    );
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+autostart: critical
+critical:
+  action: restart
+  max_retries: 3";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(
+        manifest.critical,
+        Some(critical::Critical::Restart { max_retries: 3 })
+    );
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+autostart: relaxed
+critical:
+  action: reboot";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+autostart: critical
+critical:
+  action: failure_handler
+  container: hello
+  version: '>=0.0.0'";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Manifests declare hook containers but no host commands
+#[test]
+fn hooks() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+hooks:
+  pre_start:
+    - type: container
+      container: check
+      version: '>=1.0.0'
+      args: [--quick]
+      timeout: 5s";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(
+        manifest.hooks.pre_start,
+        vec![hooks::Hook::Container {
+            container: "check".try_into()?,
+            version: VersionReq::from_str(">=1.0.0")?,
+            args: vec![nn("--quick")],
+            timeout: Duration::from_secs(5),
+        }]
+    );
+    assert!(manifest.hooks.post_stop.is_empty());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+hooks:
+  post_stop:
+    - type: command
+      command: [/bin/true]";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+hooks:
+  post_start:
+    - type: container
+      container: hello
+      version: '>=0.0.0'";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Schedules are either a cron expression or an interval
+#[test]
+fn schedule() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+schedule:
+  cron: '*/15 * * * *'
+  jitter: 30s";
+    let schedule = Manifest::from_str(manifest)?
+        .schedule
+        .expect("missing schedule");
+    assert_eq!(
+        schedule.cron.map(|c| c.to_string()),
+        Some("*/15 * * * *".into())
+    );
+    assert_eq!(schedule.jitter, Duration::from_secs(30));
+    assert!(schedule.skip_if_running);
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+schedule:
+  interval: 1h
+  skip_if_running: false";
+    let schedule = Manifest::from_str(manifest)?
+        .schedule
+        .expect("missing schedule");
+    assert_eq!(schedule.interval, Some(Duration::from_secs(3600)));
+    assert!(!schedule.skip_if_running);
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+schedule:
+  cron: '@daily'
+  interval: 1h";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+schedule:
+  cron: '61 * * * *'";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Ready notify requires the console permission notify
+#[test]
+fn ready() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+console:
+  permissions: [notify]
+ready: notify
+ready_timeout: 5s";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(manifest.ready, Some(ready::Ready::Notify));
+    assert_eq!(manifest.ready_timeout, Some(Duration::from_secs(5)));
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+ready: notify";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+console:
+  permissions: [ident]
+ready: notify";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+ready: started
+ready_timeout: 5s";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Max runtime is a duration greater than zero
+#[test]
+fn max_runtime() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+max_runtime: 1h 30m";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(manifest.max_runtime, Some(Duration::from_secs(90 * 60)));
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+max_runtime: 0s";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\nmax_runtime: 10s";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Core dumps are collected in a dedicated mount
+#[test]
+fn core_dumps_mount() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+mounts:
+  /core:
+    type: core_dumps";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(manifest.mounts.get(&nn("/core")), Some(&Mount::CoreDumps));
+    Ok(())
+}
+
+/// Root in a user namespace is mapped to a host range
+#[test]
+fn user_namespace() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 0\ngid: 0
+user_namespace:
+  uid_map:
+    - container: 0
+      host: 100000
+      size: 65536
+  gid_map:
+    - container: 0
+      host: 100000
+      size: 65536";
+    let manifest = Manifest::from_str(manifest)?;
+    let user_namespace = manifest.user_namespace.expect("missing user namespace");
+    assert_eq!(user_namespace.host_uid(0), Some(100000));
+    assert_eq!(user_namespace.host_gid(1000), Some(101000));
+
+    // The uid of the container is not mapped
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 0
+user_namespace:
+  uid_map:
+    - container: 0
+      host: 100000
+      size: 1
+  gid_map:
+    - container: 0
+      host: 100000
+      size: 1";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    // The owner of a socket is not mapped
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 0\ngid: 0
+sockets:
+  foo:
+    type: stream
+    mode: 0o600
+    uid: 1000
+user_namespace:
+  uid_map:
+    - container: 0
+      host: 100000
+      size: 1
+  gid_map:
+    - container: 0
+      host: 100000
+      size: 1";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// The cgroup mount and delegation require a cgroup namespace
+#[test]
+fn cgroup_namespace() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+cgroups:
+  namespace: true
+  delegate: true
+mounts:
+  /sys/fs/cgroup:
+    type: cgroup";
+    let manifest = Manifest::from_str(manifest)?;
+    let cgroups = manifest.cgroups.as_ref().expect("missing cgroups");
+    assert!(cgroups.namespace);
+    assert!(cgroups.delegate);
+    assert_eq!(
+        manifest.mounts.get(&nn("/sys/fs/cgroup")),
+        Some(&Mount::Cgroup)
+    );
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+mounts:
+  /sys/fs/cgroup:
+    type: cgroup";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+cgroups:
+  delegate: true";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Clock offsets of the time namespace
+#[test]
+fn time_namespace() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+time_namespace:
+  boottime: 365d";
+    let manifest = Manifest::from_str(manifest)?;
+    let time_namespace = manifest.time_namespace.expect("missing time namespace");
+    assert_eq!(time_namespace.monotonic, Duration::ZERO);
+    assert_eq!(time_namespace.boottime, Duration::from_secs(365 * 86400));
+
+    let manifest = "name: hello\nversion: 0.0.0\nuid: 1000\ngid: 1001
+time_namespace:
+  monotonic: 1h";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Hostname and domainname with placeholders
+#[test]
+fn hostname() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.1\ninit: /binary\nuid: 1000\ngid: 1001
+hostname: <NAME>-<VERSION>
+domainname: northstar";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(manifest.hostname, Some(nn("<NAME>-<VERSION>")));
+
+    let manifest = "name: hello\nversion: 0.0.1\ninit: /binary\nuid: 1000\ngid: 1001
+hostname: hello world";
+    assert!(Manifest::from_str(manifest).is_err());
+
+    let manifest = "name: hello\nversion: 0.0.1\nuid: 1000\ngid: 1001
+hostname: hello";
+    assert!(Manifest::from_str(manifest).is_err());
+    Ok(())
+}
+
+/// Bridge network
+#[test]
+fn bridge_network() -> Result<()> {
+    let manifest = "name: hello\nversion: 0.0.1\ninit: /binary\nuid: 1000\ngid: 1001
+network: bridge";
+    let manifest = Manifest::from_str(manifest)?;
+    assert_eq!(manifest.network, Some(Network::Bridge));
+    assert_eq!(
+        Manifest::from_str(&manifest.to_string())?.network,
+        Some(Network::Bridge)
+    );
+    Ok(())
+}
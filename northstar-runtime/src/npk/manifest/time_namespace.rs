@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Time namespace of the container. The monotonic and boottime clocks of the container
+/// are advanced by the offsets. The realtime clock is not affected.
+#[derive(Clone, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeNamespace {
+    /// Offset of `CLOCK_MONOTONIC`
+    #[serde(with = "humantime_serde", default)]
+    pub monotonic: Duration,
+    /// Offset of `CLOCK_BOOTTIME`
+    #[serde(with = "humantime_serde", default)]
+    pub boottime: Duration,
+}
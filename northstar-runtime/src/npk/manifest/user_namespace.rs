@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// Maximum number of ranges in a uid or gid map. See user_namespaces(7).
+const MAX_RANGES: usize = 340;
+
+/// User namespace of the container. The uids and gids of the container are mapped
+/// to ranges of host ids. Ids without a mapping are not available in the container.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UserNamespace {
+    /// Uid ranges
+    #[validate(custom = "validate")]
+    pub uid_map: Vec<IdMap>,
+    /// Gid ranges
+    #[validate(custom = "validate")]
+    pub gid_map: Vec<IdMap>,
+}
+
+impl UserNamespace {
+    /// Host uid of the container uid `uid`
+    pub fn host_uid(&self, uid: u32) -> Option<u32> {
+        map(&self.uid_map, uid)
+    }
+
+    /// Host gid of the container gid `gid`
+    pub fn host_gid(&self, gid: u32) -> Option<u32> {
+        map(&self.gid_map, gid)
+    }
+
+    /// Returns true if a host uid or gid is mapped by both `self` and `other`
+    pub fn overlaps(&self, other: &UserNamespace) -> bool {
+        let overlap = |a: &[IdMap], b: &[IdMap]| {
+            a.iter()
+                .any(|a| b.iter().any(|b| intersects(a.host, a.size, b.host, b.size)))
+        };
+        overlap(&self.uid_map, &other.uid_map) || overlap(&self.gid_map, &other.gid_map)
+    }
+}
+
+/// Range of `size` ids starting at `container` in the container and `host` on the host
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdMap {
+    /// First id in the container
+    pub container: u32,
+    /// First id on the host
+    pub host: u32,
+    /// Number of ids
+    pub size: u32,
+}
+
+fn map(ranges: &[IdMap], id: u32) -> Option<u32> {
+    ranges
+        .iter()
+        .find(|range| id >= range.container && id - range.container < range.size)
+        .map(|range| range.host + (id - range.container))
+}
+
+/// Returns true if the ranges of `size_a` ids at `a` and `size_b` ids at `b` intersect
+fn intersects(a: u32, size_a: u32, b: u32, size_b: u32) -> bool {
+    let (a, b) = (a as u64, b as u64);
+    a < b + size_b as u64 && b < a + size_a as u64
+}
+
+/// Validate that the ranges are not empty, do not contain the host root and do not overlap
+/// in the container or on the host
+fn validate(ranges: &[IdMap]) -> Result<(), ValidationError> {
+    if ranges.is_empty() {
+        return Err(ValidationError::new("user namespace map must not be empty"));
+    }
+    if ranges.len() > MAX_RANGES {
+        return Err(ValidationError::new(
+            "user namespace map exceeds the maximum number of ranges",
+        ));
+    }
+    for (n, range) in ranges.iter().enumerate() {
+        if range.size == 0 {
+            return Err(ValidationError::new(
+                "user namespace map range must not be empty",
+            ));
+        }
+        if range.host == 0 {
+            return Err(ValidationError::new(
+                "user namespace map range must not contain host id 0",
+            ));
+        }
+        if range.container.checked_add(range.size).is_none()
+            || range.host.checked_add(range.size).is_none()
+        {
+            return Err(ValidationError::new(
+                "user namespace map range exceeds the id space",
+            ));
+        }
+        for other in &ranges[n + 1..] {
+            if intersects(range.container, range.size, other.container, other.size)
+                || intersects(range.host, range.size, other.host, other.size)
+            {
+                return Err(ValidationError::new(
+                    "user namespace map ranges must not overlap",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range(container: u32, host: u32, size: u32) -> IdMap {
+        IdMap {
+            container,
+            host,
+            size,
+        }
+    }
+
+    #[test]
+    fn map_ids() {
+        let ranges = [range(0, 100000, 1000), range(1000, 5000, 1)];
+        assert_eq!(map(&ranges, 0), Some(100000));
+        assert_eq!(map(&ranges, 999), Some(100999));
+        assert_eq!(map(&ranges, 1000), Some(5000));
+        assert_eq!(map(&ranges, 1001), None);
+    }
+
+    #[test]
+    fn validate_ranges() {
+        assert!(validate(&[range(0, 100000, 65536)]).is_ok());
+        assert!(validate(&[]).is_err());
+        assert!(validate(&[range(0, 100000, 0)]).is_err());
+        assert!(validate(&[range(u32::MAX, 1, 2)]).is_err());
+        // Host root
+        assert!(validate(&[range(0, 0, 1)]).is_err());
+        assert!(validate(&[range(1000, 0, 10)]).is_err());
+        // Overlap in the container
+        assert!(validate(&[range(0, 100000, 10), range(9, 200000, 10)]).is_err());
+        // Overlap on the host
+        assert!(validate(&[range(0, 100000, 10), range(10, 100009, 10)]).is_err());
+        assert!(validate(&[range(0, 100000, 10), range(10, 100010, 10)]).is_ok());
+    }
+
+    #[test]
+    fn overlaps() {
+        let user_namespace = |uid: IdMap, gid: IdMap| UserNamespace {
+            uid_map: vec![uid],
+            gid_map: vec![gid],
+        };
+        let a = user_namespace(range(0, 100000, 1000), range(0, 100000, 1000));
+        // Same host ranges with different container ids
+        let b = user_namespace(range(1000, 100999, 1), range(0, 200000, 1000));
+        assert!(a.overlaps(&b));
+        assert!(b.overlaps(&a));
+        // Gid ranges overlap
+        let b = user_namespace(range(0, 200000, 1000), range(0, 99000, 1001));
+        assert!(a.overlaps(&b));
+        // Adjacent ranges
+        let b = user_namespace(range(0, 101000, 1000), range(0, 99000, 1000));
+        assert!(!a.overlaps(&b));
+        // A uid range does not overlap a gid range
+        let b = user_namespace(range(0, 200000, 1000), range(0, 300000, 1000));
+        let c = user_namespace(range(0, 300000, 1000), range(0, 200000, 1000));
+        assert!(!b.overlaps(&c));
+    }
+}
@@ -0,0 +1,55 @@
+use validator::ValidationError;
+
+use crate::common::{name::Name, version::Version};
+
+/// Maximum length of a hostname or domainname. See sethostname(2).
+const MAX_LENGTH: usize = 64;
+
+/// Placeholder replaced with the container name
+const NAME: &str = "<NAME>";
+/// Placeholder replaced with the container version
+const VERSION: &str = "<VERSION>";
+
+/// Replace the placeholders `<NAME>` and `<VERSION>` in the hostname or domainname `template`
+pub fn render(template: &str, name: &Name, version: &Version) -> String {
+    template
+        .replace(NAME, name.as_ref())
+        .replace(VERSION, &version.to_string())
+}
+
+/// Validate a rendered hostname or domainname. Names consist of letters, digits, hyphens and
+/// dots.
+pub(crate) fn validate(name: &str) -> Result<(), ValidationError> {
+    if name.is_empty() || name.len() > MAX_LENGTH {
+        return Err(ValidationError::new(
+            "hostname and domainname must be between 1 and 64 characters",
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+    {
+        return Err(ValidationError::new(
+            "hostname and domainname must only contain letters, digits, hyphens and dots",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn render_and_validate() {
+        let name = Name::try_from("hello").unwrap();
+        let version = Version::parse("1.2.3").unwrap();
+        let hostname = render("<NAME>-<VERSION>", &name, &version);
+        assert_eq!(hostname, "hello-1.2.3");
+        assert!(validate(&hostname).is_ok());
+        assert!(validate("").is_err());
+        assert!(validate("hello_world").is_err());
+        assert!(validate(&"a".repeat(65)).is_err());
+    }
+}
@@ -709,7 +709,7 @@ fn pseudo_dir<W: io::Write>(w: &mut W, dir: &Path, mode: u16, uid: u16, gid: u16
 }
 
 /// Returns a temporary file with all the pseudo file definitions
-fn pseudo_files(manifest: &Manifest) -> Result<Vec<u8>> {
+fn pseudo_files(manifest: &Manifest, root: &Path) -> Result<Vec<u8>> {
     let uid = manifest.uid;
     let gid = manifest.gid;
 
@@ -722,8 +722,12 @@ fn pseudo_files(manifest: &Manifest) -> Result<Vec<u8>> {
                 let mode = if flags.is_rw() { 755 } else { 555 };
                 pseudo_dir(&mut out, target.as_ref(), mode, uid, gid)?;
             }
-            Mount::Persist => pseudo_dir(&mut out, target.as_ref(), 755, uid, gid)?,
-            Mount::Proc | Mount::Sysfs => pseudo_dir(&mut out, target.as_ref(), 444, uid, gid)?,
+            Mount::Persist | Mount::CoreDumps => {
+                pseudo_dir(&mut out, target.as_ref(), 755, uid, gid)?
+            }
+            Mount::Cgroup | Mount::Proc | Mount::Sysfs => {
+                pseudo_dir(&mut out, target.as_ref(), 444, uid, gid)?
+            }
             Mount::Resource { .. } => pseudo_dir(&mut out, target.as_ref(), 555, uid, gid)?,
             Mount::Sockets => pseudo_dir(&mut out, target.as_ref(), 755, uid, gid)?,
             Mount::Tmpfs { .. } => pseudo_dir(&mut out, target.as_ref(), 755, uid, gid)?,
@@ -733,6 +737,8 @@ fn pseudo_files(manifest: &Manifest) -> Result<Vec<u8>> {
                 //     ├── fd -> /proc/self/fd
                 //     ├── full
                 //     ├── null
+                //     ├── ptmx -> pts/ptmx
+                //     ├── pts
                 //     ├── random
                 //     ├── stderr -> /proc/self/fd/2
                 //     ├── stdin -> /proc/self/fd/0
@@ -782,6 +788,27 @@ fn pseudo_files(manifest: &Manifest) -> Result<Vec<u8>> {
                         writeln!(out, "{target} x {XATTR_SECURITY_SELINUX}={security}",)?;
                     }
                 }
+
+                // Mountpoint of the devpts instance of containers with a terminal
+                if manifest.io.as_ref().map(|io| io.tty).unwrap_or(false) {
+                    let target: &Path = target.as_ref();
+                    let pts = target.join("pts").display().to_string();
+                    writeln!(out, "{pts} d 755 {uid} {gid}")?;
+                    let ptmx = target.join("ptmx").display().to_string();
+                    writeln!(out, "{ptmx} s 777 {uid} {gid} pts/ptmx")?;
+                }
+            }
+        }
+    }
+
+    // Mountpoints of the generated /etc/hostname and /etc/hosts if not present in the root
+    if manifest.hostname.is_some() {
+        if !root.join("etc").exists() {
+            pseudo_dir(&mut out, Path::new("/etc"), 755, uid, gid)?;
+        }
+        for file in ["hostname", "hosts"] {
+            if !root.join("etc").join(file).exists() {
+                writeln!(out, "/etc/{file} f 444 {uid} {gid} true")?;
             }
         }
     }
@@ -846,7 +873,7 @@ fn mksquashfs(
     }
 
     // Format pseudo files defintion.
-    let pseudo_files = pseudo_files(manifest)?;
+    let pseudo_files = pseudo_files(manifest, root)?;
 
     // Run mksquashfs to create image
     let mut cmd = Command::new(mksquashfs);
@@ -909,9 +936,12 @@ fn mksquashfs(
 
 #[cfg(test)]
 mod test {
-    use std::path::Path;
+    use std::{path::Path, str::FromStr};
 
-    use crate::npk::npk::pseudo_dir;
+    use crate::npk::{
+        manifest::Manifest,
+        npk::{pseudo_dir, pseudo_files},
+    };
 
     /// Test that the pseudo_dir function formats the output correctly.
     #[test]
@@ -934,4 +964,22 @@ mod test {
             "/dev d 755 20 30\n"
         )
     }
+
+    /// Tests that the mountpoints of /etc/hostname and /etc/hosts are created if missing.
+    #[test]
+    fn pseudo_files_hostname() {
+        let manifest = Manifest::from_str(
+            "name: hello\nversion: 0.0.1\ninit: /hello\nuid: 1000\ngid: 1000\nhostname: hello\n",
+        )
+        .expect("failed to parse manifest");
+        let root = tempfile::tempdir().expect("failed to create tempdir");
+        std::fs::create_dir(root.path().join("etc")).expect("failed to create etc");
+        std::fs::write(root.path().join("etc/hosts"), "").expect("failed to write hosts");
+
+        let out = pseudo_files(&manifest, root.path()).expect("failed to format");
+        assert_eq!(
+            String::from_utf8(out).expect("invalid string"),
+            "/etc/hostname f 444 1000 1000 true\n"
+        );
+    }
 }
@@ -0,0 +1,240 @@
+use super::{config, error::Error};
+use crate::{
+    api::model::{Budget, BudgetUsage, Budgets},
+    common::container::Container,
+    npk::manifest::Manifest,
+};
+
+/// Default cpu period of the cgroup cpu controller in microseconds
+const DEFAULT_CPU_PERIOD: u64 = 100_000;
+
+/// Resources a container takes from the budgets when it is started. `None` if the
+/// resource is not limited in the manifest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct Demand {
+    /// Memory hard limit in bytes
+    pub memory: Option<u64>,
+    /// Cpu quota in thousandths of a cpu
+    pub cpu: Option<u64>,
+}
+
+impl Demand {
+    /// Demand of a container with `manifest`
+    pub fn of(manifest: &Manifest) -> Demand {
+        let cgroups = manifest.cgroups.as_ref();
+        let memory = cgroups
+            .and_then(|cgroups| cgroups.memory.as_ref())
+            .and_then(|memory| memory.memory_hard_limit)
+            .and_then(|limit| u64::try_from(limit).ok());
+        let cpu = cgroups
+            .and_then(|cgroups| cgroups.cpu.as_ref())
+            .and_then(|cpu| {
+                let quota = u64::try_from(cpu.quota?).ok()?;
+                let period = cpu.period.filter(|p| *p > 0).unwrap_or(DEFAULT_CPU_PERIOD);
+                Some(quota.saturating_mul(1000) / period)
+            });
+        Demand { memory, cpu }
+    }
+}
+
+/// Empty usage with the limits of `budgets`
+pub(super) fn usage(budgets: &config::Budgets) -> Budgets {
+    Budgets {
+        memory: BudgetUsage {
+            used: 0,
+            limit: budgets.memory,
+        },
+        cpu: BudgetUsage {
+            used: 0,
+            limit: budgets.cpus.map(|cpus| (cpus * 1000.0).round() as u64),
+        },
+        containers: BudgetUsage {
+            used: 0,
+            limit: budgets.containers,
+        },
+        repositories: Default::default(),
+    }
+}
+
+/// Check that starting `container` from `repository` with `demand` stays within the limits
+/// of `usage`. A container without a limit for a resource with a budget is rejected.
+pub(super) fn admit(
+    container: &Container,
+    repository: &str,
+    demand: &Demand,
+    usage: &Budgets,
+) -> Result<(), Error> {
+    let repository_usage = usage
+        .repositories
+        .get(repository)
+        .copied()
+        .unwrap_or_default();
+    let unlimited = [
+        (
+            Budget::Memory,
+            demand.memory,
+            usage.memory,
+            "memory hard limit",
+        ),
+        (Budget::Cpu, demand.cpu, usage.cpu, "cpu quota"),
+    ];
+    for (budget, requested, usage, limit) in unlimited {
+        if requested.is_none() && usage.limit.is_some() {
+            return Err(Error::StartContainerFailed(
+                container.clone(),
+                format!("the {budget} budget requires a {limit} in the manifest"),
+            ));
+        }
+    }
+
+    let checks = [
+        (
+            Budget::Memory,
+            demand.memory.unwrap_or_default(),
+            usage.memory,
+        ),
+        (Budget::Cpu, demand.cpu.unwrap_or_default(), usage.cpu),
+        (Budget::Containers, 1, usage.containers),
+        (
+            Budget::RepositoryContainers {
+                repository: repository.to_string(),
+            },
+            1,
+            repository_usage,
+        ),
+    ];
+    for (budget, requested, BudgetUsage { used, limit }) in checks {
+        if let Some(limit) = limit {
+            if used.saturating_add(requested) > limit {
+                return Err(Error::BudgetExceeded(
+                    container.clone(),
+                    budget,
+                    requested,
+                    used,
+                    limit,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn demand() {
+        let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+cgroups:
+  memory:
+    memory_hard_limit: 1000000
+  cpu:
+    quota: 50000";
+        let manifest = Manifest::from_str(manifest).expect("invalid manifest");
+        assert_eq!(
+            Demand::of(&manifest),
+            Demand {
+                memory: Some(1000000),
+                cpu: Some(500)
+            }
+        );
+
+        let manifest = "name: hello\nversion: 0.0.0\ninit: /binary\nuid: 1000\ngid: 1001
+cgroups:
+  memory:
+    memory_hard_limit: -1
+  cpu:
+    quota: -1";
+        let manifest = Manifest::from_str(manifest).expect("invalid manifest");
+        assert_eq!(Demand::of(&manifest), Demand::default());
+    }
+
+    #[test]
+    fn admit() {
+        let container = Container::try_from("hello:0.0.1").expect("invalid container");
+        let budgets = config::Budgets {
+            memory: Some(1000),
+            cpus: Some(1.0),
+            containers: Some(2),
+            containers_per_repository: None,
+        };
+        let mut usage = usage(&budgets);
+        usage.repositories.insert(
+            "default".into(),
+            BudgetUsage {
+                used: 1,
+                limit: Some(1),
+            },
+        );
+        let demand = Demand {
+            memory: Some(1000),
+            cpu: Some(1000),
+        };
+        assert!(super::admit(&container, "mem", &demand, &usage).is_ok());
+
+        let demand = Demand {
+            memory: Some(1001),
+            cpu: Some(0),
+        };
+        assert!(matches!(
+            super::admit(&container, "mem", &demand, &usage),
+            Err(Error::BudgetExceeded(_, Budget::Memory, 1001, 0, 1000))
+        ));
+
+        let demand = Demand {
+            memory: Some(0),
+            cpu: Some(0),
+        };
+        assert!(matches!(
+            super::admit(&container, "default", &demand, &usage),
+            Err(Error::BudgetExceeded(
+                _,
+                Budget::RepositoryContainers { .. },
+                1,
+                1,
+                1
+            ))
+        ));
+
+        usage.containers.used = 2;
+        assert!(matches!(
+            super::admit(&container, "mem", &demand, &usage),
+            Err(Error::BudgetExceeded(_, Budget::Containers, 1, 2, 2))
+        ));
+    }
+
+    #[test]
+    fn admit_unlimited() {
+        let container = Container::try_from("hello:0.0.1").expect("invalid container");
+        let budgets = config::Budgets {
+            memory: Some(1000),
+            cpus: None,
+            containers: None,
+            containers_per_repository: None,
+        };
+        let usage = usage(&budgets);
+
+        // No memory limit with a memory budget
+        let demand = Demand {
+            memory: None,
+            cpu: None,
+        };
+        assert!(matches!(
+            super::admit(&container, "mem", &demand, &usage),
+            Err(Error::StartContainerFailed(..))
+        ));
+
+        // The cpu is not budgeted
+        let demand = Demand {
+            memory: Some(1000),
+            cpu: None,
+        };
+        assert!(super::admit(&container, "mem", &demand, &usage).is_ok());
+
+        // Nothing is budgeted
+        let usage = super::usage(&config::Budgets::default());
+        assert!(super::admit(&container, "mem", &Demand::default(), &usage).is_ok());
+    }
+}
@@ -10,13 +10,21 @@ use crate::{
 };
 use anyhow::{Context, Result};
 use cgroups_rs::{
-    memory::MemController, BlkIoDeviceResource, BlkIoDeviceThrottleResource, BlkIoResources,
-    Controller, CpuResources, Hierarchy, MemoryResources,
+    freezer::FreezerController, memory::MemController, BlkIoDeviceResource,
+    BlkIoDeviceThrottleResource, BlkIoResources, Controller, CpuResources, Hierarchy,
+    MemoryResources,
 };
 use futures::stream::StreamExt;
 use inotify::{Inotify, WatchMask};
+use itertools::Itertools;
 use log::{debug, info, warn};
-use std::{collections::HashMap, fmt::Debug, os::unix::io::AsRawFd, path::Path};
+use nix::unistd::{self, Gid, Uid};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+};
 use tokio::{
     fs,
     io::{AsyncReadExt, AsyncWriteExt},
@@ -81,7 +89,7 @@ impl Hierarchy for RuntimeHierarchy {
                 cgroups_rs::Subsystem::CpuAcct(_) => true,
                 cgroups_rs::Subsystem::Cpu(_) => true,
                 cgroups_rs::Subsystem::Devices(_) => false,
-                cgroups_rs::Subsystem::Freezer(_) => false,
+                cgroups_rs::Subsystem::Freezer(_) => true,
                 cgroups_rs::Subsystem::NetCls(_) => false,
                 cgroups_rs::Subsystem::BlkIo(_) => true,
                 cgroups_rs::Subsystem::PerfEvent(_) => false,
@@ -110,11 +118,17 @@ impl Hierarchy for RuntimeHierarchy {
     }
 }
 
+/// Files of a cgroup that are handed over to the owner of a delegated cgroup.
+/// See "Delegation" in the cgroup v2 documentation of the kernel.
+const DELEGATED_FILES: &[&str] = &["cgroup.procs", "cgroup.threads", "cgroup.subtree_control"];
+
 #[derive(Debug)]
 pub struct CGroups {
     container: Container,
     cgroup: cgroups_rs::Cgroup,
     oom_monitor: Option<MemoryMonitor>,
+    /// Directory of a delegated cgroup
+    delegated: Option<PathBuf>,
 }
 
 impl CGroups {
@@ -123,6 +137,7 @@ impl CGroups {
         tx: EventTx,
         container: &Container,
         config: &manifest::cgroups::CGroups,
+        owner: (Uid, Gid),
         pid: Pid,
     ) -> Result<CGroups> {
         debug!("Creating cgroups for {}", container);
@@ -138,7 +153,7 @@ impl CGroups {
             path.display()
         );
 
-        let cgroup = cgroups_rs::Cgroup::new(hierarchy(), path)?;
+        let cgroup = cgroups_rs::Cgroup::new(hierarchy(), &path)?;
 
         let resources = cgroups_rs::Resources {
             memory: config.memory.clone().map(Into::into).unwrap_or_default(),
@@ -173,6 +188,24 @@ impl CGroups {
             None
         };
 
+        // Hand the cgroup over to the container. The limits of the cgroup apply to all
+        // sub groups created by the container.
+        let delegated = match config.delegate {
+            true if !cgroup.v2() => {
+                warn!(
+                    "Cannot delegate the cgroup of {} without cgroups v2",
+                    container
+                );
+                None
+            }
+            true => {
+                let dir = hierarchy().root().join(&path);
+                delegate(&dir, owner).context("failed to delegate cgroup")?;
+                Some(dir)
+            }
+            false => None,
+        };
+
         // If adding the task fails it's a fault of the runtime or it's integration
         // and not of the container
         cgroup
@@ -183,6 +216,7 @@ impl CGroups {
             container: container.clone(),
             cgroup,
             oom_monitor,
+            delegated,
         })
     }
 
@@ -194,9 +228,42 @@ impl CGroups {
 
         info!("Destroying cgroup of {}", self.container);
         assert!(self.cgroup.tasks().is_empty());
+        if let Some(dir) = &self.delegated {
+            debug!("Removing sub groups of {}", self.container);
+            if let Err(e) = remove_sub_groups(dir) {
+                warn!("Failed to remove sub groups of {}: {}", self.container, e);
+            }
+        }
         self.cgroup.delete().expect("failed to remove cgroups");
     }
 
+    /// Freeze all processes of the cgroup
+    pub fn freeze(&self) -> Result<()> {
+        self.freezer()?.freeze().context("failed to freeze")
+    }
+
+    /// Thaw all processes of the cgroup
+    pub fn thaw(&self) -> Result<()> {
+        self.freezer()?.thaw().context("failed to thaw")
+    }
+
+    fn freezer(&self) -> Result<&FreezerController> {
+        self.cgroup
+            .controller_of::<FreezerController>()
+            .context("freezer controller is not available")
+    }
+
+    /// Paths of the `cgroup.procs` files of all controllers. A process that writes its
+    /// pid to these files joins the cgroups of the container.
+    pub fn procs(&self) -> Vec<PathBuf> {
+        self.cgroup
+            .subsystems()
+            .iter()
+            .map(|s| s.to_controller().path().join("cgroup.procs"))
+            .unique()
+            .collect()
+    }
+
     /// Gather statistics from controllers
     pub(super) fn stats(&self) -> ContainerStats {
         let mut stats = HashMap::new();
@@ -232,6 +299,31 @@ impl CGroups {
     }
 }
 
+/// Change the owner of the cgroup `dir` and the files required to manage sub groups
+fn delegate(dir: &Path, (uid, gid): (Uid, Gid)) -> Result<()> {
+    debug!("Delegating cgroup {} to {uid}:{gid}", dir.display());
+    for path in Some(dir.to_owned())
+        .into_iter()
+        .chain(DELEGATED_FILES.iter().map(|file| dir.join(file)))
+    {
+        unistd::chown(&path, Some(uid), Some(gid))
+            .with_context(|| format!("failed to chown {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Remove the sub groups of the cgroup `dir` starting with the leaves
+fn remove_sub_groups(dir: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            remove_sub_groups(&entry.path())?;
+            std::fs::remove_dir(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 struct MemoryMonitor {
     token: CancellationToken,
@@ -1,7 +1,10 @@
 use std::{
     collections::HashMap,
+    fmt,
+    net::Ipv4Addr,
     os::unix::prelude::{MetadataExt, PermissionsExt},
     path::{Path, PathBuf},
+    str::FromStr,
     time,
 };
 
@@ -12,7 +15,12 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer};
 use url::Url;
 
 use crate::{
-    common::non_nul_string::NonNulString, npk::manifest::console::Permissions,
+    common::non_nul_string::NonNulString,
+    npk::manifest::{
+        console::Permissions,
+        hooks::{Hook, Hooks},
+        user_namespace::{IdMap, UserNamespace},
+    },
     runtime::repository::RepositoryId,
 };
 
@@ -40,6 +48,39 @@ pub struct Config {
     /// Loop device timeout
     #[serde(with = "humantime_serde", default = "default_loop_device_timeout")]
     pub loop_device_timeout: time::Duration,
+    /// Deadline for stopping all containers on shutdown. Containers that did not
+    /// exit within this time are killed.
+    #[serde(with = "humantime_serde", default = "default_shutdown_timeout")]
+    pub shutdown_timeout: time::Duration,
+    /// Capture of container output
+    #[serde(default)]
+    pub logs: Logs,
+    /// Persist the desired state of the containers in `data_dir` and restore it on startup
+    #[serde(default)]
+    pub journal: bool,
+    /// History of the terminated runs of the containers
+    #[serde(default)]
+    pub history: History,
+    /// Collection of the core dumps of containers with a `core_dumps` mount
+    pub core_dumps: Option<CoreDumps>,
+    /// Bridge of containers with `network: bridge`
+    pub network: Option<Network>,
+    /// Named network namespaces created by the runtime. Containers join them with
+    /// `network: !namespace <name>`.
+    #[serde(default)]
+    pub network_namespaces: HashMap<String, NetworkNamespace>,
+    /// Host ids available to the user namespaces of the containers. Containers with a
+    /// `user_namespace` cannot be started without.
+    pub user_namespaces: Option<UserNamespaces>,
+    /// Command that reboots the system. Executed if a critical container with the
+    /// critical action `reboot` fails.
+    pub reboot_command: Option<Vec<NonNulString>>,
+    /// Hooks run around the lifecycle of all containers
+    #[serde(default)]
+    pub hooks: Hooks,
+    /// Admission budgets checked before a container is started
+    #[serde(default)]
+    pub budgets: Budgets,
     /// Repositories
     #[serde(default)]
     pub repositories: HashMap<RepositoryId, Repository>,
@@ -48,7 +89,7 @@ pub struct Config {
 }
 
 /// Globally accessible console.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct ConsoleGlobal {
     /// Bind globally accesible console to this address.
     #[serde(deserialize_with = "console_url")]
@@ -60,7 +101,7 @@ pub struct ConsoleGlobal {
 }
 
 /// Console Quality of Service
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConsoleOptions {
     /// Token validity duration.
@@ -96,7 +137,7 @@ impl Default for ConsoleOptions {
 }
 
 /// Console Quality of Service
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Console {
     /// Globally accessible console.
@@ -105,8 +146,192 @@ pub struct Console {
     pub options: Option<ConsoleOptions>,
 }
 
+/// Admission budgets of the started containers. Containers without a memory hard limit
+/// or cpu quota in their manifest are rejected if a memory or cpu budget is configured.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budgets {
+    /// Maximum sum of the memory hard limits
+    #[serde(default, deserialize_with = "bytesize")]
+    pub memory: Option<u64>,
+    /// Maximum sum of the cpu quotas in cpus
+    pub cpus: Option<f64>,
+    /// Maximum number of started containers
+    pub containers: Option<u64>,
+    /// Maximum number of started containers per repository
+    pub containers_per_repository: Option<u64>,
+}
+
+/// History of the terminated runs of the containers
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct History {
+    /// Number of runs kept per container. The oldest run is dropped first.
+    #[serde(default = "default_history_runs")]
+    pub runs: usize,
+    /// Persist the history in `data_dir` and restore it on startup
+    #[serde(default)]
+    pub persist: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            runs: default_history_runs(),
+            persist: false,
+        }
+    }
+}
+
+/// Collection of core dumps. The kernel core pattern must be an absolute path into the
+/// `core_dumps` mount of the containers, e.g. `/core/core.%e.%p`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoreDumps {
+    /// Directory of the core dumps. The core dumps of a container are stored in
+    /// `<dir>/<name>`.
+    pub dir: PathBuf,
+    /// Maximum size of a core dump. Applied as `RLIMIT_CORE` of the containers.
+    #[serde(deserialize_with = "bytesize", default = "default_core_dump_size")]
+    pub max_size: u64,
+    /// Number of core dumps kept per container. The oldest core dump is removed first.
+    #[serde(default = "default_core_dumps_retention")]
+    pub retention: usize,
+    /// Maximum total size of the core dumps of a container. The oldest core dump is
+    /// removed first.
+    #[serde(deserialize_with = "bytesize", default = "default_core_dumps_quota")]
+    pub quota: u64,
+}
+
+/// Bridge network created by the runtime. Each container with `network: bridge` is
+/// connected to the bridge with a veth pair and gets an address of the subnet.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Network {
+    /// Name of the bridge
+    #[serde(default = "default_bridge")]
+    pub bridge: String,
+    /// Address of the bridge and prefix length of the subnet, e.g. `172.30.0.1/24`.
+    /// The bridge is the gateway of the containers.
+    #[serde(deserialize_with = "from_str")]
+    pub address: Cidr,
+    /// Prefix of the host ends of the veth pairs. Interfaces with this prefix are owned by
+    /// the runtime and must not be used by other runtime instances or tools.
+    #[serde(default = "default_veth_prefix")]
+    pub veth_prefix: String,
+}
+
+/// Named network namespace created by the runtime on startup and removed on shutdown.
+/// The loopback interface of the namespace is up.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkNamespace {
+    /// Connect the namespace to the bridge of the `network` section
+    #[serde(default)]
+    pub bridge: bool,
+}
+
+/// Pools of host ids for the user namespaces of the containers, e.g. the subordinate ids
+/// of the runtime user. Each range of the id maps of a container must lie within a pool.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserNamespaces {
+    /// Host uids
+    pub uids: Vec<IdRange>,
+    /// Host gids
+    pub gids: Vec<IdRange>,
+}
+
+impl UserNamespaces {
+    /// Returns true if the host ids of `user_namespace` lie within the pools
+    pub fn allows(&self, user_namespace: &UserNamespace) -> bool {
+        let within = |pool: &[IdRange], maps: &[IdMap]| {
+            maps.iter().all(|map| {
+                pool.iter().any(|range| {
+                    let (start, end) = (range.start as u64, range.start as u64 + range.size as u64);
+                    start <= map.host as u64 && map.host as u64 + map.size as u64 <= end
+                })
+            })
+        };
+        within(&self.uids, &user_namespace.uid_map) && within(&self.gids, &user_namespace.gid_map)
+    }
+}
+
+/// Range of `size` host ids starting at `start`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdRange {
+    /// First id
+    pub start: u32,
+    /// Number of ids
+    pub size: u32,
+}
+
+/// IPv4 address with the prefix length of its subnet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cidr {
+    /// Address
+    pub address: Ipv4Addr,
+    /// Prefix length
+    pub prefix: u8,
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = s
+            .split_once('/')
+            .ok_or_else(|| format!("missing prefix length in {s}"))?;
+        let address = address
+            .parse()
+            .map_err(|e| format!("invalid address {address}: {e}"))?;
+        let prefix = prefix
+            .parse()
+            .ok()
+            .filter(|prefix| *prefix <= 32)
+            .ok_or_else(|| format!("invalid prefix length {prefix}"))?;
+        Ok(Cidr { address, prefix })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// Capture of the output of containers with `pipe` io
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Logs {
+    /// Number of lines kept in memory per container
+    #[serde(default = "default_log_buffer_lines")]
+    pub buffer_lines: usize,
+    /// Optional directory for log files. The log of a container is written to
+    /// `<dir>/<name>:<version>.log`.
+    pub dir: Option<PathBuf>,
+    /// Size of a log file before it is rotated
+    #[serde(deserialize_with = "bytesize", default = "default_log_file_size")]
+    pub file_size: u64,
+    /// Number of rotated log files kept per container
+    #[serde(default = "default_log_files")]
+    pub files: usize,
+}
+
+impl Default for Logs {
+    fn default() -> Self {
+        Self {
+            buffer_lines: default_log_buffer_lines(),
+            dir: None,
+            file_size: default_log_file_size(),
+            files: default_log_files(),
+        }
+    }
+}
+
 /// Repository type
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub enum RepositoryType {
     /// Directory based
     #[serde(rename = "fs")]
@@ -120,7 +345,7 @@ pub enum RepositoryType {
 }
 
 /// Repository configuration
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Repository {
     /// Repository type: fs or mem.
@@ -138,7 +363,7 @@ pub struct Repository {
 }
 
 /// Container debug settings
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Debug {
     /// Commands to run before the container is started.
@@ -149,11 +374,113 @@ pub struct Debug {
 }
 
 impl Config {
+    /// Read the configuration from the TOML file `path`
+    pub fn load(path: &Path) -> anyhow::Result<Config> {
+        let config = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read configuration file {}", path.display()))?;
+        toml::from_str(&config)
+            .with_context(|| format!("failed to parse configuration file {}", path.display()))
+    }
+
+    /// Check that `config` only differs from `self` in settings that can be changed
+    /// while the runtime is running
+    pub(crate) fn check_reload(&self, config: &Config) -> anyhow::Result<()> {
+        if self.run_dir != config.run_dir {
+            bail!("run_dir cannot be changed without a restart");
+        }
+        if self.data_dir != config.data_dir {
+            bail!("data_dir cannot be changed without a restart");
+        }
+        if self.socket_dir != config.socket_dir {
+            bail!("socket_dir cannot be changed without a restart");
+        }
+        if self.cgroup != config.cgroup {
+            bail!("cgroup cannot be changed without a restart");
+        }
+        if self.event_buffer_size != config.event_buffer_size {
+            bail!("event_buffer_size cannot be changed without a restart");
+        }
+        if self.notification_buffer_size != config.notification_buffer_size {
+            bail!("notification_buffer_size cannot be changed without a restart");
+        }
+        if self.loop_device_timeout != config.loop_device_timeout {
+            bail!("loop_device_timeout cannot be changed without a restart");
+        }
+        if self.journal != config.journal {
+            bail!("journal cannot be changed without a restart");
+        }
+        if self.network != config.network {
+            bail!("network cannot be changed without a restart");
+        }
+        if self.network_namespaces != config.network_namespaces {
+            bail!("network_namespaces cannot be changed without a restart");
+        }
+        Ok(())
+    }
+
     /// Validate the configuration
     pub(crate) fn check(&self) -> anyhow::Result<()> {
         check_rw_directory(&self.run_dir).context("checking run_dir")?;
         check_rw_directory(&self.data_dir).context("checking data_dir")?;
         check_rw_directory(&self.socket_dir).context("checking socket_dir")?;
+        if let Some(dir) = &self.logs.dir {
+            check_rw_directory(dir).context("checking logs.dir")?;
+        }
+        if let Some(core_dumps) = &self.core_dumps {
+            check_rw_directory(&core_dumps.dir).context("checking core_dumps.dir")?;
+        }
+        if self
+            .reboot_command
+            .as_ref()
+            .map(Vec::is_empty)
+            .unwrap_or(false)
+        {
+            bail!("reboot_command must not be empty");
+        }
+        for hook in self.hooks.iter() {
+            if matches!(hook, Hook::Command { command, .. } if command.is_empty()) {
+                bail!("hook command must not be empty");
+            }
+        }
+        if let Some(network) = &self.network {
+            // The names of the veth pairs are derived from the host part of the addresses
+            if !(16..=30).contains(&network.address.prefix) {
+                bail!("network.address prefix length must be between 16 and 30");
+            }
+            if network.bridge.is_empty() || network.bridge.len() > 15 {
+                bail!("network.bridge must be between 1 and 15 characters");
+            }
+            // Up to five digits of the index are appended to the prefix
+            if network.veth_prefix.is_empty() || network.veth_prefix.len() > 10 {
+                bail!("network.veth_prefix must be between 1 and 10 characters");
+            }
+        }
+        for (name, namespace) in &self.network_namespaces {
+            if name.is_empty()
+                || name.len() > 255
+                || name.contains('/')
+                || name == "."
+                || name == ".."
+            {
+                bail!("invalid network namespace name {name}");
+            }
+            if namespace.bridge && self.network.is_none() {
+                bail!("network namespace {name} requires the network section");
+            }
+        }
+        if let Some(cpus) = self.budgets.cpus {
+            if !cpus.is_finite() || cpus < 0.0 {
+                bail!("budgets.cpus must be a positive number");
+            }
+        }
+        if let Some(user_namespaces) = &self.user_namespaces {
+            for range in user_namespaces.uids.iter().chain(&user_namespaces.gids) {
+                // Root of the host is never mapped into a container
+                if range.start == 0 || range.start.checked_add(range.size).is_none() {
+                    bail!("user_namespaces range must be within 1 and {}", u32::MAX);
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -203,6 +530,16 @@ where
     }
 }
 
+/// Parse a value from a string
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    String::deserialize(deserializer).and_then(|s| s.parse().map_err(D::Error::custom))
+}
+
 /// Parse human readable byte sizes.
 fn bytesize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
@@ -219,6 +556,11 @@ const fn default_loop_device_timeout() -> time::Duration {
     time::Duration::from_secs(10)
 }
 
+/// Default shutdown timeout.
+const fn default_shutdown_timeout() -> time::Duration {
+    time::Duration::from_secs(30)
+}
+
 /// Default event buffer size.
 const fn default_event_buffer_size() -> usize {
     256
@@ -248,6 +590,51 @@ const fn default_npk_stream_timeout() -> time::Duration {
     time::Duration::from_secs(10)
 }
 
+/// Default number of log lines kept per container.
+const fn default_log_buffer_lines() -> usize {
+    1000
+}
+
+/// Default size of a log file before rotation.
+const fn default_log_file_size() -> u64 {
+    1024 * 1024
+}
+
+/// Default number of rotated log files.
+const fn default_log_files() -> usize {
+    3
+}
+
+/// Default number of runs kept per container.
+const fn default_history_runs() -> usize {
+    10
+}
+
+/// Default maximum size of a core dump.
+const fn default_core_dump_size() -> u64 {
+    64 * 1024 * 1024
+}
+
+/// Default name of the bridge.
+fn default_bridge() -> String {
+    "northstar0".into()
+}
+
+/// Default prefix of the host ends of the veth pairs
+fn default_veth_prefix() -> String {
+    "nsveth".into()
+}
+
+/// Default number of core dumps kept per container.
+const fn default_core_dumps_retention() -> usize {
+    3
+}
+
+/// Default maximum total size of the core dumps of a container.
+const fn default_core_dumps_quota() -> u64 {
+    default_core_dumps_retention() as u64 * default_core_dump_size()
+}
+
 /// Default maximum length per request in bytes.
 const fn default_max_request_size() -> u64 {
     1024 * 1024
@@ -305,3 +692,210 @@ capacity_size = "100MB"
     assert_eq!(memory.capacity_num, Some(10));
     assert_eq!(memory.capacity_size, Some(100000000));
 }
+
+#[test]
+fn logs() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[logs]
+buffer_lines = 100
+dir = "target/northstar/logs"
+file_size = "10KB"
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    assert_eq!(config.logs.buffer_lines, 100);
+    assert_eq!(config.logs.dir, Some("target/northstar/logs".into()));
+    assert_eq!(config.logs.file_size, 10000);
+    assert_eq!(config.logs.files, 3);
+}
+
+#[test]
+fn budgets() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[budgets]
+memory = "512MB"
+cpus = 1.5
+containers = 10
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    assert_eq!(config.budgets.memory, Some(512000000));
+    assert_eq!(config.budgets.cpus, Some(1.5));
+    assert_eq!(config.budgets.containers, Some(10));
+    assert_eq!(config.budgets.containers_per_repository, None);
+}
+
+#[test]
+fn history() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    assert_eq!(config.history, History::default());
+
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[history]
+runs = 3
+persist = true
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    assert_eq!(config.history.runs, 3);
+    assert!(config.history.persist);
+}
+
+#[test]
+fn core_dumps() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[core_dumps]
+dir = "target/northstar/core"
+max_size = "16MB"
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    let core_dumps = config.core_dumps.expect("missing core dumps");
+    assert_eq!(core_dumps.dir, PathBuf::from("target/northstar/core"));
+    assert_eq!(core_dumps.max_size, 16000000);
+    assert_eq!(core_dumps.retention, 3);
+    assert_eq!(core_dumps.quota, 3 * 64 * 1024 * 1024);
+}
+
+#[test]
+fn reload() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+
+    let mut reloaded = config.clone();
+    reloaded.debug = Some(Debug {
+        commands: vec!["true".into()],
+    });
+    assert!(config.check_reload(&reloaded).is_ok());
+
+    let mut reloaded = config.clone();
+    reloaded.run_dir = "target/northstar/run2".into();
+    assert!(config.check_reload(&reloaded).is_err());
+
+    let mut reloaded = config.clone();
+    reloaded.data_dir = "target/northstar/data2".into();
+    assert!(config.check_reload(&reloaded).is_err());
+
+    let mut reloaded = config.clone();
+    reloaded.socket_dir = "target/northstar/sockets2".into();
+    assert!(config.check_reload(&reloaded).is_err());
+
+    let mut reloaded = config.clone();
+    reloaded.cgroup = NonNulString::try_from("northstar2").expect("invalid cgroup");
+    assert!(config.check_reload(&reloaded).is_err());
+
+    let mut reloaded = config.clone();
+    reloaded.event_buffer_size += 1;
+    assert!(config.check_reload(&reloaded).is_err());
+
+    let mut reloaded = config.clone();
+    reloaded.loop_device_timeout += time::Duration::from_secs(1);
+    assert!(config.check_reload(&reloaded).is_err());
+}
+
+#[test]
+fn network() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[network]
+address = "172.30.0.1/24"
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    let network = config.network.expect("missing network");
+    assert_eq!(network.bridge, "northstar0");
+    assert_eq!(network.veth_prefix, "nsveth");
+    assert_eq!(network.address.address, Ipv4Addr::new(172, 30, 0, 1));
+    assert_eq!(network.address.prefix, 24);
+    assert_eq!(network.address.to_string(), "172.30.0.1/24");
+
+    for address in ["172.30.0.1", "172.30.0.1/33", "172.30.0/24"] {
+        assert!(address.parse::<Cidr>().is_err(), "{address}");
+    }
+}
+
+#[test]
+fn user_namespaces() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[user_namespaces]
+uids = [{ start = 100000, size = 65536 }]
+gids = [{ start = 100000, size = 65536 }, { start = 300000, size = 10 }]
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    let user_namespaces = config.user_namespaces.expect("missing user namespaces");
+
+    let user_namespace = |uid_host: u32, gid_host: u32, size: u32| UserNamespace {
+        uid_map: vec![IdMap {
+            container: 0,
+            host: uid_host,
+            size,
+        }],
+        gid_map: vec![IdMap {
+            container: 0,
+            host: gid_host,
+            size,
+        }],
+    };
+    assert!(user_namespaces.allows(&user_namespace(100000, 100000, 65536)));
+    assert!(user_namespaces.allows(&user_namespace(100000, 300000, 10)));
+    assert!(!user_namespaces.allows(&user_namespace(100000, 300000, 11)));
+    assert!(!user_namespaces.allows(&user_namespace(99999, 100000, 10)));
+    assert!(!user_namespaces.allows(&user_namespace(100001, 100000, 65536)));
+}
+
+#[test]
+fn network_namespaces() {
+    let config = r#"
+data_dir = "target/northstar/data"
+run_dir = "target/northstar/run"
+socket_dir = "target/northstar/sockets"
+cgroup = "northstar"
+
+[network_namespaces.backend]
+bridge = true
+
+[network_namespaces.isolated]
+"#;
+    let config = toml::from_str::<Config>(config).expect("failed to parse config");
+    assert!(config.network_namespaces["backend"].bridge);
+    assert!(!config.network_namespaces["isolated"].bridge);
+
+    let mut reloaded = config.clone();
+    reloaded.network_namespaces.remove("isolated");
+    assert!(config.check_reload(&reloaded).is_err());
+}
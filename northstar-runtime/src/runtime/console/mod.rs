@@ -1,9 +1,13 @@
 use crate::{
     api::{self, codec::Framed, VERSION},
-    common::container::Container,
+    common::{container::Container, non_nul_string::NonNulString},
     runtime::{
-        events::{CGroupEvent, ContainerEvent, Event, EventTx},
+        events::{
+            CGroupEvent, ContainerEvent, CriticalAction, Event, EventTx, ExitReason, Health,
+            ScheduledRun, UpdateProgress,
+        },
         exit_status::ExitStatus,
+        io::{Attachment, ExecIo},
         repository::RepositoryId,
         runtime::NotificationTx,
         token::Token,
@@ -16,14 +20,15 @@ use bytes::{Buf, Bytes};
 use futures::{
     future::join_all,
     stream::{self, FuturesUnordered},
-    Stream, StreamExt,
+    SinkExt, Stream, StreamExt,
 };
 use listener::Listener;
 use log::{debug, info, trace, warn};
 use semver::Comparator;
-use std::{cmp::min, fmt, path::Path, unreachable};
+use std::{cmp::min, collections::HashMap, fmt, path::Path, time::Duration, unreachable};
 use tokio::{
-    io::{self, AsyncRead, AsyncReadExt, AsyncWrite},
+    fs,
+    io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     pin, select,
     sync::{broadcast, mpsc, oneshot},
     task, time,
@@ -40,11 +45,48 @@ mod options;
 mod permissions;
 mod throttle;
 
+/// End of transmission character that closes the input of a terminal
+const EOT: u8 = 0x04;
+
+/// Size of the chunks of a core dump download
+const CHUNK_SIZE: usize = 64 * 1024;
+
 // Request from the main loop to the console
 #[derive(Debug)]
 pub(crate) enum Request {
     Request(model::Request),
     Install(RepositoryId, mpsc::Receiver<Bytes>),
+    Update {
+        from: Container,
+        to: Container,
+        repository: RepositoryId,
+        probation: Duration,
+        rx: mpsc::Receiver<Bytes>,
+    },
+    Attach {
+        container: Container,
+        attach: oneshot::Sender<Attachment>,
+    },
+    Exec {
+        container: Container,
+        path: NonNulString,
+        args: Vec<NonNulString>,
+        env: HashMap<NonNulString, NonNulString>,
+        tty: bool,
+        io: oneshot::Sender<ExecIo>,
+        exit: oneshot::Sender<ExitStatus>,
+    },
+    Logs {
+        container: Container,
+        tail: Option<usize>,
+        since: Option<u64>,
+        follow: Option<oneshot::Sender<broadcast::Receiver<model::LogLine>>>,
+    },
+    CoreDump {
+        container: Container,
+        name: String,
+        file: oneshot::Sender<std::fs::File>,
+    },
 }
 
 /// A console is responsible for monitoring and serving incoming client connections
@@ -294,6 +336,10 @@ impl Console {
                                 break;
                             }
                         }
+                        Some(Ok(model::Message::Io { .. })) => {
+                            // Input for a process that exited in the meantime
+                            debug!("{}: Discarding io without running exec", peer);
+                        }
                         Some(Ok(message)) => {
                             warn!("{}: Unexpected message: {:?}. Disconnecting...", peer, message);
                             break;
@@ -331,16 +377,30 @@ async fn process_request<S>(
     request: model::Request,
 ) -> Result<model::Message>
 where
-    S: AsyncRead + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
 {
     let required_permission = match &request {
+        model::Request::Attach { .. } => Permission::Attach,
+        model::Request::Budgets => Permission::Budgets,
+        model::Request::CoreDump { .. } => Permission::CoreDumps,
+        model::Request::CoreDumps { .. } => Permission::CoreDumps,
+        model::Request::Exec { .. } => Permission::Exec,
+        model::Request::Heartbeat => Permission::Heartbeat,
+        model::Request::History { .. } => Permission::History,
         model::Request::Ident { .. } => Permission::Ident,
         model::Request::Inspect { .. } => Permission::Inspect,
         model::Request::Install { .. } => Permission::Install,
+        model::Request::Journal { .. } => Permission::Journal,
         model::Request::Kill { .. } => Permission::Kill,
         model::Request::List => Permission::List,
+        model::Request::Logs { .. } => Permission::Logs,
         model::Request::Mount { .. } => Permission::Mount,
+        model::Request::Notify { .. } => Permission::Notify,
+        model::Request::Pause { .. } => Permission::Pause,
+        model::Request::ReloadConfig => Permission::ReloadConfig,
         model::Request::Repositories => Permission::Repositories,
+        model::Request::Resume { .. } => Permission::Resume,
+        model::Request::Schedules => Permission::Schedules,
         model::Request::Shutdown => Permission::Shutdown,
         model::Request::Start {
             init,
@@ -349,10 +409,12 @@ where
             ..
         } if init.is_none() && arguments.is_empty() && environment.is_empty() => Permission::Start,
         model::Request::Start { .. } => Permission::StartCommand,
+        model::Request::Stop { .. } => Permission::Stop,
         model::Request::TokenCreate { .. } => Permission::TokenCreate,
         model::Request::TokenVerify { .. } => Permission::TokenVerification,
         model::Request::Umount { .. } => Permission::Umount,
         model::Request::Uninstall { .. } => Permission::Uninstall,
+        model::Request::Update { .. } => Permission::Update,
     };
 
     if !permissions.contains(&required_permission) {
@@ -363,6 +425,217 @@ where
 
     let (reply_tx, reply_rx) = oneshot::channel();
     match request {
+        model::Request::Attach { container } => {
+            let (attach_tx, attach_rx) = oneshot::channel();
+            let request = Request::Attach {
+                container,
+                attach: attach_tx,
+            };
+            trace!("    {:?} -> event loop", request);
+            let event = Event::Console(request, reply_tx);
+            event_loop.send(event).await?;
+
+            let response = select! {
+                reply = reply_rx => reply.context("failed to receive reply")?,
+                _ = stop.cancelled() => bail!("shutdown"),
+            };
+            trace!("    {:?} <- event loop", response);
+            if !matches!(
+                response,
+                model::Response::Attach(model::AttachResult::Ok { .. })
+            ) {
+                return Ok(model::Message::Response { response });
+            }
+
+            // Forward the terminal io until the client detaches or the container exits
+            stream
+                .send(model::Message::Response { response })
+                .await
+                .context("failed to send response")?;
+            let attachment = attach_rx.await.context("failed to receive attachment")?;
+            forward_terminal(stream, stop, peer, attachment).await?;
+            return Ok(model::Message::Io {
+                io: model::Io::Detach,
+            });
+        }
+        model::Request::Exec {
+            container,
+            path,
+            args,
+            env,
+            tty,
+        } => {
+            let (io_tx, io_rx) = oneshot::channel();
+            let (exit_tx, exit_rx) = oneshot::channel();
+            let request = Request::Exec {
+                container,
+                path,
+                args,
+                env,
+                tty,
+                io: io_tx,
+                exit: exit_tx,
+            };
+            trace!("    {:?} -> event loop", request);
+            let event = Event::Console(request, reply_tx);
+            event_loop.send(event).await?;
+
+            let response = select! {
+                reply = reply_rx => reply.context("failed to receive reply")?,
+                _ = stop.cancelled() => bail!("shutdown"),
+            };
+            trace!("    {:?} <- event loop", response);
+            if !matches!(
+                response,
+                model::Response::Exec(model::ExecResult::Ok { .. })
+            ) {
+                return Ok(model::Message::Response { response });
+            }
+
+            // The process is running. Forward the io until the process exits.
+            let ExecIo {
+                stdin,
+                stdout,
+                stderr,
+            } = io_rx.await.context("failed to receive exec io")?;
+            stream
+                .send(model::Message::Response { response })
+                .await
+                .context("failed to send response")?;
+            let exit_status =
+                forward_exec_io(stream, stop, tty, stdin, stdout, stderr, exit_rx).await?;
+            let io = model::Io::Exit(exit_status.into());
+            return Ok(model::Message::Io { io });
+        }
+        model::Request::Logs {
+            container,
+            follow,
+            tail,
+            since,
+        } => {
+            let (follow_tx, follow_rx) = oneshot::channel();
+            let request = Request::Logs {
+                container,
+                tail,
+                since,
+                follow: follow.then_some(follow_tx),
+            };
+            trace!("    {:?} -> event loop", request);
+            let event = Event::Console(request, reply_tx);
+            event_loop.send(event).await?;
+
+            let response = select! {
+                reply = reply_rx => reply.context("failed to receive reply")?,
+                _ = stop.cancelled() => bail!("shutdown"),
+            };
+            trace!("    {:?} <- event loop", response);
+            if !follow
+                || !matches!(
+                    response,
+                    model::Response::Logs(model::LogsResult::Ok { .. })
+                )
+            {
+                return Ok(model::Message::Response { response });
+            }
+
+            stream
+                .send(model::Message::Response { response })
+                .await
+                .context("failed to send response")?;
+
+            // Forward the lines written after the request until the container exited.
+            // There is no receiver if the container is not started.
+            if let Ok(mut lines) = follow_rx.await {
+                loop {
+                    let line = select! {
+                        _ = stop.cancelled() => bail!("shutdown"),
+                        message = stream.next() => match message {
+                            Some(Ok(message)) => bail!("unexpected message while following logs: {:?}", message),
+                            Some(Err(e)) => bail!("connection error: {}", e),
+                            None => bail!("connection closed"),
+                        },
+                        line = lines.recv() => match line {
+                            Ok(line) => line,
+                            Err(broadcast::error::RecvError::Lagged(n)) => {
+                                warn!("{}: Skipped {} log lines", peer, n);
+                                continue;
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        },
+                    };
+                    let log = model::Log::Line(line);
+                    stream
+                        .send(model::Message::Log { log })
+                        .await
+                        .context("failed to send log")?;
+                }
+            }
+            return Ok(model::Message::Log {
+                log: model::Log::End,
+            });
+        }
+        model::Request::CoreDump { container, name } => {
+            let (file_tx, file_rx) = oneshot::channel();
+            let request = Request::CoreDump {
+                container,
+                name,
+                file: file_tx,
+            };
+            trace!("    {:?} -> event loop", request);
+            let event = Event::Console(request, reply_tx);
+            event_loop.send(event).await?;
+
+            let response = select! {
+                reply = reply_rx => reply.context("failed to receive reply")?,
+                _ = stop.cancelled() => bail!("shutdown"),
+            };
+            trace!("    {:?} <- event loop", response);
+            if !matches!(
+                response,
+                model::Response::CoreDump(model::CoreDumpResult::Ok { .. })
+            ) {
+                return Ok(model::Message::Response { response });
+            }
+
+            stream
+                .send(model::Message::Response { response })
+                .await
+                .context("failed to send response")?;
+
+            // Send the content of the core dump in chunks
+            let file = file_rx.await.context("failed to receive core dump")?;
+            let mut chunks = ReaderStream::with_capacity(fs::File::from_std(file), CHUNK_SIZE);
+            while let Some(chunk) = select! {
+                _ = stop.cancelled() => bail!("shutdown"),
+                chunk = chunks.next() => chunk,
+            } {
+                let chunk = chunk.context("failed to read core dump")?;
+                let download = model::Download::Chunk(chunk.to_vec());
+                stream
+                    .send(model::Message::Download { download })
+                    .await
+                    .context("failed to send core dump")?;
+            }
+            return Ok(model::Message::Download {
+                download: model::Download::End,
+            });
+        }
+        model::Request::Heartbeat => {
+            // Heartbeats are only relevant for container connections
+            if let Peer::Container(container) = peer {
+                event_loop.send(Event::Heartbeat(container.clone())).await?;
+            }
+            reply_tx.send(api::model::Response::Heartbeat).ok();
+        }
+        model::Request::Notify { notify } => {
+            // Readiness is only relevant for container connections
+            if let Peer::Container(container) = peer {
+                event_loop
+                    .send(Event::Notify(container.clone(), notify))
+                    .await?;
+            }
+            reply_tx.send(api::model::Response::Notify).ok();
+        }
         model::Request::Ident => {
             let ident = match peer {
                 #[allow(clippy::unwrap_used)]
@@ -372,10 +645,7 @@ where
             let response = api::model::Response::Ident(ident);
             reply_tx.send(response).ok();
         }
-        model::Request::Install {
-            repository,
-            mut size,
-        } => {
+        model::Request::Install { repository, size } => {
             debug!(
                 "{}: Received installation request with size {}",
                 peer,
@@ -397,29 +667,42 @@ where
             let event = Event::Console(request, reply_tx);
             event_loop.send(event).await?;
 
-            // The codec might have pulled bytes in the the read buffer of the connection.
-            if !stream.read_buffer().is_empty() {
-                let available = stream.read_buffer().len();
-                // Limit the first read operation to `size` if there's more data available.
-                // If `size` bytes are available, `size` is decremented to 0 and the following
-                // while let loop breaks.
-                let read_max = min(size as usize, available);
-                let buffer = stream.read_buffer_mut().copy_to_bytes(read_max);
-                size -= buffer.len() as u64;
-                tx.send(buffer).await.ok();
-            }
+            forward_npk(stream, size, tx, options).await?;
+        }
+        model::Request::Update {
+            from,
+            to,
+            repository,
+            size,
+            probation,
+        } => {
+            debug!(
+                "{}: Received update request of {} to {} with size {}",
+                peer,
+                from,
+                to,
+                bytesize::ByteSize::b(size)
+            );
 
-            // If the connections breaks: just break. If the receiver is dropped: just break.
-            let mut take = ReaderStream::with_capacity(stream.get_mut().take(size), 1024 * 1024);
-            let timeout = options.npk_stream_timeout;
-            while let Some(buf) = time::timeout(timeout, take.next())
-                .await
-                .context("npk stream timeout")?
-            {
-                let buf = buf.context("npk steam")?;
-                // Ignore any sending error because the stream needs to be drained for `size` bytes.
-                tx.send(buf).await.ok();
+            // Check the update request size
+            if size > options.max_npk_install_size {
+                bail!("npk size too large");
             }
+
+            // Send a Receiver<Bytes> to the runtime and forward n bytes to this channel
+            let (tx, rx) = mpsc::channel(10);
+            let request = Request::Update {
+                from,
+                to,
+                repository,
+                probation: Duration::from_secs(probation),
+                rx,
+            };
+            trace!("    {:?} -> event loop", request);
+            let event = Event::Console(request, reply_tx);
+            event_loop.send(event).await?;
+
+            forward_npk(stream, size, tx, options).await?;
         }
         model::Request::TokenCreate { target, shared } => {
             let user = match peer {
@@ -464,6 +747,19 @@ where
             let response = api::model::Response::TokenVerification(result);
             reply_tx.send(response).ok();
         }
+        model::Request::ReloadConfig => {
+            // The configuration is reloaded by the main loop. The reply is returned without
+            // checking `stop` because the console is restarted right after the reply if the
+            // console configuration changed.
+            let (result_tx, result_rx) = oneshot::channel();
+            event_loop.send(Event::ReloadConfig(result_tx)).await?;
+            let result = match result_rx.await.context("failed to receive reply")? {
+                Ok(()) => model::ReloadConfigResult::Ok,
+                Err(e) => model::ReloadConfigResult::Error { error: e.into() },
+            };
+            let response = model::Response::ReloadConfig(result);
+            return Ok(model::Message::Response { response });
+        }
         request => {
             let message = Request::Request(request);
             trace!("    {:?} -> event loop", message);
@@ -483,6 +779,166 @@ where
     .map(|response| model::Message::Response { response })
 }
 
+/// Forward the `size` bytes of a npk that follow an install or update request on `stream`
+/// to `tx`
+async fn forward_npk<S>(
+    stream: &mut Framed<S>,
+    mut size: u64,
+    tx: mpsc::Sender<Bytes>,
+    options: &Options,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // The codec might have pulled bytes in the the read buffer of the connection.
+    if !stream.read_buffer().is_empty() {
+        let available = stream.read_buffer().len();
+        // Limit the first read operation to `size` if there's more data available.
+        // If `size` bytes are available, `size` is decremented to 0 and the following
+        // while let loop breaks.
+        let read_max = min(size as usize, available);
+        let buffer = stream.read_buffer_mut().copy_to_bytes(read_max);
+        size -= buffer.len() as u64;
+        tx.send(buffer).await.ok();
+    }
+
+    // If the connections breaks: just break. If the receiver is dropped: just break.
+    let mut take = ReaderStream::with_capacity(stream.get_mut().take(size), 1024 * 1024);
+    let timeout = options.npk_stream_timeout;
+    while let Some(buf) = time::timeout(timeout, take.next())
+        .await
+        .context("npk stream timeout")?
+    {
+        let buf = buf.context("npk steam")?;
+        // Ignore any sending error because the stream needs to be drained for `size` bytes.
+        tx.send(buf).await.ok();
+    }
+    Ok(())
+}
+
+/// Forward the io of a process spawned with an exec request between the client and
+/// the process. Returns the exit status of the process once it exited.
+async fn forward_exec_io<S>(
+    stream: &mut Framed<S>,
+    stop: &CancellationToken,
+    tty: bool,
+    mut stdin: Box<dyn AsyncWrite + Send + Unpin>,
+    stdout: Box<dyn AsyncRead + Send + Unpin>,
+    stderr: Box<dyn AsyncRead + Send + Unpin>,
+    exit: oneshot::Receiver<ExitStatus>,
+) -> Result<ExitStatus>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut stdout = ReaderStream::new(stdout);
+    let mut stderr = ReaderStream::new(stderr);
+    let mut stdout_closed = false;
+    let mut stderr_closed = false;
+
+    // Forward until the process closed stdout and stderr. Errors on stdin are ignored
+    // because the process is free to close its input.
+    while !stdout_closed || !stderr_closed {
+        let io = select! {
+            _ = stop.cancelled() => bail!("shutdown"),
+            message = stream.next() => {
+                match message {
+                    Some(Ok(model::Message::Io { io: model::Io::Stdin(data) })) => {
+                        if !data.is_empty() {
+                            stdin.write_all(&data).await.ok();
+                        } else if tty {
+                            stdin.write_all(&[EOT]).await.ok();
+                        } else {
+                            stdin.shutdown().await.ok();
+                        }
+                        stdin.flush().await.ok();
+                    }
+                    Some(Ok(message)) => bail!("unexpected message during exec: {:?}", message),
+                    Some(Err(e)) => bail!("connection error: {}", e),
+                    None => bail!("connection closed"),
+                }
+                continue;
+            }
+            // Reading from a terminal fails with EIO once the process closed its end
+            data = stdout.next(), if !stdout_closed => match data {
+                Some(Ok(data)) => model::Io::Stdout(data.to_vec()),
+                _ => {
+                    stdout_closed = true;
+                    continue;
+                }
+            },
+            data = stderr.next(), if !stderr_closed => match data {
+                Some(Ok(data)) => model::Io::Stderr(data.to_vec()),
+                _ => {
+                    stderr_closed = true;
+                    continue;
+                }
+            },
+        };
+        stream
+            .send(model::Message::Io { io })
+            .await
+            .context("failed to send io")?;
+    }
+
+    // The exit status is dropped if the container exited before the process. The
+    // process is killed with the pid namespace of the container in this case but its
+    // exit status is unknown. The connection is closed with an error.
+    select! {
+        exit_status = exit => exit_status.context("exit status of the process is lost"),
+        _ = stop.cancelled() => bail!("shutdown"),
+    }
+}
+
+/// Forward the io of a client attached to the terminal of a container until the client
+/// detaches or the terminal is closed
+async fn forward_terminal<S>(
+    stream: &mut Framed<S>,
+    stop: &CancellationToken,
+    peer: &Peer,
+    mut attachment: Attachment,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        let data = select! {
+            _ = stop.cancelled() => bail!("shutdown"),
+            message = stream.next() => {
+                match message {
+                    Some(Ok(model::Message::Io { io: model::Io::Stdin(data) })) => {
+                        let data = if data.is_empty() { vec![EOT] } else { data };
+                        // The input is closed if the container exited
+                        attachment.input.send(data).await.ok();
+                    }
+                    Some(Ok(model::Message::Io { io: model::Io::Resize { rows, cols } })) => {
+                        if let Err(e) = attachment.resize(rows, cols) {
+                            warn!("{}: Failed to resize terminal: {}", peer, e);
+                        }
+                    }
+                    Some(Ok(model::Message::Io { io: model::Io::Detach })) => return Ok(()),
+                    Some(Ok(message)) => bail!("unexpected message while attached: {:?}", message),
+                    Some(Err(e)) => bail!("connection error: {}", e),
+                    None => bail!("connection closed"),
+                }
+                continue;
+            }
+            data = attachment.output.recv() => match data {
+                Ok(data) => data,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("{}: Skipped {} terminal outputs", peer, n);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        };
+        let io = model::Io::Stdout(data);
+        stream
+            .send(model::Message::Io { io })
+            .await
+            .context("failed to send io")?;
+    }
+}
+
 /// Function to handle connections
 ///
 /// Generic handling of connections. The first parameter is a function that when called awaits for
@@ -597,13 +1053,69 @@ impl From<ExitStatus> for model::ExitStatus {
     }
 }
 
+impl From<Health> for model::Health {
+    fn from(health: Health) -> Self {
+        match health {
+            Health::Healthy => model::Health::Healthy,
+            Health::Unhealthy => model::Health::Unhealthy,
+        }
+    }
+}
+
+impl From<UpdateProgress> for model::UpdateProgress {
+    fn from(progress: UpdateProgress) -> Self {
+        match progress {
+            UpdateProgress::Stopping => model::UpdateProgress::Stopping,
+            UpdateProgress::Probation => model::UpdateProgress::Probation,
+            UpdateProgress::Completed => model::UpdateProgress::Completed,
+            UpdateProgress::RolledBack => model::UpdateProgress::RolledBack,
+        }
+    }
+}
+
+impl From<CriticalAction> for model::CriticalAction {
+    fn from(action: CriticalAction) -> Self {
+        match action {
+            CriticalAction::Restart(retry) => model::CriticalAction::Restart { retry },
+            CriticalAction::RestartRuntime => model::CriticalAction::RestartRuntime,
+            CriticalAction::FailureHandler(container) => {
+                model::CriticalAction::FailureHandler { container }
+            }
+            CriticalAction::Reboot => model::CriticalAction::Reboot,
+            CriticalAction::Exit => model::CriticalAction::Exit,
+        }
+    }
+}
+
+impl From<ExitReason> for model::ExitReason {
+    fn from(reason: ExitReason) -> Self {
+        match reason {
+            ExitReason::Exited => model::ExitReason::Exited,
+            ExitReason::Stopped => model::ExitReason::Stopped,
+            ExitReason::MaxRuntime => model::ExitReason::MaxRuntime,
+            ExitReason::NotReady => model::ExitReason::NotReady,
+        }
+    }
+}
+
+impl From<ScheduledRun> for model::ScheduledRun {
+    fn from(run: ScheduledRun) -> Self {
+        match run {
+            ScheduledRun::Started => model::ScheduledRun::Started,
+            ScheduledRun::Skipped => model::ScheduledRun::Skipped,
+            ScheduledRun::Deferred => model::ScheduledRun::Deferred,
+            ScheduledRun::Failed(error) => model::ScheduledRun::Failed { error },
+        }
+    }
+}
+
 impl From<(Container, ContainerEvent)> for model::Notification {
     fn from(p: (Container, ContainerEvent)) -> model::Notification {
         let container = p.0.clone();
         match p.1 {
             ContainerEvent::Started => api::model::Notification::Started(container),
-            ContainerEvent::Exit(status) => {
-                api::model::Notification::Exit(container, status.into())
+            ContainerEvent::Exit(status, reason) => {
+                api::model::Notification::Exit(container, status.into(), reason.into())
             }
             ContainerEvent::Installed => api::model::Notification::Install(container),
             ContainerEvent::Uninstalled => api::model::Notification::Uninstall(container),
@@ -619,6 +1131,28 @@ impl From<(Container, ContainerEvent)> for model::Notification {
                     }),
                 ),
             },
+            ContainerEvent::RestartExhausted(retries) => {
+                api::model::Notification::RestartExhausted(container, retries)
+            }
+            ContainerEvent::Health(health) => {
+                api::model::Notification::Health(container, health.into())
+            }
+            ContainerEvent::Paused => api::model::Notification::Paused(container),
+            ContainerEvent::Resumed => api::model::Notification::Resumed(container),
+            ContainerEvent::Ready => api::model::Notification::Ready(container),
+            ContainerEvent::Update(from, progress) => api::model::Notification::Update {
+                from,
+                to: container,
+                progress: progress.into(),
+            },
+            ContainerEvent::Schedule(run) => api::model::Notification::Schedule {
+                container,
+                run: run.into(),
+            },
+            ContainerEvent::Critical(action) => api::model::Notification::Critical {
+                container,
+                action: action.into(),
+            },
         }
     }
 }
@@ -25,28 +25,56 @@ impl fmt::Display for Permissions {
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub enum Permission {
+    /// Attach to the terminal of a container
+    Attach,
+    /// Show the usage of the admission budgets
+    Budgets,
+    /// List and download the core dumps of a container
+    CoreDumps,
+    /// Execute a process in a started container
+    Exec,
+    /// Health heartbeat
+    Heartbeat,
+    /// Show the terminated runs of a container
+    History,
     /// Identification
     Ident,
     /// Inspect a container
     Inspect,
     /// Install a container
     Install,
+    /// Inspect or clear the desired state journal
+    Journal,
     /// Send a singal to a container
     Kill,
     /// List all containers
     List,
+    /// Read the output of a container
+    Logs,
     /// Notifications
     Notifications,
+    /// Report the readiness and status of the container
+    Notify,
     /// Mount a container
     Mount,
+    /// Pause a container
+    Pause,
+    /// Reload the runtime configuration
+    ReloadConfig,
     /// List repositories
     Repositories,
+    /// Resume a paused container
+    Resume,
+    /// List the schedules of the containers
+    Schedules,
     /// Shutdown the runtime
     Shutdown,
     /// Start a container
     Start,
     /// Start a container with custom init, args and env
     StartCommand,
+    /// Stop a container
+    Stop,
     /// Token creation
     TokenCreate,
     /// Token verification
@@ -55,6 +83,8 @@ pub enum Permission {
     Umount,
     /// Uninstall a container
     Uninstall,
+    /// Update a started container to a new version
+    Update,
 }
 
 impl fmt::Display for Permission {
@@ -73,21 +103,36 @@ impl From<crate::npk::manifest::console::Permissions> for Permissions {
 impl From<crate::npk::manifest::console::Permission> for Permission {
     fn from(permission: ManifestPermission) -> Permission {
         match permission {
+            ManifestPermission::Attach => Permission::Attach,
+            ManifestPermission::Exec => Permission::Exec,
+            ManifestPermission::Heartbeat => Permission::Heartbeat,
+            ManifestPermission::History => Permission::History,
             ManifestPermission::Ident => Permission::Ident,
             ManifestPermission::Inspect => Permission::Inspect,
             ManifestPermission::Install => Permission::Install,
+            ManifestPermission::Journal => Permission::Journal,
             ManifestPermission::Kill => Permission::Kill,
             ManifestPermission::List => Permission::List,
+            ManifestPermission::Logs => Permission::Logs,
             ManifestPermission::Notifications => Permission::Notifications,
+            ManifestPermission::Notify => Permission::Notify,
+            ManifestPermission::Budgets => Permission::Budgets,
+            ManifestPermission::CoreDumps => Permission::CoreDumps,
             ManifestPermission::Mount => Permission::Mount,
+            ManifestPermission::Pause => Permission::Pause,
+            ManifestPermission::ReloadConfig => Permission::ReloadConfig,
             ManifestPermission::Repositories => Permission::Repositories,
+            ManifestPermission::Resume => Permission::Resume,
+            ManifestPermission::Schedules => Permission::Schedules,
             ManifestPermission::Shutdown => Permission::Shutdown,
             ManifestPermission::Start => Permission::Start,
             ManifestPermission::StartCommand => Permission::StartCommand,
+            ManifestPermission::Stop => Permission::Stop,
             ManifestPermission::TokenCreate => Permission::TokenCreate,
             ManifestPermission::TokenVerification => Permission::TokenVerification,
             ManifestPermission::Umount => Permission::Umount,
             ManifestPermission::Uninstall => Permission::Uninstall,
+            ManifestPermission::Update => Permission::Update,
         }
     }
 }
@@ -0,0 +1,171 @@
+use std::{
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{api::model, common::name::Name, runtime::config};
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+use tokio::fs;
+
+/// Kernel core pattern
+const CORE_PATTERN: &str = "/proc/sys/kernel/core_pattern";
+
+/// Directory of the core dumps of the container `name`. Like the persist directory the
+/// core dumps are shared between versions.
+pub(super) fn dir(config: &config::CoreDumps, name: &Name) -> PathBuf {
+    config.dir.join(name.as_ref())
+}
+
+/// Check that the kernel writes core dumps to an absolute path. The path is resolved in
+/// the mount namespace of the crashing process and must point into the `core_dumps` mount
+/// of the container. Core dumps piped to a program would bypass the quota of the container.
+pub(super) async fn check_pattern() -> Result<()> {
+    let pattern = fs::read_to_string(CORE_PATTERN)
+        .await
+        .with_context(|| format!("failed to read {CORE_PATTERN}"))?;
+    let pattern = pattern.trim();
+    if !pattern.starts_with('/') {
+        bail!("kernel core pattern {pattern} is not an absolute path");
+    }
+    debug!("Kernel core pattern is {}", pattern);
+    Ok(())
+}
+
+/// Core dumps in `dir` starting with the oldest. A missing directory contains no core dumps.
+pub(super) async fn list(dir: &Path) -> Result<Vec<model::CoreDump>> {
+    let mut core_dumps = Vec::new();
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(core_dumps),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", dir.display())),
+    };
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .with_context(|| format!("failed to read {}", dir.display()))?
+    {
+        let metadata = entry
+            .metadata()
+            .await
+            .with_context(|| format!("failed to stat {}", entry.path().display()))?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let created = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_millis() as u64)
+            .unwrap_or_default();
+        core_dumps.push(model::CoreDump {
+            name: entry.file_name().to_string_lossy().to_string(),
+            size: metadata.len(),
+            created,
+        });
+    }
+    core_dumps.sort_by(|a, b| (a.created, &a.name).cmp(&(b.created, &b.name)));
+    Ok(core_dumps)
+}
+
+/// Remove everything in `dir` that is not a core dump and the oldest core dumps until at
+/// most `retention` core dumps with a total size of at most `quota` bytes are left
+pub(super) async fn retain(dir: &Path, retention: usize, quota: u64) -> Result<()> {
+    // The directory is writeable by the container. Directories, links and other files
+    // are not core dumps and not accounted by `list`.
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", dir.display())),
+    };
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .with_context(|| format!("failed to read {}", dir.display()))?
+    {
+        let file_type = entry
+            .file_type()
+            .await
+            .with_context(|| format!("failed to stat {}", entry.path().display()))?;
+        let path = entry.path();
+        let result = if file_type.is_dir() {
+            fs::remove_dir_all(&path).await
+        } else if !file_type.is_file() {
+            fs::remove_file(&path).await
+        } else {
+            continue;
+        };
+        warn!("Removing {} which is not a core dump", path.display());
+        result.with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+
+    let core_dumps = list(dir).await?;
+    let mut count = core_dumps.len();
+    let mut size = core_dumps
+        .iter()
+        .map(|core_dump| core_dump.size)
+        .sum::<u64>();
+    for core_dump in &core_dumps {
+        if count <= retention && size <= quota {
+            break;
+        }
+        let path = dir.join(&core_dump.name);
+        debug!("Removing core dump {}", path.display());
+        fs::remove_file(&path)
+            .await
+            .with_context(|| format!("failed to remove {}", path.display()))?;
+        count -= 1;
+        size -= core_dump.size;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn list_and_retain() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list(&dir.path().join("missing")).await.unwrap().is_empty());
+
+        // Core dumps with the same modification time are ordered by name
+        for (n, name) in ["core.1", "core.2", "core.3"].iter().enumerate() {
+            std::fs::write(dir.path().join(name), vec![0u8; n + 1]).unwrap();
+        }
+        std::fs::create_dir(dir.path().join("dir")).unwrap();
+
+        let core_dumps = list(dir.path()).await.unwrap();
+        assert_eq!(
+            core_dumps
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["core.1", "core.2", "core.3"]
+        );
+        assert_eq!(core_dumps[2].size, 3);
+
+        retain(dir.path(), 2, u64::MAX).await.unwrap();
+        let core_dumps = list(dir.path()).await.unwrap();
+        assert_eq!(
+            core_dumps
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["core.2", "core.3"]
+        );
+        assert!(!dir.path().join("dir").exists());
+
+        // The oldest core dumps are removed until the quota is met
+        retain(dir.path(), 2, 3).await.unwrap();
+        let core_dumps = list(dir.path()).await.unwrap();
+        assert_eq!(
+            core_dumps
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["core.3"]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::common::container::Container;
+use std::collections::{HashMap, HashSet};
+
+/// Dependency graph. Maps a container to the containers it depends on.
+pub(super) type Graph = HashMap<Container, HashSet<Container>>;
+
+/// Sort `graph` into layers. The containers of a layer depend only on containers of
+/// previous layers and are independent of each other. Dependencies that are not a node
+/// of `graph` are considered as satisfied.
+///
+/// Returns the containers that are part of a cycle or depend on a cycle as error.
+pub(super) fn layers(graph: &Graph) -> Result<Vec<Vec<Container>>, HashSet<Container>> {
+    let mut layers = Vec::new();
+    let mut remaining = graph
+        .iter()
+        .map(|(container, dependencies)| {
+            let dependencies = dependencies
+                .iter()
+                .filter(|d| graph.contains_key(d))
+                .collect::<HashSet<_>>();
+            (container, dependencies)
+        })
+        .collect::<HashMap<_, _>>();
+
+    while !remaining.is_empty() {
+        let mut layer = remaining
+            .iter()
+            .filter(|(_, dependencies)| dependencies.is_empty())
+            .map(|(container, _)| (*container).clone())
+            .collect::<Vec<_>>();
+
+        if layer.is_empty() {
+            return Err(remaining.into_keys().cloned().collect());
+        }
+        layer.sort();
+
+        for container in &layer {
+            remaining.remove(container);
+        }
+        for dependencies in remaining.values_mut() {
+            for container in &layer {
+                dependencies.remove(container);
+            }
+        }
+        layers.push(layer);
+    }
+
+    Ok(layers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{layers, Graph};
+    use crate::common::container::Container;
+    use std::convert::TryFrom;
+
+    #[allow(clippy::unwrap_used)]
+    fn graph(edges: &[(&str, &[&str])]) -> Graph {
+        edges
+            .iter()
+            .map(|(container, dependencies)| {
+                let container = Container::try_from(*container).unwrap();
+                let dependencies = dependencies
+                    .iter()
+                    .map(|d| Container::try_from(*d).unwrap())
+                    .collect();
+                (container, dependencies)
+            })
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn sort() {
+        let graph = graph(&[
+            ("client:0.0.1", &["server:0.0.1", "logger:0.0.1"]),
+            ("server:0.0.1", &["logger:0.0.1"]),
+            ("logger:0.0.1", &[]),
+            ("other:0.0.1", &[]),
+        ]);
+        let layers = layers(&graph).unwrap();
+        assert_eq!(layers.len(), 3);
+        assert_eq!(
+            layers[0],
+            vec![
+                Container::try_from("logger:0.0.1").unwrap(),
+                Container::try_from("other:0.0.1").unwrap()
+            ]
+        );
+        assert_eq!(
+            layers[1],
+            vec![Container::try_from("server:0.0.1").unwrap()]
+        );
+        assert_eq!(
+            layers[2],
+            vec![Container::try_from("client:0.0.1").unwrap()]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn external_dependency() {
+        let graph = graph(&[("client:0.0.1", &["server:0.0.1"])]);
+        let layers = layers(&graph).unwrap();
+        assert_eq!(
+            layers,
+            vec![vec![Container::try_from("client:0.0.1").unwrap()]]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn cycle() {
+        let graph = graph(&[
+            ("a:0.0.1", &["b:0.0.1"]),
+            ("b:0.0.1", &["c:0.0.1"]),
+            ("c:0.0.1", &["a:0.0.1"]),
+            ("d:0.0.1", &["a:0.0.1"]),
+            ("e:0.0.1", &[]),
+        ]);
+        let cycle = layers(&graph).unwrap_err();
+        assert_eq!(cycle.len(), 4);
+        assert!(!cycle.contains(&Container::try_from("e:0.0.1").unwrap()));
+    }
+}
@@ -6,3 +6,11 @@ pub(crate) const VERSION: &str = "NORTHSTAR_VERSION";
 pub(crate) const CONTAINER: &str = "NORTHSTAR_CONTAINER";
 /// Environment variable name passed to the container with the console fd
 pub(crate) const CONSOLE: &str = "NORTHSTAR_CONSOLE";
+/// Environment variable name passed to hooks with the lifecycle stage
+pub(crate) const HOOK: &str = "NORTHSTAR_HOOK";
+/// Environment variable name passed to hook containers with the container the hook runs for
+pub(crate) const HOOK_CONTAINER: &str = "NORTHSTAR_HOOK_CONTAINER";
+/// Environment variable name passed to hooks with the pid of the container
+pub(crate) const PID: &str = "NORTHSTAR_PID";
+/// Environment variable name passed to hooks with the exit status of the container
+pub(crate) const EXIT_STATUS: &str = "NORTHSTAR_EXIT_STATUS";
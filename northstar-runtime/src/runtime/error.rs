@@ -28,18 +28,32 @@ pub enum Error {
     StartContainerResource(Container),
     #[error("container {0} failed to start: resource {1} version {2} is missing")]
     StartContainerMissingResource(Container, Name, String),
+    #[error("container {0} failed to start: dependency {1} version {2} is missing")]
+    StartContainerMissingDependency(Container, Name, String),
     #[error("container {0} failed to start: {1}")]
     StartContainerFailed(Container, String),
     #[error("container {0} failed to stop")]
     StopContainerNotStarted(Container),
-    #[error("container {0} failed is stop: not started")]
+    #[error("container {0} is not started")]
     ContainerNotStarted(Container),
+    #[error("container {0} is paused")]
+    ContainerPaused(Container),
+    #[error("container {0} is not paused")]
+    ContainerNotPaused(Container),
+    #[error("container {0} has no terminal")]
+    ContainerNoTerminal(Container),
     #[error("invalid repository {0}")]
     InvalidRepository(RepositoryId),
     #[error("failed to install {0}: already installed")]
     InstallDuplicate(Container),
+    #[error("container {0} is part of a dependency cycle")]
+    DependencyCycle(Container),
+    #[error("hook {1} of {0} failed: {2}")]
+    HookFailed(Container, String, String),
     #[error("critical container failure")]
     CriticalContainer(Container, ExitStatus),
+    #[error("container {0} failed to start: {1} budget exceeded (requested {2}, used {3} of {4})")]
+    BudgetExceeded(Container, api::model::Budget, u64, u64, u64),
 
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
@@ -69,6 +83,13 @@ impl From<Error> for api::model::Error {
                     version,
                 }
             }
+            Error::StartContainerMissingDependency(container, dependency, version) => {
+                api::model::Error::StartContainerMissingDependency {
+                    container,
+                    dependency,
+                    version,
+                }
+            }
             Error::StartContainerFailed(container, error) => {
                 api::model::Error::StartContainerFailed { container, error }
             }
@@ -76,16 +97,38 @@ impl From<Error> for api::model::Error {
                 api::model::Error::StopContainerNotStarted { container }
             }
             Error::ContainerNotStarted(container) => {
-                api::model::Error::StopContainerNotStarted { container }
+                api::model::Error::ContainerNotStarted { container }
+            }
+            Error::ContainerPaused(container) => api::model::Error::ContainerPaused { container },
+            Error::ContainerNotPaused(container) => {
+                api::model::Error::ContainerNotPaused { container }
+            }
+            Error::ContainerNoTerminal(container) => {
+                api::model::Error::ContainerNoTerminal { container }
             }
             Error::InvalidRepository(repository) => {
                 api::model::Error::InvalidRepository { repository }
             }
             Error::InstallDuplicate(container) => api::model::Error::InstallDuplicate { container },
+            Error::DependencyCycle(container) => api::model::Error::DependencyCycle { container },
+            Error::HookFailed(container, hook, error) => api::model::Error::HookFailed {
+                container,
+                hook,
+                error,
+            },
             Error::CriticalContainer(container, status) => api::model::Error::CriticalContainer {
                 container,
                 status: status.into(),
             },
+            Error::BudgetExceeded(container, budget, requested, used, limit) => {
+                api::model::Error::BudgetExceeded {
+                    container,
+                    budget,
+                    requested,
+                    used,
+                    limit,
+                }
+            }
             Error::Unexpected(error) => api::model::Error::Unexpected {
                 error: error.to_string(),
             },
@@ -1,8 +1,12 @@
 use tokio::sync::{mpsc, oneshot};
 
-use crate::{api, common::container::Container};
+use crate::{
+    api,
+    common::{container::Container, non_nul_string::NonNulString},
+    npk::manifest::hooks::Stage,
+};
 
-use super::{console, exit_status::ExitStatus};
+use super::{console, error::Error, exit_status::ExitStatus, hooks, runtime::Pid};
 
 pub(crate) type EventTx = mpsc::Sender<Event>;
 
@@ -12,22 +16,121 @@ pub(crate) enum Event {
     Console(console::Request, oneshot::Sender<api::model::Response>),
     /// Northstar shall shut down
     Shutdown,
+    /// Reload the configuration file
+    ReloadConfig(oneshot::Sender<Result<(), Error>>),
     /// Container event
     Container(Container, ContainerEvent),
+    /// Scheduled restart of a container
+    Restart(Container),
+    /// Heartbeat of a container received via the console
+    Heartbeat(Container),
+    /// Execute a health probe in a container. The pid and a receiver for the exit
+    /// status of the probe are sent back.
+    Probe(
+        Container,
+        NonNulString,
+        Vec<NonNulString>,
+        oneshot::Sender<(Pid, oneshot::Receiver<ExitStatus>)>,
+    ),
+    /// Readiness or status of a container received via the console
+    Notify(Container, api::model::Notify),
+    /// The ready timeout of a container elapsed
+    ReadyTimeout(Container),
+    /// The maximum run time of a container elapsed
+    MaxRuntime(Container),
+    /// The stop timeout of a container elapsed
+    StopTimeout(Container),
+    /// A process spawned with an exec request exited
+    ExecExit(Container, Pid, ExitStatus),
+    /// The probation period of an updated container elapsed
+    Probation(Container),
+    /// A critical container requested a restart of the runtime
+    RestartRuntime(Container),
+    /// The reboot command executed for a failed critical container failed
+    RebootFailed(Container, ExitStatus),
+    /// The hooks of a run finished
+    Hooks(Stage, hooks::Results, hooks::Action),
+    /// Start a hook container
+    HookContainer(hooks::ContainerRequest),
+    /// The timeout of a hook container elapsed
+    HookTimeout(Container),
+    /// A scheduled run of a container is due
+    Schedule(Container),
 }
 
 #[derive(Clone, Debug)]
 pub(crate) enum ContainerEvent {
     /// Container has been started
     Started,
-    /// Container exited with status
-    Exit(ExitStatus),
+    /// Container is ready
+    Ready,
+    /// Container exited with status and reason. The forker reports every exit as
+    /// `Exited`. The runtime replaces the reason if it terminated the container.
+    Exit(ExitStatus, ExitReason),
     /// Container is installed
     Installed,
     /// Container is uninstalled
     Uninstalled,
     /// CGroup event
     CGroup(CGroupEvent),
+    /// The runtime gave up restarting the container after n retries
+    RestartExhausted(u32),
+    /// Health changed
+    Health(Health),
+    /// Container is frozen
+    Paused,
+    /// Container is thawed
+    Resumed,
+    /// Progress of the update from a previous version
+    Update(Container, UpdateProgress),
+    /// The container failed and the critical action is carried out
+    Critical(CriticalAction),
+    /// A scheduled run is triggered
+    Schedule(ScheduledRun),
+}
+
+/// Container health reported by health probes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Health {
+    Healthy,
+    Unhealthy,
+}
+
+/// Reason of the termination of a container
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExitReason {
+    Exited,
+    Stopped,
+    MaxRuntime,
+    NotReady,
+}
+
+/// Progress of a container update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UpdateProgress {
+    Stopping,
+    Probation,
+    Completed,
+    RolledBack,
+}
+
+/// Outcome of a scheduled run
+#[derive(Clone, Debug)]
+pub(crate) enum ScheduledRun {
+    Started,
+    Skipped,
+    Deferred,
+    Failed(api::model::Error),
+}
+
+/// Action taken after a critical container failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CriticalAction {
+    Restart(u32),
+    RestartRuntime,
+    FailureHandler(Container),
+    Reboot,
+    Exit,
 }
 
 /// Events generated by cgroup controllers
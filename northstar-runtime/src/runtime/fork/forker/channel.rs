@@ -3,7 +3,7 @@ use std::{iter::once, os::unix::prelude::OwnedFd};
 use crate::{
     common::{container::Container, non_nul_string::NonNulString},
     runtime::{
-        fork::init::Init,
+        fork::init::{Init, Spawn},
         ipc::{AsyncFramedUnixStream, FramedUnixStream},
         runtime::Pid,
     },
@@ -55,6 +55,14 @@ impl Channel {
                     self.socket.send_fds(&fds).expect("failed to send fds");
                 }
             }
+            Message::SpawnRequest { spawn, io } => {
+                let message = SerdeMessage::SpawnRequest { spawn };
+                self.command
+                    .send(message)
+                    .await
+                    .expect("failed to send spawn request");
+                self.socket.send_fds(&io).expect("failed to send fds");
+            }
             m => {
                 let message: SerdeMessage = m.into();
                 self.command
@@ -103,6 +111,15 @@ impl Channel {
                     sockets,
                 })
             }
+            SerdeMessage::SpawnRequest { spawn } => {
+                let io = self
+                    .socket
+                    .recv_fds::<OwnedFd>(3)
+                    .expect("failed to receive fds")
+                    .try_into()
+                    .expect("failed to convert io fds");
+                Some(Message::SpawnRequest { spawn, io })
+            }
             message => Some(message.into()),
         }
     }
@@ -128,6 +145,14 @@ enum SerdeMessage {
     },
     /// Confirmation message for a exec request.
     ExecResult,
+    /// Spawn an additional process in the namespaces of a started container.
+    SpawnRequest {
+        spawn: Spawn,
+    },
+    /// Result of a spawn request.
+    SpawnResult {
+        result: Result<Pid, String>,
+    },
 }
 
 impl From<Message> for SerdeMessage {
@@ -147,6 +172,8 @@ impl From<Message> for SerdeMessage {
                 env,
             },
             Message::ExecResult => SerdeMessage::ExecResult,
+            Message::SpawnRequest { spawn, .. } => SerdeMessage::SpawnRequest { spawn },
+            Message::SpawnResult { result } => SerdeMessage::SpawnResult { result },
         }
     }
 }
@@ -170,6 +197,10 @@ impl From<SerdeMessage> for Message {
                 env,
             },
             SerdeMessage::ExecResult => Message::ExecResult,
+            SerdeMessage::SpawnRequest { .. } => {
+                unreachable!("this shall never happen and is a bug")
+            }
+            SerdeMessage::SpawnResult { result } => Message::SpawnResult { result },
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::os::unix::prelude::OwnedFd;
 
-use super::init::Init;
+use super::init::{Init, Spawn};
 use crate::{
     common::{container::Container, non_nul_string::NonNulString},
     runtime::{exit_status::ExitStatus, runtime::Pid},
@@ -31,6 +31,10 @@ pub enum Message {
     },
     /// Confirmation message for a exec request.
     ExecResult,
+    /// Spawn an additional process in the namespaces of a started container.
+    SpawnRequest { spawn: Spawn, io: [OwnedFd; 3] },
+    /// Result of a spawn request.
+    SpawnResult { result: Result<Pid, String> },
 }
 
 /// Notification from the forker to the runtime.
@@ -40,4 +44,10 @@ pub enum Notification {
         container: Container,
         exit_status: ExitStatus,
     },
+    /// A process spawned in a container exited.
+    SpawnExit {
+        container: Container,
+        pid: Pid,
+        exit_status: ExitStatus,
+    },
 }
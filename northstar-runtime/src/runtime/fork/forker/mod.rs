@@ -10,19 +10,23 @@ use crate::{
     npk::manifest::Manifest,
     runtime::{
         config::Config,
+        fork::init::Spawn,
         ipc::{AsyncFramedUnixStream, FramedUnixStream},
         runtime::Pid,
     },
 };
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use log::debug;
 pub use messages::{Message, Notification};
 use nix::{
     sys::signal::{signal, SigHandler, Signal},
     unistd,
 };
-use std::os::unix::{net::UnixStream, prelude::OwnedFd};
-use tokio::runtime;
+use std::{
+    os::unix::{net::UnixStream, prelude::OwnedFd},
+    path::PathBuf,
+};
+use tokio::{runtime, sync::Mutex};
 
 mod channel;
 mod messages;
@@ -85,10 +89,10 @@ pub fn start() -> Result<(Pid, Streams)> {
 }
 
 /// Handle to the forker process. This is used in the runtime to interface.
-/// with the forker process.
+/// with the forker process. Requests of concurrent starts are sent one after another.
 #[derive(Debug)]
 pub struct Forker {
-    channel: Channel,
+    channel: Mutex<Channel>,
 }
 
 impl Forker {
@@ -99,14 +103,14 @@ impl Forker {
         // this fn takes UnixStream from `std`.
         let command_stream = AsyncFramedUnixStream::new(command_stream);
         let socket_stream = FramedUnixStream::new(socket_stream);
-        let channel = Channel::new(command_stream, socket_stream);
+        let channel = Mutex::new(Channel::new(command_stream, socket_stream));
         Self { channel }
     }
 
     /// Send a request to the forker process to create a new container.
     #[allow(clippy::too_many_arguments)]
     pub async fn create<'a, I: Iterator<Item = &'a Container> + Clone>(
-        &mut self,
+        &self,
         container: &Container,
         config: &Config,
         manifest: &Manifest,
@@ -126,10 +130,11 @@ impl Forker {
             console,
             sockets,
         };
-        self.channel.send(request).await;
+        let mut channel = self.channel.lock().await;
+        channel.send(request).await;
 
         // Response
-        match self.channel.recv().await {
+        match channel.recv().await {
             Some(Message::CreateResult { result }) => {
                 result.map_err(|e| Error::StartContainerFailed(container.clone(), e))
             }
@@ -140,7 +145,7 @@ impl Forker {
 
     /// Start container process in a previously created container.
     pub async fn exec(
-        &mut self,
+        &self,
         container: Container,
         path: NonNulString,
         args: Args,
@@ -153,13 +158,50 @@ impl Forker {
             args,
             env,
         };
-        self.channel.send(request).await;
+        let mut channel = self.channel.lock().await;
+        channel.send(request).await;
 
         // Response
-        match self.channel.recv().await {
+        match channel.recv().await {
             Some(Message::ExecResult { .. }) => Ok(()),
             Some(message) => panic!("unexpected message from forker: {message:?}"),
             None => panic!("forker stream closed"),
         }
     }
+
+    /// Spawn an additional process in the namespaces of a started container. The
+    /// process is added to the cgroups whose `cgroup.procs` files are passed.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn spawn(
+        &self,
+        container: Container,
+        path: NonNulString,
+        args: Args,
+        env: Env,
+        tty: bool,
+        cgroups: Vec<PathBuf>,
+        io: [OwnedFd; 3],
+    ) -> Result<Pid, Error> {
+        // Request
+        let spawn = Spawn {
+            container,
+            path,
+            args,
+            env,
+            cgroups,
+            tty,
+        };
+        let request = Message::SpawnRequest { spawn, io };
+        let mut channel = self.channel.lock().await;
+        channel.send(request).await;
+
+        // Response
+        match channel.recv().await {
+            Some(Message::SpawnResult { result }) => {
+                result.map_err(|e| Error::Unexpected(anyhow!(e)))
+            }
+            Some(message) => panic!("unexpected message from forker: {message:?}"),
+            None => panic!("forker stream closed"),
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use super::{
     init,
-    init::Init,
+    init::{Init, Spawn},
     messages::{Message, Notification},
 };
 use crate::{
     common::{container::Container, non_nul_string::NonNulString},
+    npk::manifest::{network::Network, time_namespace::TimeNamespace},
     runtime::{
         exit_status::ExitStatus,
         fork::{forker::channel::Channel, util::set_parent_death_signal},
@@ -14,20 +15,22 @@ use crate::{
 };
 use anyhow::{anyhow, Context, Result};
 use futures::{
+    future::LocalBoxFuture,
     stream::{FuturesUnordered, StreamExt},
-    Future,
+    Future, FutureExt,
 };
 use itertools::Itertools;
 use log::{debug, warn};
 use nix::{
     errno::Errno,
-    libc::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO},
+    libc::{self, c_int, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO},
     sched,
     sys::{signal::Signal, wait::waitpid},
     unistd::{self, fork, ForkResult},
 };
 use std::{
     collections::HashMap,
+    fs,
     os::unix::{
         net::UnixStream,
         prelude::{AsRawFd, OwnedFd},
@@ -42,8 +45,9 @@ pub async fn run(
     socket_stream: UnixStream,
     notifications: UnixStream,
 ) -> ! {
-    let mut inits = HashMap::<Container, (Pid, FramedUnixStream)>::new();
-    let mut exits = FuturesUnordered::new();
+    let mut inits = HashMap::<Container, (Pid, FramedUnixStream, Init)>::new();
+    let mut started = HashMap::<Container, (Pid, Init)>::new();
+    let mut exits = FuturesUnordered::<LocalBoxFuture<Notification>>::new();
     let mut notifications = AsyncFramedUnixStream::new(notifications);
     let command = AsyncFramedUnixStream::new(command_stream);
     let socket = FramedUnixStream::new(socket_stream);
@@ -58,10 +62,10 @@ pub async fn run(
                     Some(Message::CreateRequest { init, console, io, sockets }) => {
                         debug!("Creating init process for {}", init.container);
                         let container = init.container.clone();
-                        match create(init, io, console, sockets).await {
+                        match create(init.clone(), io, console, sockets).await {
                             Ok((pid, stream)) => {
                                 debug_assert!(!inits.contains_key(&container));
-                                inits.insert(container, (pid, stream));
+                                inits.insert(container, (pid, stream, init));
                                 let message = Message::CreateResult { result: Ok(pid) };
                                 channel.send(message).await;
                             }
@@ -72,16 +76,33 @@ pub async fn run(
                         }
                     }
                     Some(Message::ExecRequest { container, path, args, env }) => {
-                        let (pid, message_stream) = inits.remove(&container).unwrap_or_else(|| panic!("failed to find init process for {container}"));
+                        let (pid, message_stream, init) = inits.remove(&container).unwrap_or_else(|| panic!("failed to find init process for {container}"));
+                        started.insert(container.clone(), (pid, init));
+
                         // There's a init - let's exec!
                         let (response, exit) = exec(pid, message_stream, container, path, args, env).await;
 
                         // Add exit status future of this exec request
-                        exits.push(exit);
+                        let exit = exit.map(|(container, exit_status)| Notification::Exit { container, exit_status });
+                        exits.push(exit.boxed_local());
 
                         // Send the result of the exec request to the runtime
                         channel.send(response).await;
                     }
+                    Some(Message::SpawnRequest { spawn: request, io }) => {
+                        let result = match started.get(&request.container) {
+                            Some((pid, init)) => spawn(init.clone(), *pid, request, io).await,
+                            None => Err(anyhow!("failed to find init process for {}", request.container)),
+                        };
+                        let result = match result {
+                            Ok((pid, exit)) => {
+                                exits.push(exit.boxed_local());
+                                Ok(pid)
+                            }
+                            Err(e) => Err(e.to_string()),
+                        };
+                        channel.send(Message::SpawnResult { result }).await;
+                    }
                     Some(_) => unreachable!("Unexpected message"),
                     None => {
                         debug!("Channel closed. Exiting...");
@@ -90,9 +111,11 @@ pub async fn run(
                 }
             }
             exit = exits.next(), if !exits.is_empty() => {
-                let (container, exit_status) = exit.expect("invalid exit status");
-                debug!("Forwarding exit status notification of {}: {}", container, exit_status);
-                let notification = Notification::Exit { container, exit_status };
+                let notification = exit.expect("invalid exit status");
+                if let Notification::Exit { container, exit_status } = &notification {
+                    debug!("Forwarding exit status notification of {}: {}", container, exit_status);
+                    started.remove(container);
+                }
                 notifications.send(notification).await.expect("failed to send exit notification");
             }
         }
@@ -133,6 +156,20 @@ async fn create(
             sched::unshare(sched::CloneFlags::CLONE_NEWPID)
                 .expect("failed to create pid namespace");
 
+            // Create time namespace. The offsets apply to the children of the trampoline
+            // and must be set before init is forked.
+            if let Some(time_namespace) = &init.time_namespace {
+                unshare_time_namespace(time_namespace);
+            }
+
+            // Create the network namespace of bridged containers before init is forked.
+            // The runtime configures the interfaces of the namespace of init once the pid
+            // is known.
+            if init.network == Some(Network::Bridge) {
+                sched::unshare(sched::CloneFlags::CLONE_NEWNET)
+                    .expect("failed to create network namespace");
+            }
+
             // Fork the init process
             let init_pid = match unsafe { fork().expect("failed to fork") } {
                 ForkResult::Parent { child } => child.as_raw() as Pid,
@@ -181,6 +218,28 @@ async fn create(
     Ok((pid, stream))
 }
 
+/// Create a time namespace for the children of the calling process and set the offsets
+/// of the clocks
+fn unshare_time_namespace(time_namespace: &TimeNamespace) {
+    /// Does not exist in nix yet.
+    const CLONE_NEWTIME: c_int = 0x80;
+
+    debug!("Creating time namespace");
+    let result = unsafe { libc::unshare(CLONE_NEWTIME) };
+    Errno::result(result).expect("failed to create time namespace");
+
+    let monotonic = time_namespace.monotonic;
+    let boottime = time_namespace.boottime;
+    let offsets = format!(
+        "monotonic {} {}\nboottime {} {}\n",
+        monotonic.as_secs(),
+        monotonic.subsec_nanos(),
+        boottime.as_secs(),
+        boottime.subsec_nanos()
+    );
+    fs::write("/proc/self/timens_offsets", offsets).expect("failed to set clock offsets");
+}
+
 /// Send a exec request to a container
 async fn exec(
     init_pid: Pid,
@@ -251,3 +310,71 @@ async fn exec(
 
     (Message::ExecResult, exit_status)
 }
+
+/// Spawn an additional process in the namespaces of the init process `init_pid`
+async fn spawn(
+    init: Init,
+    init_pid: Pid,
+    spawn: Spawn,
+    io: [OwnedFd; 3],
+) -> Result<(Pid, impl Future<Output = Notification>)> {
+    let container = spawn.container.clone();
+    debug!(
+        "Spawning {} in container {}",
+        spawn.args.iter().map(ToString::to_string).join(" "),
+        container
+    );
+
+    let (stream_parent, stream_child) =
+        UnixStream::pair().context("failed to create socket pair")?;
+
+    let helper_pid = match unsafe { fork().context("failed to fork") }? {
+        ForkResult::Parent { child } => child,
+        ForkResult::Child => {
+            drop(stream_parent);
+            init.spawn(init_pid, spawn, io, FramedUnixStream::new(stream_child))
+        }
+    };
+
+    // Ensure to close the socket pair end of the child and the io of the process.
+    drop(stream_child);
+    drop(io);
+
+    // Wait until the helper tells us that it forked.
+    let mut stream = AsyncFramedUnixStream::new(stream_parent);
+    let pid = match stream.recv().await {
+        Ok(Some(init::Message::Forked { pid })) => pid,
+        _ => {
+            waitpid(helper_pid, None).ok();
+            return Err(anyhow!("failed to spawn process in {}", container));
+        }
+    };
+
+    // Construct a future that waits for the helper to signal a exit of it's child.
+    let exit = async move {
+        let exit_status = match stream.recv().await {
+            Ok(Some(init::Message::Exit {
+                pid: _,
+                exit_status,
+            })) => exit_status,
+            Ok(m) => {
+                warn!("Unexpected message from spawn helper {:?}", m);
+                ExitStatus::Exit(-1)
+            }
+            Err(e) => {
+                warn!("Unexpected error while waiting for exit status: {}", e);
+                ExitStatus::Exit(-2)
+            }
+        };
+
+        debug!("Reaping spawn helper of {} ({})", container, helper_pid);
+        waitpid(helper_pid, None).expect("failed to reap spawn helper");
+        Notification::SpawnExit {
+            container,
+            pid,
+            exit_status,
+        }
+    };
+
+    Ok((pid, exit))
+}
@@ -3,9 +3,14 @@ use crate::{
     common::container::Container,
     npk::{
         manifest,
-        manifest::{mount, Manifest},
+        manifest::{
+            mount,
+            mount::MountOptions,
+            rlimit::{RLimitResource, RLimitValue},
+            Manifest,
+        },
     },
-    runtime::{config::Config, error::Error, state::State},
+    runtime::{config::Config, core_dumps, error::Error, io, state::State, uts},
     seccomp,
 };
 use itertools::Itertools;
@@ -35,11 +40,29 @@ pub async fn build<'a, I: Iterator<Item = &'a Container> + Clone>(
     let gid = manifest.gid;
     let sched = manifest.sched.clone();
     let network = manifest.network.clone();
+    let user_namespace = manifest.user_namespace.clone();
+    let time_namespace = manifest.time_namespace.clone();
+    let hostname = uts::hostname(manifest);
+    let domainname = uts::domainname(manifest);
+    let cgroup_namespace = manifest
+        .cgroups
+        .as_ref()
+        .map(|cgroups| cgroups.namespace)
+        .unwrap_or(false);
     let groups = groups(manifest);
     let mounts = prepare_mounts(config, &root, manifest, containers).await?;
-    let rlimits = manifest.rlimits.clone();
+    let mut rlimits = manifest.rlimits.clone();
+    // The size of core dumps is limited by the runtime configuration
+    if let Some(core_dumps) = core_dumps_config(config, manifest) {
+        let limit = RLimitValue {
+            soft: Some(core_dumps.max_size),
+            hard: Some(core_dumps.max_size),
+        };
+        rlimits.insert(RLimitResource::CORE, limit);
+    }
     let seccomp = seccomp_filter(manifest);
     let uid = manifest.uid;
+    let tty = manifest.io.as_ref().map(|io| io.tty).unwrap_or(false);
     let sockets = manifest
         .sockets
         .keys()
@@ -61,12 +84,31 @@ pub async fn build<'a, I: Iterator<Item = &'a Container> + Clone>(
         mounts,
         groups,
         network,
+        user_namespace,
+        cgroup_namespace,
+        time_namespace,
+        hostname,
+        domainname,
         capabilities,
         rlimits,
         seccomp,
         console,
         sockets,
         selinux,
+        tty,
+    })
+}
+
+/// Core dump configuration if the runtime collects the core dumps of `manifest`
+fn core_dumps_config<'a>(
+    config: &'a Config,
+    manifest: &Manifest,
+) -> Option<&'a crate::runtime::config::CoreDumps> {
+    config.core_dumps.as_ref().filter(|_| {
+        manifest
+            .mounts
+            .values()
+            .any(|mount| matches!(mount, mount::Mount::CoreDumps))
     })
 }
 
@@ -113,7 +155,8 @@ async fn prepare_mounts<'a, I: Iterator<Item = &'a Container> + Clone>(
     let mut mounts = vec![];
     let manifest_mounts = &manifest.mounts;
 
-    for (target, mount) in manifest_mounts {
+    // Mount parents before nested mounts e.g. sysfs before the cgroup mount
+    for (target, mount) in manifest_mounts.iter().sorted_by(|(a, _), (b, _)| a.cmp(b)) {
         match mount {
             mount::Mount::Bind(manifest::mount::Bind { host, options }) => {
                 mounts.extend(bind(root, target.as_ref(), host.as_ref(), options));
@@ -121,6 +164,20 @@ async fn prepare_mounts<'a, I: Iterator<Item = &'a Container> + Clone>(
             mount::Mount::Persist => {
                 mounts.push(persist(config, manifest, root, target.as_ref()).await?);
             }
+            mount::Mount::CoreDumps => {
+                if let Some(configuration) = &config.core_dumps {
+                    let source = core_dumps::dir(configuration, &manifest.name);
+                    mounts.push(core_dumps(source, root, target.as_ref()));
+                }
+            }
+            mount::Mount::Cgroup => {
+                let delegate = manifest
+                    .cgroups
+                    .as_ref()
+                    .map(|cgroups| cgroups.delegate)
+                    .unwrap_or(false);
+                mounts.push(cgroup(root, target.as_ref(), delegate))
+            }
             mount::Mount::Proc => mounts.push(proc(root, target.as_ref())),
             mount::Mount::Sysfs => mounts.push(sysfs(root, target.as_ref())),
             mount::Mount::Sockets => {
@@ -149,7 +206,30 @@ async fn prepare_mounts<'a, I: Iterator<Item = &'a Container> + Clone>(
             mount::Mount::Tmpfs(mount::Tmpfs { size }) => {
                 mounts.push(tmpfs(root, target.as_ref(), *size))
             }
-            mount::Mount::Dev => {}
+            mount::Mount::Dev => {
+                if manifest.io.as_ref().map(|io| io.tty).unwrap_or(false) {
+                    let source = io::devpts_dir(&config.run_dir, &manifest.container());
+                    mounts.push(devpts(source, root, target.as_ref()));
+                }
+            }
+        }
+    }
+
+    // Generated /etc/hostname and /etc/hosts. The mountpoints are created when the npk is packed.
+    if manifest.hostname.is_some() {
+        let dir = uts::dir(&config.run_dir, &manifest.container());
+        for file in ["hostname", "hosts"] {
+            let target = Path::new("/etc").join(file);
+            if root.join_strip(&target).exists() {
+                let options = MountOptions::default();
+                mounts.extend(bind(root, &target, &dir.join(file), &options));
+            } else {
+                warn!(
+                    "Skipping {} of {} because the file does not exist in the container",
+                    target.display(),
+                    manifest.container()
+                );
+            }
         }
     }
 
@@ -168,6 +248,30 @@ fn proc(root: &Path, target: &Path) -> Mount {
     Mount::new(Some(source), target, FSTYPE, flags, None)
 }
 
+/// Cgroup2 filesystem mounted in the cgroup namespace of the container. The root of the
+/// filesystem is the cgroup of the container. Only a delegated cgroup is writable.
+fn cgroup(root: &Path, target: &Path, delegate: bool) -> Mount {
+    log::debug!(
+        "Adding cgroup on {} with options {}nosuid, noexec and nodev",
+        target.display(),
+        if delegate { "" } else { "ro, " }
+    );
+    let source = PathBuf::from("cgroup2");
+    let target = root.join_strip(target);
+    const FSTYPE: Option<&'static str> = Some("cgroup2");
+    let mut flags = MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC | MsFlags::MS_NODEV;
+    flags.set(MsFlags::MS_RDONLY, !delegate);
+    Mount::new(Some(source), target, FSTYPE, flags, None)
+}
+
+/// Private devpts instance of the runtime bound to `pts` in the dev mount `target`
+fn devpts(source: PathBuf, root: &Path, target: &Path) -> Mount {
+    let target = root.join_strip(target).join("pts");
+    log::debug!("Adding {} on {}", source.display(), target.display());
+    let flags = MsFlags::MS_BIND | MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC;
+    Mount::new(Some(source), target, None, flags, None)
+}
+
 fn sysfs(root: &Path, target: &Path) -> Mount {
     log::debug!(
         "Adding sysfs on {} with options ro, nosuid, noexec and nodev",
@@ -265,6 +369,17 @@ async fn persist(
     Ok(Mount::new(Some(source), target, None, flags, None))
 }
 
+fn core_dumps(source: PathBuf, root: &Path, target: &Path) -> Mount {
+    log::debug!(
+        "Adding {} on {} with options nodev, nosuid and noexec",
+        source.display(),
+        target.display(),
+    );
+    let target = root.join_strip(target);
+    let flags = MsFlags::MS_BIND | MsFlags::MS_NODEV | MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC;
+    Mount::new(Some(source), target, None, flags, None)
+}
+
 fn resource(
     root: &Path,
     target: &Path,
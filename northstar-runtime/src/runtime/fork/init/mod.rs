@@ -6,22 +6,25 @@ use crate::{
         rlimit::{RLimitResource, RLimitValue},
         sched::{Policy, Sched},
         selinux::Selinux,
+        time_namespace::TimeNamespace,
+        user_namespace::UserNamespace,
     },
     runtime::{
         exit_status::ExitStatus,
         fork::util::{self, set_child_subreaper, set_process_name},
         ipc::FramedUnixStream,
+        network,
         runtime::Pid,
     },
     seccomp::AllowList,
 };
 pub use builder::build;
 use itertools::Itertools;
-use log::{debug, info, warn};
+use log::{debug, info};
 use nix::{
     errno::Errno,
     fcntl::{self},
-    libc::{self, c_ulong},
+    libc::{self, c_ulong, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO},
     mount::{self},
     sched::{self, unshare, CloneFlags},
     sys::{
@@ -38,7 +41,7 @@ use std::{
     ffi::{c_int, CString},
     fs,
     io::{self, Write},
-    os::unix::prelude::{AsRawFd, OwnedFd},
+    os::unix::prelude::{AsRawFd, OwnedFd, RawFd},
     path::{Path, PathBuf},
     process::exit,
 };
@@ -60,6 +63,19 @@ pub enum Message {
     },
 }
 
+/// Additional process started in the namespaces of a container
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Spawn {
+    pub container: Container,
+    pub path: NonNulString,
+    pub args: Vec<NonNulString>,
+    pub env: Vec<NonNulString>,
+    /// `cgroup.procs` files of the container cgroups
+    pub cgroups: Vec<PathBuf>,
+    /// Stdin is a terminal that becomes the controlling terminal of the process
+    pub tty: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Init {
     pub container: Container,
@@ -70,12 +86,21 @@ pub struct Init {
     pub mounts: Vec<Mount>,
     pub groups: Vec<u32>,
     pub network: Option<Network>,
+    pub user_namespace: Option<UserNamespace>,
+    /// Create a cgroup namespace. Init is already member of the container cgroup.
+    pub cgroup_namespace: bool,
+    /// Clock offsets of the time namespace created by the forker before init is forked
+    pub time_namespace: Option<TimeNamespace>,
+    pub hostname: Option<String>,
+    pub domainname: Option<String>,
     pub capabilities: HashSet<Capability>,
     pub rlimits: HashMap<RLimitResource, RLimitValue>,
     pub seccomp: Option<AllowList>,
     pub console: bool,
     pub sockets: Vec<String>,
     pub selinux: Option<Selinux>,
+    /// Stdin is a terminal that becomes the controlling terminal of the container
+    pub tty: bool,
 }
 
 impl Init {
@@ -121,19 +146,43 @@ impl Init {
         debug!("Setting session id");
         unistd::setsid().expect("failed to call setsid");
 
+        // Acquire the terminal. The process of the container inherits the session and
+        // process group and is in the foreground of the terminal.
+        if self.tty {
+            let result = unsafe { libc::ioctl(STDIN_FILENO, libc::TIOCSCTTY, 0) };
+            Errno::result(result).expect("failed to set controlling terminal");
+        }
+
         // Enter network namespace
         self.network();
 
         // Enter mount namespace
         debug!("Entering mount, IPC and UTS namespace");
-        sched::unshare(
-            CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_NEWIPC | CloneFlags::CLONE_NEWUTS,
-        )
-        .expect("failed to unshare");
+        let mut flags =
+            CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_NEWIPC | CloneFlags::CLONE_NEWUTS;
+        if self.cgroup_namespace {
+            debug!("Entering cgroup namespace");
+            flags.insert(CloneFlags::CLONE_NEWCGROUP);
+        }
+        sched::unshare(flags).expect("failed to unshare");
+
+        // Set hostname and domainname in the UTS namespace
+        self.set_uts();
 
         // Perform all mounts passed in mounts
         self.mount();
 
+        // Open the proc directory of init before the proc of the host is unmounted with
+        // the old root. The id maps of the user namespace are written via this handle.
+        let proc = self.user_namespace.as_ref().map(|_| {
+            fcntl::open(
+                "/proc/self",
+                fcntl::OFlag::O_DIRECTORY | fcntl::OFlag::O_RDONLY | fcntl::OFlag::O_CLOEXEC,
+                Mode::empty(),
+            )
+            .expect("failed to open /proc/self")
+        });
+
         // Set the root to the containers root mount point
         self.pivot_rootfs(&self.root);
 
@@ -141,15 +190,24 @@ impl Init {
         debug!("Setting current working directory to root");
         env::set_current_dir("/").expect("failed to set cwd to /");
 
+        // Apply resource limits. Raising a hard limit is not permitted in a user namespace.
+        self.set_rlimits();
+
+        // The runtime is privileged if it runs as root on the host. Root of the user
+        // namespace has all capabilities regardless of the runtime.
+        let rt_privileged = unistd::geteuid() == Uid::from_raw(0);
+
+        // Enter user namespace
+        if let (Some(user_namespace), Some(proc)) = (&self.user_namespace, proc) {
+            Self::enter_user_namespace(user_namespace, proc);
+        }
+
         // UID / GID
-        self.set_ids();
+        self.set_ids(rt_privileged);
 
         // Supplementary groups
         self.set_groups();
 
-        // Apply resource limits
-        self.set_rlimits();
-
         // The init process got adopted by the forker after the trampoline exited. It is
         // safe to set the parent death signal now.
         util::set_parent_death_signal(Signal::SIGKILL);
@@ -225,25 +283,117 @@ impl Init {
             .expect("failed to send fork result");
 
         // Wait for the child to exit
-        let exit_status = loop {
-            debug!("Waiting for child process {} to exit", pid);
-            match waitpid(Some(unistd::Pid::from_raw(pid as i32)), None) {
-                Ok(WaitStatus::Exited(_, status)) => {
-                    debug!("Child process {} exited with status code {}", pid, status);
-                    break ExitStatus::Exit(status);
-                }
-                Ok(WaitStatus::Signaled(_, status, _)) => {
-                    debug!("Child process {} exited with signal {}", pid, status);
-                    break ExitStatus::Signalled(status as u8);
+        let exit_status = wait_for_exit(pid);
+
+        stream
+            .send(Message::Exit { pid, exit_status })
+            .expect("channel error");
+
+        exit(0);
+    }
+
+    /// Spawn an additional process in the namespaces of the init process `pid` with the
+    /// same credentials, limits and restrictions as the main process. This runs in a
+    /// child of the forker that reports the exit status of the process on `stream`.
+    pub fn spawn(
+        self,
+        pid: Pid,
+        spawn: Spawn,
+        io: [OwnedFd; 3],
+        mut stream: FramedUnixStream,
+    ) -> ! {
+        util::set_parent_death_signal(Signal::SIGKILL);
+        set_process_name(&format!("exec-{}", self.container));
+
+        // Join the cgroups of the container. The membership is inherited by the child.
+        for procs in &spawn.cgroups {
+            debug!("Joining cgroup {}", procs.display());
+            fs::write(procs, "0").expect("failed to join cgroup");
+        }
+
+        // SE context transition
+        if let Some(context) = self.selinux.as_ref().and_then(|s| s.exec.as_ref()) {
+            debug!("Setting SELinux context to {}", context);
+            fs::write("/proc/thread-self/attr/exec", context.as_bytes())
+                .expect("failed to write SELinux context");
+        }
+
+        // Apply resource limits. Raising a hard limit is not permitted in a user namespace.
+        self.set_rlimits();
+
+        // Determine the privileges of the runtime before the user namespace is joined
+        let rt_privileged = unistd::geteuid() == Uid::from_raw(0);
+
+        // Open all namespaces before the mount namespace is changed. The user namespace
+        // is entered last because the other namespaces are owned by the user namespace
+        // of the runtime.
+        debug!("Entering namespaces of {}", pid);
+        let cgroup = self.cgroup_namespace.then_some("cgroup");
+        let time = self.time_namespace.as_ref().map(|_| "time");
+        let user = self.user_namespace.as_ref().map(|_| "user");
+        let namespaces = ["pid", "net", "ipc", "uts", "mnt"]
+            .into_iter()
+            .chain(cgroup)
+            .chain(time)
+            .chain(user)
+            .map(|namespace| {
+                fs::File::open(format!("/proc/{pid}/ns/{namespace}"))
+                    .expect("failed to open namespace")
+            })
+            .collect::<Vec<_>>();
+        for namespace in namespaces {
+            sched::setns(namespace, CloneFlags::empty()).expect("failed to enter namespace");
+        }
+        env::set_current_dir("/").expect("failed to set cwd to /");
+
+        let path = CString::from(spawn.path);
+        let args: Vec<_> = spawn.args.into_iter().map_into::<CString>().collect();
+        let env: Vec<_> = spawn.env.into_iter().map_into::<CString>().collect();
+
+        // The pid namespace is entered by the child
+        let pid = match unsafe { fork().expect("failed to fork") } {
+            ForkResult::Parent { child } => child.as_raw() as Pid,
+            ForkResult::Child => {
+                util::set_parent_death_signal(Signal::SIGKILL);
+
+                // Apply io settings
+                unistd::dup2(io[0].as_raw_fd(), STDIN_FILENO).expect("failed to dup2");
+                unistd::dup2(io[1].as_raw_fd(), STDOUT_FILENO).expect("failed to dup2");
+                unistd::dup2(io[2].as_raw_fd(), STDERR_FILENO).expect("failed to dup2");
+                drop(io);
+
+                // Become a session leader and acquire the terminal if present
+                unistd::setsid().expect("failed to call setsid");
+                if spawn.tty {
+                    let result = unsafe { libc::ioctl(STDIN_FILENO, libc::TIOCSCTTY, 0) };
+                    Errno::result(result).expect("failed to set controlling terminal");
                 }
-                Ok(WaitStatus::Continued(_)) | Ok(WaitStatus::Stopped(_, _)) => {
-                    log::warn!("Child process continued or stopped");
-                    continue;
+
+                self.set_ids(rt_privileged);
+                self.set_groups();
+                self.set_scheduler_policy()
+                    .expect("failed to set scheduler policy");
+                if let Some(ref filter) = self.seccomp {
+                    filter.apply().expect("failed to apply seccomp filter.");
                 }
-                Err(nix::Error::EINTR) => continue,
-                e => panic!("failed to waitpid on {pid}: {e:?}"),
+                Self::set_no_new_privs(true);
+                self.drop_privileges();
+
+                panic!(
+                    "execve: {:?} {:?}: {:?}",
+                    &path,
+                    &args,
+                    unistd::execve(&path, &args, &env)
+                )
             }
         };
+        drop(io);
+
+        stream
+            .send(&Message::Forked { pid })
+            .expect("failed to send fork result");
+
+        let exit_status = wait_for_exit(pid);
 
         stream
             .send(Message::Exit { pid, exit_status })
@@ -252,13 +402,11 @@ impl Init {
         exit(0);
     }
 
-    /// Set uid/gid
-    fn set_ids(&self) {
+    /// Set uid/gid. `rt_privileged` is set if the runtime runs as root on the host.
+    fn set_ids(&self, rt_privileged: bool) {
         let uid = self.uid;
         let gid = self.gid;
 
-        let rt_privileged = unistd::geteuid() == Uid::from_raw(0);
-
         // If running as uid 0 save our caps across the uid/gid drop
         if rt_privileged {
             caps::securebits::set_keepcaps(true).expect("failed to set keep caps");
@@ -278,6 +426,67 @@ impl Init {
         }
     }
 
+    /// Create a new user namespace. A process cannot map arbitrary ids in its own user
+    /// namespace. The id maps are written by a child that stays in the user namespace of
+    /// the runtime via the proc directory `proc` of this process.
+    fn enter_user_namespace(user_namespace: &UserNamespace, proc: RawFd) {
+        debug!("Entering user namespace");
+        let (read, write) = unistd::pipe().expect("failed to create pipe");
+
+        let child = match unsafe { fork().expect("failed to fork") } {
+            ForkResult::Parent { child } => child,
+            ForkResult::Child => {
+                unistd::close(write).expect("failed to close pipe");
+
+                // Wait until the parent closes the pipe after the unshare
+                let mut buffer = [0u8; 1];
+                unistd::read(read, &mut buffer).expect("failed to read pipe");
+
+                for (file, ranges) in [
+                    ("uid_map", &user_namespace.uid_map),
+                    ("gid_map", &user_namespace.gid_map),
+                ] {
+                    let map = ranges
+                        .iter()
+                        .map(|range| format!("{} {} {}\n", range.container, range.host, range.size))
+                        .collect::<String>();
+                    debug!("Writing {}: {}", file, map.trim_end());
+                    let fd = fcntl::openat(proc, file, fcntl::OFlag::O_WRONLY, Mode::empty())
+                        .expect("failed to open id map");
+                    // The map must be written with a single write
+                    unistd::write(fd, map.as_bytes()).expect("failed to write id map");
+                    unistd::close(fd).expect("failed to close id map");
+                }
+                exit(0);
+            }
+        };
+
+        unistd::close(read).expect("failed to close pipe");
+        unshare(CloneFlags::CLONE_NEWUSER).expect("failed to unshare user namespace");
+        unistd::close(write).expect("failed to close pipe");
+
+        match waitpid(child, None) {
+            Ok(WaitStatus::Exited(_, 0)) => (),
+            result => panic!("failed to map ids of user namespace: {result:?}"),
+        }
+        unistd::close(proc).expect("failed to close /proc/self");
+    }
+
+    /// Set hostname and domainname
+    fn set_uts(&self) {
+        if let Some(hostname) = &self.hostname {
+            debug!("Setting hostname to {}", hostname);
+            let result = unsafe { libc::sethostname(hostname.as_ptr().cast(), hostname.len()) };
+            Errno::result(result).expect("failed to set hostname");
+        }
+        if let Some(domainname) = &self.domainname {
+            debug!("Setting domainname to {}", domainname);
+            let result =
+                unsafe { libc::setdomainname(domainname.as_ptr().cast(), domainname.len()) };
+            Errno::result(result).expect("failed to set domainname");
+        }
+    }
+
     fn set_groups(&self) {
         debug!("Setting groups {:?}", self.groups);
         let result = unsafe { nix::libc::setgroups(self.groups.len(), self.groups.as_ptr()) };
@@ -373,27 +582,25 @@ impl Init {
             Some(Network::Host) => {
                 debug!("Using host network");
             }
+            Some(Network::Bridge) => {
+                // The network namespace is created by the trampoline
+                debug!("Using bridge network");
+            }
             Some(Network::Namespace(namespace)) => {
-                #[cfg(target_os = "android")]
-                let path = Path::new("/run/netns").join(namespace);
-                #[cfg(not(target_os = "android"))]
-                let path = Path::new("/var/run/netns").join(namespace);
-
-                if path.exists() {
-                    let handle = fs::OpenOptions::new()
-                        .read(true)
-                        .write(false)
-                        .open(&path)
-                        .expect("failed to open netns");
-                    debug!("Trying to attach to network namespace \"{}\"", namespace);
-                    sched::setns(handle, CloneFlags::CLONE_NEWNET).expect("failed to enter netns");
-                } else {
-                    warn!("Failed to attach to network namespace \"{}\"", namespace);
-                }
+                // The runtime checks that the namespace exists before the container is created
+                let path = network::namespace(namespace);
+                let handle = fs::OpenOptions::new()
+                    .read(true)
+                    .write(false)
+                    .open(path)
+                    .expect("failed to open netns");
+                debug!("Attaching to network namespace \"{}\"", namespace);
+                sched::setns(handle, CloneFlags::CLONE_NEWNET).expect("failed to enter netns");
             }
             None => {
                 debug!("Unsharing network namespace");
                 unshare(CloneFlags::CLONE_NEWNET).expect("failed to unshare");
+                network::loopback().expect("failed to set up loopback");
             }
         }
     }
@@ -514,6 +721,29 @@ impl Init {
     }
 }
 
+/// Wait for the child process `pid` to exit
+fn wait_for_exit(pid: Pid) -> ExitStatus {
+    loop {
+        debug!("Waiting for child process {} to exit", pid);
+        match waitpid(Some(unistd::Pid::from_raw(pid as i32)), None) {
+            Ok(WaitStatus::Exited(_, status)) => {
+                debug!("Child process {} exited with status code {}", pid, status);
+                break ExitStatus::Exit(status);
+            }
+            Ok(WaitStatus::Signaled(_, status, _)) => {
+                debug!("Child process {} exited with signal {}", pid, status);
+                break ExitStatus::Signalled(status as u8);
+            }
+            Ok(WaitStatus::Continued(_)) | Ok(WaitStatus::Stopped(_, _)) => {
+                log::warn!("Child process continued or stopped");
+                continue;
+            }
+            Err(nix::Error::EINTR) => continue,
+            e => panic!("failed to waitpid on {pid}: {e:?}"),
+        }
+    }
+}
+
 impl From<Capability> for caps::Capability {
     fn from(cap: Capability) -> Self {
         match cap {
@@ -0,0 +1,245 @@
+use crate::{
+    common::{container::Container, non_nul_string::NonNulString},
+    npk::manifest::{
+        health::{Health as HealthCheck, Probe},
+        socket::Type,
+        Manifest,
+    },
+    runtime::{
+        events::{ContainerEvent, Event, EventTx, Health},
+        sockets,
+    },
+};
+use anyhow::{bail, Context, Result};
+use log::debug;
+use nix::{
+    sys::{
+        signal,
+        socket::{self, sockopt, AddressFamily, MsgFlags, SockFlag, SockType, UnixAddr},
+        time::TimeVal,
+    },
+    unistd,
+};
+use std::{
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    select,
+    sync::{oneshot, Notify},
+    task, time,
+};
+use tokio_util::sync::CancellationToken;
+
+/// Health monitor of a started container.
+#[derive(Debug)]
+pub(crate) struct Monitor {
+    /// Last reported health. None until the first probe result.
+    pub health: Option<Health>,
+    /// Heartbeats received via the console
+    heartbeat: Arc<Notify>,
+}
+
+impl Monitor {
+    /// Spawn a task that probes the health of `container` if configured in the manifest.
+    /// The task sends an event upon each health change and stops when `stop` is cancelled.
+    pub(crate) fn start(
+        container: &Container,
+        manifest: &Manifest,
+        socket_dir: &Path,
+        events_tx: EventTx,
+        stop: CancellationToken,
+    ) -> Option<Monitor> {
+        let check = manifest.health.clone()?;
+        let heartbeat = Arc::new(Notify::new());
+
+        let probe = Prober {
+            container: container.clone(),
+            events_tx: events_tx.clone(),
+            socket_dir: socket_dir.to_owned(),
+            socket_type: match &check.probe {
+                Probe::Socket { name } => manifest.sockets.get(name).map(|s| s.r#type.clone()),
+                _ => None,
+            },
+            heartbeat: heartbeat.clone(),
+        };
+        task::spawn(monitor(probe, check, events_tx, stop));
+
+        Some(Monitor {
+            health: None,
+            heartbeat,
+        })
+    }
+
+    /// Record a heartbeat of the container.
+    pub(crate) fn heartbeat(&self) {
+        self.heartbeat.notify_one();
+    }
+}
+
+struct Prober {
+    container: Container,
+    events_tx: EventTx,
+    socket_dir: PathBuf,
+    socket_type: Option<Type>,
+    heartbeat: Arc<Notify>,
+}
+
+impl Prober {
+    /// Run a single probe.
+    async fn probe(&self, check: &HealthCheck) -> Result<()> {
+        match &check.probe {
+            Probe::Exec { path, args } => {
+                time::sleep(check.interval).await;
+                self.exec(path, args, check.timeout).await
+            }
+            Probe::Socket { name } => {
+                time::sleep(check.interval).await;
+                let path = sockets::path(&self.socket_dir, &self.container, name);
+                let r#type = self.socket_type.clone().context("unknown socket")?;
+                let timeout = check.timeout;
+                task::spawn_blocking(move || connect(&path, &r#type, timeout))
+                    .await
+                    .context("failed to join probe")?
+            }
+            Probe::Heartbeat => time::timeout(check.interval, self.heartbeat.notified())
+                .await
+                .context("missing heartbeat"),
+        }
+    }
+
+    /// Execute `path` in the container like an exec request. The probe runs in the
+    /// namespaces and cgroups of the container and is killed after `timeout`.
+    async fn exec(
+        &self,
+        path: &NonNulString,
+        args: &[NonNulString],
+        timeout: time::Duration,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        let event = Event::Probe(self.container.clone(), path.clone(), args.to_vec(), tx);
+        self.events_tx
+            .send(event)
+            .await
+            .context("failed to send probe request")?;
+        let (pid, exit_status) = rx.await.context("failed to spawn probe")?;
+
+        match time::timeout(timeout, exit_status).await {
+            Ok(Ok(exit_status)) if exit_status.success() => Ok(()),
+            Ok(Ok(exit_status)) => bail!("probe exited with {exit_status}"),
+            Ok(Err(_)) => bail!("exit status of the probe is unknown"),
+            Err(_) => {
+                // The probe is the leader of its process group
+                let process_group = unistd::Pid::from_raw(-(pid as i32));
+                signal::kill(process_group, Some(signal::Signal::SIGKILL)).ok();
+                bail!("timeout")
+            }
+        }
+    }
+}
+
+/// Connect to the socket at `path`. The connection must be accepted and written to or
+/// closed by the container within `timeout`. A refused connection is a failure.
+fn connect(path: &Path, r#type: &Type, timeout: time::Duration) -> Result<()> {
+    let r#type = match r#type {
+        Type::Stream => SockType::Stream,
+        Type::SeqPacket => SockType::SeqPacket,
+        Type::Datagram => bail!("datagram sockets cannot be probed"),
+    };
+    let socket = socket::socket(AddressFamily::Unix, r#type, SockFlag::SOCK_CLOEXEC, None)
+        .context("failed to create socket")?;
+    // Connecting blocks if the backlog is full and reading blocks until the container
+    // accepted and handled the connection
+    let timeout = TimeVal::from(libc::timeval {
+        tv_sec: timeout.as_secs() as libc::time_t,
+        tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+    });
+    socket::setsockopt(&socket, sockopt::SendTimeout, &timeout)
+        .and_then(|_| socket::setsockopt(&socket, sockopt::ReceiveTimeout, &timeout))
+        .context("failed to set socket timeout")?;
+
+    let addr = UnixAddr::new(path).context("invalid unix path")?;
+    socket::connect(socket.as_raw_fd(), &addr)
+        .with_context(|| format!("failed to connect to {}", path.display()))?;
+    let mut buffer = [0u8; 1];
+    match socket::recv(socket.as_raw_fd(), &mut buffer, MsgFlags::empty()) {
+        Ok(_) => Ok(()),
+        Err(nix::errno::Errno::EAGAIN) => bail!("timeout"),
+        Err(e) => Err(e).with_context(|| format!("failed to read from {}", path.display())),
+    }
+}
+
+/// Probe until `stop` is cancelled and report health changes.
+async fn monitor(prober: Prober, check: HealthCheck, events_tx: EventTx, stop: CancellationToken) {
+    let container = &prober.container;
+    let mut failures = 0u32;
+    let mut health = None;
+
+    loop {
+        let result = select! {
+            _ = stop.cancelled() => break,
+            result = prober.probe(&check) => result,
+        };
+
+        let current = match result {
+            Ok(()) => {
+                failures = 0;
+                Health::Healthy
+            }
+            Err(e) => {
+                failures = failures.saturating_add(1);
+                debug!(
+                    "Health probe of {} failed ({}/{}): {:#}",
+                    container, failures, check.failure_threshold, e
+                );
+                if failures < check.failure_threshold {
+                    continue;
+                }
+                Health::Unhealthy
+            }
+        };
+
+        if health != Some(current) {
+            health = Some(current);
+            let event = Event::Container(container.clone(), ContainerEvent::Health(current));
+            if events_tx.send(event).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::connect;
+    use crate::npk::manifest::socket::Type;
+    use std::{io::Write, os::unix::net::UnixListener, thread, time::Duration};
+
+    #[test]
+    fn socket_probe() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let path = dir.path().join("socket");
+        let timeout = Duration::from_millis(100);
+
+        // No listener
+        assert!(connect(&path, &Type::Stream, timeout).is_err());
+
+        // The connection is accepted and answered
+        let listener = UnixListener::bind(&path).expect("failed to bind");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("failed to accept");
+            stream.write_all(b"ok").expect("failed to write");
+            listener
+        });
+        assert!(connect(&path, &Type::Stream, timeout).is_ok());
+        let listener = server.join().expect("failed to join");
+
+        // The connection is not accepted
+        assert!(connect(&path, &Type::Stream, timeout).is_err());
+
+        // The listener is closed
+        drop(listener);
+        assert!(connect(&path, &Type::Stream, timeout).is_err());
+    }
+}
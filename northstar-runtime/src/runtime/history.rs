@@ -0,0 +1,139 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use crate::api::model::{self, Container};
+use anyhow::{Context, Result};
+use log::warn;
+use tokio::fs;
+
+/// File name of the history in the data directory. Container names cannot contain
+/// a `@` and therefore the file does not clash with a persist directory.
+const FILE: &str = "@history.json";
+
+/// Bounded history of the terminated runs per container. The history is optionally
+/// written to the data directory and restored when the runtime starts.
+#[derive(Debug, Default)]
+pub(super) struct History {
+    path: Option<PathBuf>,
+    runs: HashMap<Container, VecDeque<model::Run>>,
+}
+
+impl History {
+    /// Load the history from `data_dir`. A missing or unreadable history results in an
+    /// empty history.
+    pub async fn load(data_dir: &Path) -> History {
+        let path = data_dir.join(FILE);
+        let runs = match fs::read(&path).await {
+            Ok(content) => serde_json::from_slice(&content).unwrap_or_else(|e| {
+                warn!("Discarding invalid history {}: {}", path.display(), e);
+                HashMap::default()
+            }),
+            Err(_) => HashMap::default(),
+        };
+        History {
+            path: Some(path),
+            runs,
+        }
+    }
+
+    /// Runs of `container` starting with the oldest run
+    pub fn runs(&self, container: &Container) -> Vec<model::Run> {
+        self.runs
+            .get(container)
+            .map(|runs| runs.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Record a terminated run of `container` and drop the oldest runs that exceed `limit`
+    pub async fn record(&mut self, container: &Container, run: model::Run, limit: usize) {
+        let runs = self.runs.entry(container.clone()).or_default();
+        runs.push_back(run);
+        while runs.len() > limit {
+            runs.pop_front();
+        }
+        self.save().await;
+    }
+
+    /// Remove the runs of `container`
+    pub async fn remove(&mut self, container: &Container) {
+        if self.runs.remove(container).is_some() {
+            self.save().await;
+        }
+    }
+
+    /// Write the history if it is persisted. Failures are logged because the runtime
+    /// continues without a persisted history.
+    async fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = self.write(path).await {
+                warn!("Failed to write history {}: {:#}", path.display(), e);
+            }
+        }
+    }
+
+    /// Write the history to a temporary file and replace the history atomically
+    async fn write(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_vec(&self.runs).context("failed to serialize")?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)
+            .await
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path)
+            .await
+            .with_context(|| format!("failed to rename {}", tmp.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(started: u64) -> model::Run {
+        model::Run {
+            started,
+            duration: 10,
+            exit_status: model::ExitStatus::Exit { code: 0 },
+            reason: model::ExitReason::Exited,
+            oom_kills: 0,
+            arguments: model::JournalStart {
+                init: None,
+                arguments: Vec::new(),
+                environment: Default::default(),
+                max_runtime: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn bounded() {
+        let container = Container::try_from("test:0.0.1").unwrap();
+        let mut history = History::default();
+        for started in 0..5 {
+            history.record(&container, run(started), 3).await;
+        }
+        let runs = history.runs(&container);
+        assert_eq!(
+            runs.iter().map(|run| run.started).collect::<Vec<_>>(),
+            [2, 3, 4]
+        );
+
+        history.remove(&container).await;
+        assert!(history.runs(&container).is_empty());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let container = Container::try_from("test:0.0.1").unwrap();
+
+        let mut history = History::load(dir.path()).await;
+        history.record(&container, run(1), 10).await;
+
+        let loaded = History::load(dir.path()).await;
+        assert_eq!(loaded.runs(&container), [run(1)]);
+    }
+}
@@ -0,0 +1,269 @@
+use super::{
+    env,
+    events::{Event, EventTx},
+    exit_status::ExitStatus,
+};
+use crate::{
+    api::model,
+    common::{container::Container, name::Name, non_nul_string::NonNulString, version::VersionReq},
+    npk::manifest::hooks::{Hook, Stage},
+};
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use log::{debug, warn};
+use std::{process::Stdio, time::Duration};
+use tokio::{process::Command, sync::oneshot, task, time};
+
+/// Hooks of one stage of a list of containers
+#[derive(Debug)]
+pub(crate) struct Run {
+    /// Stage of the hooks
+    pub stage: Stage,
+    /// Containers and their hooks. The containers are processed one after another.
+    pub hooks: Vec<(Container, Vec<Hook>)>,
+    /// Environment passed to all hooks
+    pub env: Vec<(&'static str, String)>,
+    /// Action that continues once all hooks ran
+    pub action: Action,
+}
+
+/// Action that continues once the hooks of a run finished
+#[derive(Debug)]
+pub(crate) enum Action {
+    /// Mount the containers whose hooks passed. The list contains the containers of the
+    /// request in order, including containers without hooks. The response is sent to the
+    /// console if set.
+    Mount(Vec<Container>, Option<oneshot::Sender<model::Response>>),
+    /// Continue the pending start of the container
+    Start(Container),
+    /// Uninstall the container and wipe its data if set. The response is sent to the
+    /// console if set.
+    Uninstall(Container, bool, Option<oneshot::Sender<model::Response>>),
+    /// Nothing to continue. Failures are logged.
+    None,
+}
+
+/// Request to start a hook container. The container and a receiver for its exit status
+/// are sent back.
+#[derive(Debug)]
+pub(crate) struct ContainerRequest {
+    /// Name of the hook container
+    pub name: Name,
+    /// Version requirement of the hook container
+    pub version: VersionReq,
+    /// Arguments passed to the hook container
+    pub args: Vec<NonNulString>,
+    /// Hook environment
+    pub env: Vec<(&'static str, String)>,
+    pub reply: oneshot::Sender<Result<(Container, oneshot::Receiver<ExitStatus>), String>>,
+}
+
+/// Results of the hooks of each container of a run
+pub(crate) type Results = Vec<(Container, Result<(), String>)>;
+
+/// Run the hooks of `run` in a task and send the results with the action in an
+/// `Event::Hooks`. The hooks of a container run one after another. A failing hook
+/// of a veto stage skips the remaining hooks of the container.
+pub(super) fn spawn(run: Run, events_tx: EventTx) {
+    task::spawn(async move {
+        let Run {
+            stage,
+            hooks,
+            env,
+            action,
+        } = run;
+        let mut results = Vec::with_capacity(hooks.len());
+        for (container, hooks) in hooks {
+            let mut result = Ok(());
+            for hook in hooks {
+                debug!("Running {} hook of {}: {:?}", stage, container, hook);
+                let hook_result = match hook {
+                    Hook::Command {
+                        command: line,
+                        timeout,
+                    } => {
+                        let mut env = env.clone();
+                        env.push((env::CONTAINER, container.to_string()));
+                        command(&line, timeout, &env)
+                            .await
+                            .map_err(|e| format!("{e:#}"))
+                    }
+                    Hook::Container {
+                        container: name,
+                        version,
+                        args,
+                        timeout,
+                    } => {
+                        let mut env = env.clone();
+                        env.push((env::HOOK_CONTAINER, container.to_string()));
+                        let (reply, rx) = oneshot::channel();
+                        let request = ContainerRequest {
+                            name,
+                            version,
+                            args,
+                            env,
+                            reply,
+                        };
+                        hook_container(request, rx, timeout, &events_tx).await
+                    }
+                };
+                if let Err(e) = hook_result {
+                    warn!("Hook {} of {} failed: {}", stage, container, e);
+                    result = Err(e);
+                    if stage.is_veto() {
+                        break;
+                    }
+                }
+            }
+            results.push((container, result));
+        }
+        // The event loop is gone if the runtime shuts down.
+        events_tx
+            .send(Event::Hooks(stage, results, action))
+            .await
+            .ok();
+    });
+}
+
+/// Start a hook container with `request` and wait for its exit. The hook container is
+/// killed if it did not exit within `timeout`. The hook fails unless the hook container
+/// exits with 0.
+async fn hook_container(
+    request: ContainerRequest,
+    rx: oneshot::Receiver<Result<(Container, oneshot::Receiver<ExitStatus>), String>>,
+    timeout: Duration,
+    events_tx: &EventTx,
+) -> Result<(), String> {
+    events_tx
+        .send(Event::HookContainer(request))
+        .await
+        .map_err(|_| "failed to send hook container request".to_string())?;
+    let (container, mut exit_status) = rx
+        .await
+        .map_err(|_| "failed to start hook container".to_string())??;
+
+    match time::timeout(timeout, &mut exit_status).await {
+        Ok(Ok(exit_status)) if exit_status.success() => Ok(()),
+        Ok(Ok(exit_status)) => Err(format!("{container} exited with {exit_status}")),
+        Ok(Err(_)) => Err(format!("{container} failed to start")),
+        Err(_) => {
+            events_tx
+                .send(Event::HookTimeout(container.clone()))
+                .await
+                .ok();
+            // Wait for the hook container to be gone before the next hook runs
+            exit_status.await.ok();
+            Err(format!("{container} timed out after {timeout:?}"))
+        }
+    }
+}
+
+/// Execute the host command of a hook with the additional environment `env`. The
+/// command is killed if it did not exit within `timeout`.
+pub(super) async fn command(
+    command: &[NonNulString],
+    timeout: Duration,
+    env: &[(&str, String)],
+) -> Result<()> {
+    let (program, args) = command.split_first().context("empty command")?;
+    debug!("Executing hook command {}", command.iter().join(" "));
+
+    let mut child = Command::new(program.as_str())
+        .args(args.iter().map(|arg| arg.as_str()))
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to spawn {}", program.as_str()))?;
+
+    match time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => bail!("{} exited with {}", program.as_str(), status),
+        Ok(Err(e)) => Err(e).with_context(|| format!("failed to wait for {}", program.as_str())),
+        Err(_) => {
+            child.kill().await.ok();
+            bail!("{} timed out after {:?}", program.as_str(), timeout)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn command_line(command: &str) -> Vec<NonNulString> {
+        ["/bin/sh", "-c", command]
+            .iter()
+            .map(|arg| NonNulString::try_from(*arg).expect("invalid argument"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn exit_status_and_env() {
+        let env = [("NORTHSTAR_CONTAINER", "test:0.0.1".to_string())];
+        let check = command_line("test \"$NORTHSTAR_CONTAINER\" = test:0.0.1");
+        assert!(command(&check, Duration::from_secs(5), &env).await.is_ok());
+        let check = command_line("test \"$NORTHSTAR_CONTAINER\" = foo:0.0.1");
+        assert!(command(&check, Duration::from_secs(5), &env).await.is_err());
+        assert!(command(&[], Duration::from_secs(5), &env).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let sleep = command_line("sleep 10");
+        let start = time::Instant::now();
+        assert!(command(&sleep, Duration::from_millis(100), &[])
+            .await
+            .is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    async fn run(stage: Stage, hooks: Vec<(Container, Vec<Hook>)>) -> Results {
+        let (events_tx, mut events_rx) = tokio::sync::mpsc::channel(1);
+        let run = Run {
+            stage,
+            hooks,
+            env: Vec::new(),
+            action: Action::None,
+        };
+        spawn(run, events_tx);
+        match events_rx.recv().await {
+            Some(Event::Hooks(s, results, Action::None)) if s == stage => results,
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    fn hook(command: &str) -> Hook {
+        Hook::Command {
+            command: command_line(command),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn veto_stage_skips_remaining_hooks() {
+        let a = Container::try_from("a:0.0.1").expect("invalid container");
+        let b = Container::try_from("b:0.0.1").expect("invalid container");
+        let hooks = vec![
+            (a.clone(), vec![hook("exit 1"), hook("sleep 10")]),
+            (b.clone(), vec![hook("true")]),
+        ];
+        let start = time::Instant::now();
+        let results = run(Stage::PreStart, hooks).await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(results[0].0, a);
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1], (b, Ok(())));
+    }
+
+    #[tokio::test]
+    async fn stage_runs_all_hooks() {
+        let a = Container::try_from("a:0.0.1").expect("invalid container");
+        let hooks = vec![(a, vec![hook("exit 1"), hook("exit 2")])];
+        let results = run(Stage::PostStop, hooks).await;
+        let error = results[0].1.as_ref().expect_err("hooks passed");
+        assert!(error.contains('2'));
+    }
+}
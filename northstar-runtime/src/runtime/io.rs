@@ -1,29 +1,246 @@
-use std::os::unix::{
-    net::UnixStream as StdUnixStream,
-    prelude::{FromRawFd, OwnedFd},
+use std::{
+    fmt, fs,
+    os::unix::{
+        net::UnixStream as StdUnixStream,
+        prelude::{AsRawFd, FromRawFd, OwnedFd},
+    },
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
 };
 
 use crate::{
+    api::model::LogStream,
     common::container::Container,
     npk::manifest::{self, io::Output},
+    runtime::logs::Capture,
+};
+use anyhow::Context as _;
+use log::{debug, warn};
+use nix::{
+    errno::Errno,
+    fcntl::OFlag,
+    libc,
+    mount::{self, MntFlags, MsFlags},
+    sys::stat::Mode,
+    unistd,
 };
-use log::debug;
-use nix::{fcntl::OFlag, sys::stat::Mode};
 use tokio::{
-    io::{self, copy_buf, AsyncBufReadExt, AsyncWrite},
+    io::{
+        self, copy_buf, unix::AsyncFd, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite,
+        AsyncWriteExt, ReadBuf,
+    },
     net::UnixStream,
+    sync::{broadcast, mpsc},
     task::{self},
 };
 
 /// Buffer size for stdout/stderr forwarding
 const BUFFER_SIZE: usize = 16 * 4048;
 
+/// Number of terminal output chunks buffered for attached clients
+const ATTACH_BUFFER: usize = 256;
+
+/// Private devpts instance of a container with a terminal. The instance is mounted by the
+/// runtime and bound to `pts` of the dev mount of the container. The terminal of the
+/// container and the terminals of exec requests are allocated from this instance.
+/// The instance is unmounted on drop.
+#[derive(Debug)]
+pub(crate) struct Devpts {
+    dir: PathBuf,
+}
+
+impl Drop for Devpts {
+    fn drop(&mut self) {
+        debug!("Unmounting {}", self.dir.display());
+        if let Err(e) = mount::umount2(&self.dir, MntFlags::MNT_DETACH) {
+            warn!("Failed to unmount {}: {}", self.dir.display(), e);
+        }
+        if let Err(e) = fs::remove_dir(&self.dir) {
+            warn!("Failed to remove {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+impl Devpts {
+    /// Allocate a pseudo terminal and return the master and the slave
+    fn openpty(&self) -> io::Result<(Master, OwnedFd)> {
+        let flags = OFlag::O_RDWR | OFlag::O_NOCTTY | OFlag::O_CLOEXEC;
+        let ptmx = self.dir.join("ptmx");
+        let master = nix::fcntl::open(&ptmx, flags | OFlag::O_NONBLOCK, Mode::empty())
+            .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })?;
+
+        let unlock: libc::c_int = 0;
+        Errno::result(unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSPTLCK, &unlock) })?;
+        let mut number: libc::c_uint = 0;
+        Errno::result(unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCGPTN, &mut number) })?;
+
+        let slave = self.dir.join(number.to_string());
+        let slave = nix::fcntl::open(&slave, flags, Mode::empty())
+            .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })?;
+        Ok((Master(Arc::new(AsyncFd::new(master)?)), slave))
+    }
+}
+
+/// Directory of the devpts instance of `container`
+pub(super) fn devpts_dir(run_dir: &Path, container: &Container) -> PathBuf {
+    run_dir.join(format!("{container}.pts"))
+}
+
+/// Mount a new devpts instance for `container`. The slaves are owned by `owner`.
+pub(super) fn devpts(
+    run_dir: &Path,
+    container: &Container,
+    owner: (unistd::Uid, unistd::Gid),
+) -> anyhow::Result<Devpts> {
+    let dir = devpts_dir(run_dir, container);
+    debug!("Mounting devpts on {}", dir.display());
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let (uid, gid) = owner;
+    let data = format!("newinstance,ptmxmode=0666,mode=0620,uid={uid},gid={gid}");
+    match mount::mount(
+        Some("devpts"),
+        &dir,
+        Some("devpts"),
+        MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC,
+        Some(data.as_str()),
+    ) {
+        Ok(()) => Ok(Devpts { dir }),
+        Err(e) => {
+            fs::remove_dir(&dir).ok();
+            Err(e).with_context(|| format!("failed to mount devpts on {}", dir.display()))
+        }
+    }
+}
+
+/// Non blocking master of a pseudo terminal
+#[derive(Clone, Debug)]
+struct Master(Arc<AsyncFd<OwnedFd>>);
+
+impl AsyncRead for Master {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = ready!(self.0.poll_read_ready(cx))?;
+            let unfilled = buf.initialize_unfilled();
+            match guard.try_io(|fd| Ok(unistd::read(fd.as_raw_fd(), unfilled)?)) {
+                Ok(Ok(n)) => {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(Err(e)) => return Poll::Ready(Err(e)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncWrite for Master {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.0.poll_write_ready(cx))?;
+            match guard.try_io(|fd| Ok(unistd::write(fd.as_raw_fd(), buf)?)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
 pub struct ContainerIo {
     pub io: [OwnedFd; 3],
+    /// Pseudo terminal if configured in the manifest
+    pub terminal: Option<Terminal>,
+}
+
+/// Pseudo terminal of a container. The output is forwarded according to the stdout
+/// configuration and sent to the attached clients.
+#[derive(Clone, Debug)]
+pub struct Terminal {
+    master: Master,
+    input: mpsc::Sender<Vec<u8>>,
+    output: broadcast::Sender<Vec<u8>>,
+}
+
+impl Terminal {
+    /// Attach a client to the terminal
+    pub fn attach(&self) -> Attachment {
+        Attachment {
+            master: self.master.clone(),
+            input: self.input.clone(),
+            output: self.output.subscribe(),
+        }
+    }
+}
+
+/// Client attached to the terminal of a container. The output is closed once the
+/// container exited.
+#[derive(Debug)]
+pub struct Attachment {
+    master: Master,
+    /// Input of the terminal
+    pub input: mpsc::Sender<Vec<u8>>,
+    /// Output of the terminal
+    pub output: broadcast::Receiver<Vec<u8>>,
+}
+
+impl Attachment {
+    /// Set the window size of the terminal
+    pub fn resize(&self, rows: u16, cols: u16) -> io::Result<()> {
+        let size = libc::winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let result = unsafe { libc::ioctl(self.master.0.as_raw_fd(), libc::TIOCSWINSZ, &size) };
+        Errno::result(result)
+            .map(drop)
+            .map_err(|err| io::Error::from_raw_os_error(err as i32))
+    }
+}
+
+/// Runtime end of the io of a process spawned with an exec request
+pub struct ExecIo {
+    /// Writer to the standard input of the process
+    pub stdin: Box<dyn AsyncWrite + Send + Unpin>,
+    /// Reader of the standard output of the process
+    pub stdout: Box<dyn AsyncRead + Send + Unpin>,
+    /// Reader of the standard error of the process. Empty if a terminal is used.
+    pub stderr: Box<dyn AsyncRead + Send + Unpin>,
+}
+
+impl fmt::Debug for ExecIo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecIo").finish_non_exhaustive()
+    }
 }
 
-/// Create a new pty handle if configured in the manifest or open /dev/null instead.
-pub async fn open(container: &Container, io: &manifest::io::Io) -> io::Result<ContainerIo> {
+/// Allocate a pseudo terminal from `devpts` if configured in the manifest or open /dev/null
+/// instead. Fails if a terminal is configured without a devpts instance. Piped output is
+/// recorded in `capture`.
+pub async fn open(
+    container: &Container,
+    io: &manifest::io::Io,
+    devpts: Option<&Devpts>,
+    capture: &Capture,
+) -> io::Result<ContainerIo> {
     debug!(
         "Container {} stdout is {}",
         container,
@@ -35,23 +252,159 @@ pub async fn open(container: &Container, io: &manifest::io::Io) -> io::Result<Co
         serde_plain::to_string(&io.stderr).expect("internal error")
     );
 
+    if io.tty {
+        let devpts = devpts.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no devpts instance for the terminal of {container}"),
+            )
+        })?;
+        debug!(
+            "Container {} stdin, stdout and stderr is a terminal",
+            container
+        );
+        let (terminal, io) = terminal(container, devpts, &io.stdout, capture.clone())?;
+        return Ok(ContainerIo {
+            io,
+            terminal: Some(terminal),
+        });
+    }
+
     // Open dev null - needed in any case for stdin
     let dev_null = nix::fcntl::open("/dev/null", OFlag::O_RDWR, Mode::empty())
         .map_err(|err| io::Error::from_raw_os_error(err as i32))
         .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })?;
-    let stdout = forward(&io.stdout, &dev_null, container, tokio::io::stdout())?;
-    let stderr = forward(&io.stderr, &dev_null, container, tokio::io::stderr())?;
+    let stdout = forward(
+        &io.stdout,
+        &dev_null,
+        container,
+        tokio::io::stdout(),
+        capture.clone(),
+        LogStream::Stdout,
+    )?;
+    let stderr = forward(
+        &io.stderr,
+        &dev_null,
+        container,
+        tokio::io::stderr(),
+        capture.clone(),
+        LogStream::Stderr,
+    )?;
     let io = [dev_null, stdout, stderr];
 
-    Ok(ContainerIo { io })
+    Ok(ContainerIo { io, terminal: None })
 }
 
-/// Spawn a task that forwards stdout/stderr to the logging system or stdout/stderr
+/// Open a pseudo terminal used for stdin, stdout and stderr. The output is forwarded
+/// according to `destination` and to the attached clients.
+fn terminal(
+    container: &Container,
+    devpts: &Devpts,
+    destination: &Output,
+    capture: Capture,
+) -> io::Result<(Terminal, [OwnedFd; 3])> {
+    let (master, slave) = devpts.openpty()?;
+    let mut reader = master.clone();
+    let mut writer = master.clone();
+    let io = [slave.try_clone()?, slave.try_clone()?, slave];
+
+    let (input, mut input_rx) = mpsc::channel::<Vec<u8>>(ATTACH_BUFFER);
+    task::spawn(async move {
+        while let Some(data) = input_rx.recv().await {
+            if writer.write_all(&data).await.is_err() || writer.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let (output, _) = broadcast::channel(ATTACH_BUFFER);
+    let sender = output.clone();
+    let destination = destination.clone();
+    let target = container.to_string();
+    task::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut line = Vec::new();
+        // Reading fails with EIO once all processes closed the terminal
+        while let Ok(n) = reader.read(&mut buffer).await {
+            if n == 0 {
+                break;
+            }
+            let data = &buffer[..n];
+            // There might be no client attached
+            sender.send(data.to_vec()).ok();
+
+            match destination {
+                Output::Discard => (),
+                Output::Pipe => {
+                    line.extend_from_slice(data);
+                    while let Some(end) = line.iter().position(|b| *b == b'\n') {
+                        let bytes = line.drain(..=end).collect::<Vec<_>>();
+                        let text = String::from_utf8_lossy(&bytes)
+                            .trim_end_matches(['\r', '\n'])
+                            .to_string();
+                        log::debug!(target: &target, "{}", text);
+                        capture.push(LogStream::Stdout, text).await;
+                    }
+                }
+                Output::Inherit => {
+                    stdout.write_all(data).await.ok();
+                    stdout.flush().await.ok();
+                }
+            }
+        }
+    });
+
+    let terminal = Terminal {
+        master,
+        input,
+        output,
+    };
+    Ok((terminal, io))
+}
+
+/// Open the io of a process spawned with an exec request and return the process end and
+/// the runtime end. If `devpts` is set a pseudo terminal allocated from it is used for
+/// stdin, stdout and stderr. Otherwise each stream is a socket pair.
+pub fn open_exec(devpts: Option<&Devpts>) -> io::Result<([OwnedFd; 3], ExecIo)> {
+    if let Some(devpts) = devpts {
+        let (master, slave) = devpts.openpty()?;
+        let io = [slave.try_clone()?, slave.try_clone()?, slave];
+        let exec_io = ExecIo {
+            stdin: Box::new(master.clone()),
+            stdout: Box::new(master),
+            stderr: Box::new(io::empty()),
+        };
+        Ok((io, exec_io))
+    } else {
+        let (stdin, stdin_fd) = pair()?;
+        let (stdout, stdout_fd) = pair()?;
+        let (stderr, stderr_fd) = pair()?;
+        let exec_io = ExecIo {
+            stdin: Box::new(stdin),
+            stdout: Box::new(stdout),
+            stderr: Box::new(stderr),
+        };
+        Ok(([stdin_fd, stdout_fd, stderr_fd], exec_io))
+    }
+}
+
+/// Create a socket pair and return the async runtime end and the process end
+fn pair() -> io::Result<(UnixStream, OwnedFd)> {
+    let (runtime, process) = StdUnixStream::pair()?;
+    runtime.set_nonblocking(true)?;
+    Ok((UnixStream::from_std(runtime)?, process.into()))
+}
+
+/// Spawn a task that forwards stdout/stderr to the logging system and the log capture
+/// or stdout/stderr
 fn forward<W: AsyncWrite + Send + Sync + Unpin + 'static>(
     destination: &Output,
     dev_null: &OwnedFd,
     container: &Container,
     mut out: W,
+    capture: Capture,
+    stream: LogStream,
 ) -> io::Result<OwnedFd> {
     match destination {
         Output::Discard => dev_null.try_clone(),
@@ -63,6 +416,7 @@ fn forward<W: AsyncWrite + Send + Sync + Unpin + 'static>(
             task::spawn(async move {
                 while let Ok(Some(line)) = lines.next_line().await {
                     log::debug!(target: &target, "{}", line);
+                    capture.push(stream, line).await;
                 }
             });
             Ok(write.into())
@@ -76,3 +430,29 @@ fn forward<W: AsyncWrite + Send + Sync + Unpin + 'static>(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runtime::{config, logs::LogBuffer};
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn tty_without_devpts() {
+        let container = Container::try_from("test:0.0.1").unwrap();
+        let capture = Capture::new(
+            &container,
+            Arc::new(LogBuffer::new(0)),
+            &config::Logs::default(),
+        );
+        let io = manifest::io::Io {
+            tty: true,
+            ..Default::default()
+        };
+        assert!(open(&container, &io, None, &capture).await.is_err());
+
+        let io = manifest::io::Io::default();
+        let io = open(&container, &io, None, &capture).await.unwrap();
+        assert!(io.terminal.is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    api::model::{self, Container, JournalEntry},
+    runtime::repository::RepositoryId,
+};
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use tokio::fs;
+
+/// File name of the journal in the data directory. Container names cannot contain
+/// a `@` and therefore the file does not clash with a persist directory.
+const FILE: &str = "@journal.json";
+
+/// Desired state of the containers. Every change is written to the data directory
+/// and the state is restored when the runtime starts.
+#[derive(Debug)]
+pub(super) struct Journal {
+    path: PathBuf,
+    journal: model::Journal,
+}
+
+impl Journal {
+    /// Load the journal from `data_dir`. A missing or unreadable journal results in an
+    /// empty journal.
+    pub async fn load(data_dir: &Path) -> Journal {
+        let path = data_dir.join(FILE);
+        let journal = match fs::read(&path).await {
+            Ok(content) => serde_json::from_slice(&content).unwrap_or_else(|e| {
+                warn!("Discarding invalid journal {}: {}", path.display(), e);
+                model::Journal::default()
+            }),
+            Err(_) => model::Journal::default(),
+        };
+        Journal { path, journal }
+    }
+
+    /// The recorded desired state
+    pub fn journal(&self) -> &model::Journal {
+        &self.journal
+    }
+
+    /// Record that `container` is installed in `repository`
+    pub async fn installed(&mut self, container: &Container, repository: &RepositoryId) {
+        let entry = JournalEntry {
+            repository: repository.clone(),
+            mounted: false,
+            started: None,
+        };
+        self.journal.containers.insert(container.clone(), entry);
+        self.save().await;
+    }
+
+    /// Remove `container` from the journal
+    pub async fn uninstalled(&mut self, container: &Container) {
+        if self.journal.containers.remove(container).is_some() {
+            self.save().await;
+        }
+    }
+
+    /// Update the entry of `container` that is installed in `repository`
+    pub async fn update<F>(&mut self, container: &Container, repository: &RepositoryId, f: F)
+    where
+        F: FnOnce(&mut JournalEntry),
+    {
+        let entry = self
+            .journal
+            .containers
+            .entry(container.clone())
+            .or_insert_with(|| JournalEntry {
+                repository: repository.clone(),
+                mounted: false,
+                started: None,
+            });
+        let previous = entry.clone();
+        f(entry);
+        if *entry != previous {
+            self.save().await;
+        }
+    }
+
+    /// Clear the journal and return the previous content
+    pub async fn clear(&mut self) -> model::Journal {
+        let journal = std::mem::take(&mut self.journal);
+        if let Err(e) = fs::remove_file(&self.path).await {
+            debug!("Failed to remove {}: {}", self.path.display(), e);
+        }
+        journal
+    }
+
+    /// Write the journal. Failures are logged because the runtime continues without
+    /// a persisted state.
+    async fn save(&self) {
+        if let Err(e) = self.write().await {
+            warn!("Failed to write journal {}: {:#}", self.path.display(), e);
+        }
+    }
+
+    /// Write the journal to a temporary file and replace the journal atomically
+    async fn write(&self) -> Result<()> {
+        let content = serde_json::to_vec(&self.journal).context("failed to serialize")?;
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, content)
+            .await
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .await
+            .with_context(|| format!("failed to rename {}", tmp.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::non_nul_string::NonNulString;
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let container = Container::try_from("test:0.0.1").unwrap();
+        let repository = RepositoryId::from("mem");
+
+        let mut journal = Journal::load(dir.path()).await;
+        assert!(journal.journal().containers.is_empty());
+        journal.installed(&container, &repository).await;
+        journal
+            .update(&container, &repository, |entry| {
+                entry.mounted = true;
+                entry.started = Some(model::JournalStart {
+                    init: None,
+                    arguments: vec![NonNulString::try_from("sleep").unwrap()],
+                    environment: Default::default(),
+                    max_runtime: None,
+                });
+            })
+            .await;
+
+        let loaded = Journal::load(dir.path()).await;
+        assert_eq!(loaded.journal(), journal.journal());
+        let entry = &loaded.journal().containers[&container];
+        assert!(entry.mounted);
+        assert_eq!(entry.started.as_ref().unwrap().arguments.len(), 1);
+
+        assert_eq!(journal.clear().await.containers.len(), 1);
+        assert!(Journal::load(dir.path())
+            .await
+            .journal()
+            .containers
+            .is_empty());
+    }
+}
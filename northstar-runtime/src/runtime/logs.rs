@@ -0,0 +1,285 @@
+use crate::{
+    api::model::{LogLine, LogStream},
+    common::container::Container,
+    runtime::config,
+};
+use humantime_serde::re::humantime;
+use log::warn;
+use std::{
+    collections::VecDeque,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    fs,
+    io::AsyncWriteExt,
+    sync::{broadcast, Mutex as AsyncMutex},
+};
+
+/// Number of lines buffered for clients that follow the output of a container
+const FOLLOW_BUFFER: usize = 256;
+
+/// Bounded buffer of the last lines written by a container. The buffer is kept
+/// across restarts of the container.
+#[derive(Debug)]
+pub(super) struct LogBuffer {
+    capacity: usize,
+    lines: Mutex<VecDeque<LogLine>>,
+}
+
+impl LogBuffer {
+    /// Create a new buffer that keeps up to `capacity` lines
+    pub fn new(capacity: usize) -> LogBuffer {
+        LogBuffer {
+            capacity,
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Buffered lines since the timestamp `since` limited to the last `tail` lines.
+    /// If `follow` is set a receiver for the lines pushed afterwards is returned.
+    pub fn lines(
+        &self,
+        tail: Option<usize>,
+        since: Option<u64>,
+        follow: Option<&broadcast::Sender<LogLine>>,
+    ) -> (Vec<LogLine>, Option<broadcast::Receiver<LogLine>>) {
+        let lines = self.lines.lock().expect("failed to lock log buffer");
+
+        // Subscribe while the buffer is locked to neither miss nor duplicate a line
+        let receiver = follow.map(|sender| sender.subscribe());
+
+        let lines = lines
+            .iter()
+            .filter(|line| since.map(|since| line.timestamp >= since).unwrap_or(true))
+            .cloned()
+            .collect::<Vec<_>>();
+        let skip = tail
+            .map(|tail| lines.len().saturating_sub(tail))
+            .unwrap_or(0);
+        (lines.into_iter().skip(skip).collect(), receiver)
+    }
+}
+
+/// Capture of the output of a started container. Lines are stored in the buffer of the
+/// container, the optional log file and are sent to the clients that follow the output.
+#[derive(Clone, Debug)]
+pub(super) struct Capture {
+    buffer: Arc<LogBuffer>,
+    sender: broadcast::Sender<LogLine>,
+    file: Option<Arc<AsyncMutex<LogFile>>>,
+}
+
+impl Capture {
+    /// Create a new capture for `container` that writes to `buffer` and to a log file
+    /// if a log directory is configured. Each version has a log file of its own because
+    /// different versions of a container might be started one after another.
+    pub fn new(container: &Container, buffer: Arc<LogBuffer>, config: &config::Logs) -> Capture {
+        let (sender, _) = broadcast::channel(FOLLOW_BUFFER);
+        let file = config.dir.as_ref().map(|dir| {
+            let path = dir.join(format!("{container}.log"));
+            Arc::new(AsyncMutex::new(LogFile::new(
+                path,
+                config.file_size,
+                config.files,
+            )))
+        });
+        Capture {
+            buffer,
+            sender,
+            file,
+        }
+    }
+
+    /// Sender of the lines of this capture. Followers subscribe to this sender.
+    pub fn sender(&self) -> broadcast::Sender<LogLine> {
+        self.sender.clone()
+    }
+
+    /// Record a line written by the container
+    pub async fn push(&self, stream: LogStream, line: String) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let line = LogLine {
+            timestamp,
+            stream,
+            line,
+        };
+
+        if let Some(file) = &self.file {
+            let mut file = file.lock().await;
+            if let Err(e) = file.write(&line).await {
+                warn!("Failed to write to {}: {}", file.path.display(), e);
+            }
+        }
+
+        let mut lines = self.buffer.lines.lock().expect("failed to lock log buffer");
+        if self.buffer.capacity > 0 {
+            if lines.len() == self.buffer.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.clone());
+        }
+        // There might be no follower
+        self.sender.send(line).ok();
+    }
+}
+
+/// Log file that is rotated once it exceeds `max_size` bytes. The rotated files are
+/// named `<name>:<version>.log.1` to `<name>:<version>.log.<files>`.
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    file: Option<fs::File>,
+    size: u64,
+    max_size: u64,
+    files: usize,
+}
+
+impl LogFile {
+    fn new(path: PathBuf, max_size: u64, files: usize) -> LogFile {
+        LogFile {
+            path,
+            file: None,
+            size: 0,
+            max_size,
+            files,
+        }
+    }
+
+    /// Append `line` and rotate the file if needed. The file is opened on the first write.
+    async fn write(&mut self, line: &LogLine) -> io::Result<()> {
+        let timestamp = UNIX_EPOCH + Duration::from_millis(line.timestamp);
+        let stream = match line.stream {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        };
+        let entry = format!(
+            "{} {} {}\n",
+            humantime::format_rfc3339_millis(timestamp),
+            stream,
+            line.line
+        );
+
+        if self.file.is_none() {
+            self.open().await?;
+        }
+        if self.size > 0 && self.size + entry.len() as u64 > self.max_size {
+            self.rotate().await?;
+        }
+
+        let file = self.file.as_mut().expect("log file is not open");
+        file.write_all(entry.as_bytes()).await?;
+        file.flush().await?;
+        self.size += entry.len() as u64;
+        Ok(())
+    }
+
+    async fn open(&mut self) -> io::Result<()> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        self.size = file.metadata().await?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    async fn rotate(&mut self) -> io::Result<()> {
+        self.file.take();
+        let rotated = |n: usize| self.path.with_extension(format!("log.{n}"));
+        for n in (1..self.files).rev() {
+            // Not all rotated files exist yet
+            fs::rename(rotated(n), rotated(n + 1)).await.ok();
+        }
+        if self.files > 0 {
+            fs::rename(&self.path, rotated(1)).await?;
+        } else {
+            fs::remove_file(&self.path).await?;
+        }
+        self.open().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(timestamp: u64) -> LogLine {
+        LogLine {
+            timestamp,
+            stream: LogStream::Stdout,
+            line: timestamp.to_string(),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn tail_and_since() {
+        let buffer = LogBuffer::new(3);
+        {
+            let mut lines = buffer.lines.lock().unwrap();
+            lines.extend((1..=3).map(line));
+        }
+
+        let (lines, receiver) = buffer.lines(None, None, None);
+        assert_eq!(lines, vec![line(1), line(2), line(3)]);
+        assert!(receiver.is_none());
+        assert_eq!(buffer.lines(Some(2), None, None).0, vec![line(2), line(3)]);
+        assert_eq!(buffer.lines(None, Some(3), None).0, vec![line(3)]);
+        assert_eq!(
+            buffer.lines(Some(5), Some(2), None).0,
+            vec![line(2), line(3)]
+        );
+    }
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn capacity_and_follow() {
+        let buffer = Arc::new(LogBuffer::new(2));
+        let container = Container::try_from("test:0.0.1").unwrap();
+        let capture = Capture::new(&container, buffer.clone(), &config::Logs::default());
+
+        let sender = capture.sender();
+        let (_, receiver) = buffer.lines(None, None, Some(&sender));
+        let mut receiver = receiver.unwrap();
+
+        for n in 0..3 {
+            capture.push(LogStream::Stdout, n.to_string()).await;
+        }
+
+        let lines = buffer.lines(None, None, None).0;
+        assert_eq!(
+            lines.iter().map(|l| l.line.as_str()).collect::<Vec<_>>(),
+            vec!["1", "2"]
+        );
+        assert_eq!(receiver.recv().await.unwrap().line, "0");
+    }
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn file_per_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config::Logs {
+            dir: Some(dir.path().to_owned()),
+            ..Default::default()
+        };
+        for version in ["0.0.1", "0.0.2"] {
+            let container = Container::try_from(format!("test:{version}").as_str()).unwrap();
+            let capture = Capture::new(&container, Arc::new(LogBuffer::new(0)), &config);
+            for n in 0..3 {
+                capture.push(LogStream::Stdout, n.to_string()).await;
+            }
+        }
+
+        for version in ["0.0.1", "0.0.2"] {
+            let path = dir.path().join(format!("test:{version}.log"));
+            assert_eq!(std::fs::read_to_string(path).unwrap().lines().count(), 3);
+        }
+    }
+}
@@ -1,18 +1,27 @@
+mod budgets;
 mod cgroups;
 mod console;
+mod core_dumps;
 mod debug;
+mod dependency;
 mod devicemapper;
 mod env;
 mod error;
 mod events;
 mod exit_status;
 mod fork;
+mod health;
+mod history;
+mod hooks;
 mod io;
 mod ipc;
+mod journal;
 mod key;
+mod logs;
 #[allow(unused)]
 mod loopdev;
 mod mount;
+mod network;
 mod persistence;
 mod repository;
 #[allow(clippy::module_inception)]
@@ -21,6 +30,7 @@ mod sockets;
 mod state;
 mod stats;
 mod token;
+mod uts;
 
 /// Runtime configuration
 pub mod config;
@@ -0,0 +1,398 @@
+use std::{
+    fs, io,
+    net::Ipv4Addr,
+    os::unix::prelude::AsRawFd,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+use log::{debug, info, warn};
+use nix::{
+    errno::Errno,
+    mount::{self, MntFlags, MsFlags},
+    sched,
+};
+
+use super::config::{self, Config};
+use netlink::Netlink;
+
+mod netlink;
+
+/// Name of the container end of the veth pairs
+const INTERFACE: &str = "eth0";
+
+/// Directory of the named network namespaces. See ip-netns(8).
+#[cfg(target_os = "android")]
+const NETNS_DIR: &str = "/run/netns";
+#[cfg(not(target_os = "android"))]
+const NETNS_DIR: &str = "/var/run/netns";
+
+/// Bridge of the runtime. A bridge that existed before the runtime started is
+/// not removed on shutdown.
+#[derive(Debug)]
+pub(crate) struct Bridge {
+    name: String,
+    /// Set if the bridge was created by the runtime
+    created: bool,
+}
+
+impl Bridge {
+    /// Remove the bridge if it was created by the runtime
+    pub fn destroy(self) -> Result<()> {
+        if !self.created {
+            debug!("Keeping bridge {}", self.name);
+            return Ok(());
+        }
+        debug!("Removing bridge {}", self.name);
+        let mut netlink = Netlink::new().context("failed to open netlink socket")?;
+        remove(&mut netlink, &self.name).with_context(|| format!("failed to remove {}", self.name))
+    }
+}
+
+/// Host end of the veth pair of a container. The container end is removed by the
+/// kernel together with the host end.
+#[derive(Debug)]
+pub(crate) struct Veth {
+    /// Index of the address of the container in the subnet
+    index: u32,
+    name: String,
+}
+
+impl Veth {
+    /// Index of the address of the container
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn destroy(self) {
+        debug!("Removing {}", self.name);
+        if let Err(e) = Netlink::new().and_then(|mut netlink| remove(&mut netlink, &self.name)) {
+            warn!("Failed to remove {}: {}", self.name, e);
+        }
+    }
+}
+
+/// Named network namespace created by the runtime
+#[derive(Debug)]
+pub(crate) struct Namespace {
+    path: PathBuf,
+    /// Veth pair if the namespace is connected to the bridge
+    veth: Option<Veth>,
+}
+
+impl Namespace {
+    /// Veth pair if the namespace is connected to the bridge
+    pub fn veth(&self) -> Option<&Veth> {
+        self.veth.as_ref()
+    }
+
+    pub fn destroy(self) {
+        if let Some(veth) = self.veth {
+            veth.destroy();
+        }
+        debug!("Removing network namespace {}", self.path.display());
+        if let Err(e) = unmount(&self.path) {
+            warn!("Failed to remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Path of the named network namespace `name`
+pub(crate) fn namespace(name: &str) -> PathBuf {
+    Path::new(NETNS_DIR).join(name)
+}
+
+/// Create the bridge and assign its address. An existing bridge is reused.
+pub(super) fn init(config: &config::Network) -> Result<Bridge> {
+    info!(
+        "Initializing bridge {} with address {}",
+        config.bridge, config.address
+    );
+    let mut netlink = Netlink::new().context("failed to open netlink socket")?;
+    let created = match netlink.add_bridge(&config.bridge) {
+        Err(e) if e.raw_os_error() == Some(Errno::EEXIST as i32) => {
+            debug!("Using existing bridge {}", config.bridge);
+            false
+        }
+        result => {
+            result.with_context(|| format!("failed to create {}", config.bridge))?;
+            true
+        }
+    };
+    let bridge = Bridge {
+        name: config.bridge.clone(),
+        created,
+    };
+
+    match configure_bridge(&mut netlink, config) {
+        Ok(()) => Ok(bridge),
+        Err(e) => {
+            bridge.destroy().ok();
+            Err(e)
+        }
+    }
+}
+
+/// Assign the address of the bridge and bring it up
+fn configure_bridge(netlink: &mut Netlink, config: &config::Network) -> Result<()> {
+    let index = netlink
+        .index(&config.bridge)
+        .with_context(|| format!("failed to get index of {}", config.bridge))?;
+    match netlink.add_address(index, config.address.address, config.address.prefix) {
+        Err(e) if e.raw_os_error() == Some(Errno::EEXIST as i32) => (),
+        result => result
+            .with_context(|| format!("failed to assign {} to {}", config.address, config.bridge))?,
+    }
+    netlink
+        .up(&config.bridge)
+        .with_context(|| format!("failed to set {} up", config.bridge))
+}
+
+/// Create the named network namespaces of `config`. Namespaces with `bridge` are
+/// connected to the bridge.
+pub(super) fn create_namespaces(config: &Config) -> Result<Vec<Namespace>> {
+    let mut namespaces: Vec<Namespace> = Vec::with_capacity(config.network_namespaces.len());
+    // Addresses are allocated in the order of the names
+    for (name, namespace) in config
+        .network_namespaces
+        .iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+    {
+        let used = namespaces
+            .iter()
+            .filter_map(Namespace::veth)
+            .map(Veth::index)
+            .collect::<Vec<_>>();
+        let bridge = config.network.as_ref().filter(|_| namespace.bridge);
+        match create_namespace(name, bridge, &used) {
+            Ok(namespace) => namespaces.push(namespace),
+            Err(e) => {
+                namespaces.into_iter().for_each(Namespace::destroy);
+                return Err(e);
+            }
+        }
+    }
+    Ok(namespaces)
+}
+
+/// Create the named network namespace `name` and bring `lo` up
+fn create_namespace(
+    name: &str,
+    bridge: Option<&config::Network>,
+    used: &[u32],
+) -> Result<Namespace> {
+    let path = namespace(name);
+    info!("Creating network namespace {}", path.display());
+
+    fs::create_dir_all(NETNS_DIR).with_context(|| format!("failed to create {NETNS_DIR}"))?;
+    // Remove the leftover of a runtime instance that did not shut down
+    if path.exists() {
+        unmount(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+    fs::File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+
+    let mut namespace = Namespace { path, veth: None };
+
+    // The namespace is kept alive by a bind mount of the namespace of a thread that
+    // exits afterwards
+    let result = thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                sched::unshare(sched::CloneFlags::CLONE_NEWNET)
+                    .context("failed to unshare network namespace")?;
+                loopback()?;
+                mount::mount(
+                    Some("/proc/thread-self/ns/net"),
+                    &namespace.path,
+                    None::<&str>,
+                    MsFlags::MS_BIND,
+                    None::<&str>,
+                )
+                .with_context(|| format!("failed to mount {}", namespace.path.display()))
+            })
+            .join()
+            .expect("failed to join network thread")
+    })
+    .and_then(|_| match bridge {
+        Some(config) => {
+            let index = allocate(config, used)?;
+            namespace.veth = Some(setup(config, index, &namespace.path)?);
+            Ok(())
+        }
+        None => Ok(()),
+    });
+
+    match result {
+        Ok(()) => Ok(namespace),
+        Err(e) => {
+            namespace.destroy();
+            Err(e)
+        }
+    }
+}
+
+/// Bring `lo` of the network namespace of the calling thread up
+pub(super) fn loopback() -> Result<()> {
+    Netlink::new()
+        .and_then(|mut netlink| netlink.up("lo"))
+        .context("failed to set lo up")
+}
+
+/// Lowest index of a container address in the subnet that is not in `used`. The network,
+/// broadcast and bridge addresses are skipped.
+pub(super) fn allocate(config: &config::Network, used: &[u32]) -> Result<u32> {
+    let hosts = u32::MAX
+        .checked_shr(config.address.prefix as u32)
+        .unwrap_or(0);
+    let gateway = u32::from(config.address.address) & hosts;
+    match (1..hosts).find(|index| *index != gateway && !used.contains(index)) {
+        Some(index) => Ok(index),
+        None => bail!("no free address in {}", config.address),
+    }
+}
+
+/// Address of the container with `index`
+fn address(config: &config::Network, index: u32) -> Ipv4Addr {
+    let hosts = u32::MAX
+        .checked_shr(config.address.prefix as u32)
+        .unwrap_or(0);
+    Ipv4Addr::from((u32::from(config.address.address) & !hosts) | index)
+}
+
+/// Connect the network namespace `netns` to the bridge. Create a veth pair, assign the
+/// address with `index` to the namespace end and route via the bridge.
+pub(super) fn setup(config: &config::Network, index: u32, netns: &Path) -> Result<Veth> {
+    let veth = Veth {
+        index,
+        name: format!("{}{}", config.veth_prefix, index),
+    };
+    let address = address(config, index);
+    debug!(
+        "Connecting {} of {} with address {} to {}",
+        veth.name,
+        netns.display(),
+        address,
+        config.bridge
+    );
+
+    let netns =
+        fs::File::open(netns).with_context(|| format!("failed to open {}", netns.display()))?;
+    let mut netlink = Netlink::new().context("failed to open netlink socket")?;
+
+    // Remove the leftover of a runtime instance that did not shut down. Interfaces with the
+    // configured prefix are owned by the runtime.
+    remove(&mut netlink, &veth.name).with_context(|| format!("failed to remove {}", veth.name))?;
+    netlink
+        .add_veth(&veth.name, INTERFACE, netns.as_raw_fd())
+        .with_context(|| format!("failed to create {}", veth.name))?;
+
+    let result = attach(&mut netlink, config, &veth.name).and_then(|_| {
+        // Netlink sockets operate on the namespace they are created in. Enter the network
+        // namespace of the container on a dedicated thread in order to leave the runtime
+        // threads untouched.
+        thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    sched::setns(&netns, sched::CloneFlags::CLONE_NEWNET)
+                        .context("failed to enter network namespace")?;
+                    configure(address, config)
+                })
+                .join()
+                .expect("failed to join network thread")
+        })
+    });
+
+    match result {
+        Ok(()) => Ok(veth),
+        Err(e) => {
+            veth.destroy();
+            Err(e)
+        }
+    }
+}
+
+/// Attach the host end `name` to the bridge and bring it up
+fn attach(netlink: &mut Netlink, config: &config::Network, name: &str) -> Result<()> {
+    let bridge = netlink
+        .index(&config.bridge)
+        .with_context(|| format!("failed to get index of {}", config.bridge))?;
+    netlink
+        .set_master(name, bridge)
+        .with_context(|| format!("failed to attach {} to {}", name, config.bridge))?;
+    netlink
+        .up(name)
+        .with_context(|| format!("failed to set {name} up"))
+}
+
+/// Configure the interfaces of the network namespace of the calling thread
+fn configure(address: Ipv4Addr, config: &config::Network) -> Result<()> {
+    loopback()?;
+    let mut netlink = Netlink::new().context("failed to open netlink socket")?;
+    let index = netlink
+        .index(INTERFACE)
+        .with_context(|| format!("failed to get index of {INTERFACE}"))?;
+    netlink
+        .add_address(index, address, config.address.prefix)
+        .with_context(|| format!("failed to assign {address} to {INTERFACE}"))?;
+    netlink
+        .up(INTERFACE)
+        .with_context(|| format!("failed to set {INTERFACE} up"))?;
+    netlink
+        .add_default_route(index, config.address.address)
+        .with_context(|| format!("failed to add route via {}", config.address.address))
+}
+
+/// Detach the mount of the named network namespace `path` and remove the mount point
+fn unmount(path: &Path) -> io::Result<()> {
+    match mount::umount2(path, MntFlags::MNT_DETACH) {
+        Ok(()) | Err(Errno::EINVAL) => (),
+        Err(e) => return Err(e.into()),
+    }
+    fs::remove_file(path)
+}
+
+/// Remove the interface `name`. A missing interface is not an error.
+fn remove(netlink: &mut Netlink, name: &str) -> io::Result<()> {
+    match netlink.delete(name) {
+        Err(e) if e.raw_os_error() == Some(Errno::ENODEV as i32) => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runtime::config::{Cidr, Network};
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn allocate_addresses() {
+        let config = Network {
+            bridge: "northstar0".into(),
+            address: Cidr {
+                address: Ipv4Addr::new(172, 30, 0, 1),
+                prefix: 30,
+            },
+            veth_prefix: "nsveth".into(),
+        };
+        // The bridge has the first host address
+        assert_eq!(allocate(&config, &[]).unwrap(), 2);
+        assert_eq!(address(&config, 2), Ipv4Addr::new(172, 30, 0, 2));
+        // The broadcast address is not allocated
+        assert!(allocate(&config, &[2]).is_err());
+
+        let config = Network {
+            address: Cidr {
+                address: Ipv4Addr::new(10, 0, 0, 2),
+                prefix: 16,
+            },
+            ..config
+        };
+        assert_eq!(allocate(&config, &[]).unwrap(), 1);
+        assert_eq!(allocate(&config, &[1]).unwrap(), 3);
+        assert_eq!(address(&config, 3), Ipv4Addr::new(10, 0, 0, 3));
+        assert_eq!(address(&config, 258), Ipv4Addr::new(10, 0, 1, 2));
+    }
+}
@@ -0,0 +1,325 @@
+//! Minimal rtnetlink client for the setup of bridges, veth pairs, addresses and routes.
+//! See rtnetlink(7). The constants do not exist in libc for all targets.
+
+use std::{
+    io,
+    net::Ipv4Addr,
+    os::unix::prelude::{AsRawFd, OwnedFd, RawFd},
+};
+
+use nix::sys::socket::{
+    self, AddressFamily, MsgFlags, NetlinkAddr, SockFlag, SockProtocol, SockType,
+};
+
+const NLMSG_HDRLEN: usize = 16;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+const NLM_F_REQUEST: u16 = 0x1;
+const NLM_F_ACK: u16 = 0x4;
+const NLM_F_EXCL: u16 = 0x200;
+const NLM_F_CREATE: u16 = 0x400;
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_DELLINK: u16 = 17;
+const RTM_GETLINK: u16 = 18;
+const RTM_NEWADDR: u16 = 20;
+const RTM_NEWROUTE: u16 = 24;
+
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MASTER: u16 = 10;
+const IFLA_LINKINFO: u16 = 18;
+const IFLA_NET_NS_FD: u16 = 28;
+const IFLA_INFO_KIND: u16 = 1;
+const IFLA_INFO_DATA: u16 = 2;
+const VETH_INFO_PEER: u16 = 1;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_BROADCAST: u16 = 4;
+
+const RTA_OIF: u16 = 4;
+const RTA_GATEWAY: u16 = 5;
+
+const AF_UNSPEC: u8 = 0;
+const AF_INET: u8 = 2;
+const IFF_UP: u32 = 0x1;
+const RT_TABLE_MAIN: u8 = 254;
+const RTPROT_BOOT: u8 = 3;
+const RT_SCOPE_UNIVERSE: u8 = 0;
+const RTN_UNICAST: u8 = 1;
+
+/// Size of the receive buffer
+const BUFFER_SIZE: usize = 8192;
+
+/// Route netlink socket. The socket operates on the network namespace it was created in.
+#[derive(Debug)]
+pub(crate) struct Netlink {
+    socket: OwnedFd,
+    seq: u32,
+}
+
+impl Netlink {
+    /// Open a route netlink socket in the network namespace of the calling thread
+    pub fn new() -> io::Result<Netlink> {
+        let socket = socket::socket(
+            AddressFamily::Netlink,
+            SockType::Raw,
+            SockFlag::SOCK_CLOEXEC,
+            SockProtocol::NetlinkRoute,
+        )?;
+        socket::bind(socket.as_raw_fd(), &NetlinkAddr::new(0, 0))?;
+        Ok(Netlink { socket, seq: 0 })
+    }
+
+    /// Index of the interface `name`
+    pub fn index(&mut self, name: &str) -> io::Result<u32> {
+        let mut message = Message::new(RTM_GETLINK, 0);
+        message.ifinfomsg(0, 0);
+        message.attr_str(IFLA_IFNAME, name);
+        let reply = self.request(message)?;
+        // The index follows family, padding and type of the ifinfomsg
+        reply
+            .get(NLMSG_HDRLEN + 4..NLMSG_HDRLEN + 8)
+            .map(|index| u32::from_ne_bytes([index[0], index[1], index[2], index[3]]))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid link message"))
+    }
+
+    /// Create the bridge `name`
+    pub fn add_bridge(&mut self, name: &str) -> io::Result<()> {
+        let mut message = Message::new(RTM_NEWLINK, NLM_F_CREATE | NLM_F_EXCL);
+        message.ifinfomsg(0, 0);
+        message.attr_str(IFLA_IFNAME, name);
+        let linkinfo = message.begin(IFLA_LINKINFO);
+        message.attr_str(IFLA_INFO_KIND, "bridge");
+        message.end(linkinfo);
+        self.request(message).map(drop)
+    }
+
+    /// Create a veth pair with the end `name` in the namespace of this socket and the
+    /// end `peer` in the network namespace `netns`
+    pub fn add_veth(&mut self, name: &str, peer: &str, netns: RawFd) -> io::Result<()> {
+        let mut message = Message::new(RTM_NEWLINK, NLM_F_CREATE | NLM_F_EXCL);
+        message.ifinfomsg(0, 0);
+        message.attr_str(IFLA_IFNAME, name);
+        let linkinfo = message.begin(IFLA_LINKINFO);
+        message.attr_str(IFLA_INFO_KIND, "veth");
+        let data = message.begin(IFLA_INFO_DATA);
+        let info = message.begin(VETH_INFO_PEER);
+        message.ifinfomsg(0, 0);
+        message.attr_str(IFLA_IFNAME, peer);
+        message.attr(IFLA_NET_NS_FD, &(netns as u32).to_ne_bytes());
+        message.end(info);
+        message.end(data);
+        message.end(linkinfo);
+        self.request(message).map(drop)
+    }
+
+    /// Remove the interface `name`
+    pub fn delete(&mut self, name: &str) -> io::Result<()> {
+        let mut message = Message::new(RTM_DELLINK, 0);
+        message.ifinfomsg(0, 0);
+        message.attr_str(IFLA_IFNAME, name);
+        self.request(message).map(drop)
+    }
+
+    /// Bring the interface `name` up
+    pub fn up(&mut self, name: &str) -> io::Result<()> {
+        let mut message = Message::new(RTM_NEWLINK, 0);
+        message.ifinfomsg(IFF_UP, IFF_UP);
+        message.attr_str(IFLA_IFNAME, name);
+        self.request(message).map(drop)
+    }
+
+    /// Attach the interface `name` to the bridge with the index `master`
+    pub fn set_master(&mut self, name: &str, master: u32) -> io::Result<()> {
+        let mut message = Message::new(RTM_NEWLINK, 0);
+        message.ifinfomsg(0, 0);
+        message.attr_str(IFLA_IFNAME, name);
+        message.attr(IFLA_MASTER, &master.to_ne_bytes());
+        self.request(message).map(drop)
+    }
+
+    /// Assign `address` with `prefix` to the interface `index`
+    pub fn add_address(&mut self, index: u32, address: Ipv4Addr, prefix: u8) -> io::Result<()> {
+        let broadcast = u32::from(address) | u32::MAX.checked_shr(prefix as u32).unwrap_or(0);
+        let mut message = Message::new(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL);
+        // ifaddrmsg: family, prefix length, flags, scope and index
+        message.push(&[AF_INET, prefix, 0, RT_SCOPE_UNIVERSE]);
+        message.push(&index.to_ne_bytes());
+        message.attr(IFA_LOCAL, &address.octets());
+        message.attr(IFA_ADDRESS, &address.octets());
+        message.attr(IFA_BROADCAST, &broadcast.to_be_bytes());
+        self.request(message).map(drop)
+    }
+
+    /// Add a default route via `gateway` on the interface `index`
+    pub fn add_default_route(&mut self, index: u32, gateway: Ipv4Addr) -> io::Result<()> {
+        let mut message = Message::new(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL);
+        // rtmsg: family, destination and source length, tos, table, protocol, scope, type
+        // and flags
+        message.push(&[
+            AF_INET,
+            0,
+            0,
+            0,
+            RT_TABLE_MAIN,
+            RTPROT_BOOT,
+            RT_SCOPE_UNIVERSE,
+            RTN_UNICAST,
+        ]);
+        message.push(&0u32.to_ne_bytes());
+        message.attr(RTA_GATEWAY, &gateway.octets());
+        message.attr(RTA_OIF, &index.to_ne_bytes());
+        self.request(message).map(drop)
+    }
+
+    /// Send `message` and wait for the acknowledgement. Returns the last reply that
+    /// is not an acknowledgement.
+    fn request(&mut self, message: Message) -> io::Result<Vec<u8>> {
+        self.seq = self.seq.wrapping_add(1);
+        let seq = self.seq;
+        let message = message.finish(seq);
+        socket::send(self.socket.as_raw_fd(), &message, MsgFlags::empty())?;
+
+        let mut reply = Vec::new();
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        loop {
+            let len = socket::recv(self.socket.as_raw_fd(), &mut buffer, MsgFlags::empty())?;
+            let mut messages = &buffer[..len];
+            while messages.len() >= NLMSG_HDRLEN {
+                let message_len = u32_at(messages, 0) as usize;
+                let ty = u16::from_ne_bytes([messages[4], messages[5]]);
+                if message_len < NLMSG_HDRLEN || message_len > messages.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "invalid netlink message",
+                    ));
+                }
+                let (message, rest) = messages.split_at(align(message_len).min(messages.len()));
+                messages = rest;
+
+                if u32_at(message, 8) != seq {
+                    continue;
+                }
+                match ty {
+                    NLMSG_ERROR => {
+                        // The error code is zero for an acknowledgement
+                        let error = u32_at(message, NLMSG_HDRLEN) as i32;
+                        return if error == 0 {
+                            Ok(reply)
+                        } else {
+                            Err(io::Error::from_raw_os_error(-error))
+                        };
+                    }
+                    NLMSG_DONE => return Ok(reply),
+                    _ => reply = message[..message_len].to_vec(),
+                }
+            }
+        }
+    }
+}
+
+/// Netlink request with a header and attributes
+struct Message {
+    buffer: Vec<u8>,
+}
+
+impl Message {
+    fn new(ty: u16, flags: u16) -> Message {
+        let mut buffer = Vec::with_capacity(256);
+        // Length and sequence number are set in `finish`
+        buffer.extend(0u32.to_ne_bytes());
+        buffer.extend(ty.to_ne_bytes());
+        buffer.extend((flags | NLM_F_REQUEST | NLM_F_ACK).to_ne_bytes());
+        buffer.extend(0u32.to_ne_bytes());
+        buffer.extend(0u32.to_ne_bytes());
+        Message { buffer }
+    }
+
+    fn push(&mut self, data: &[u8]) {
+        self.buffer.extend(data);
+        self.buffer.resize(align(self.buffer.len()), 0);
+    }
+
+    /// ifinfomsg: family, padding, type, index, flags and change mask
+    fn ifinfomsg(&mut self, flags: u32, change: u32) {
+        self.push(&[AF_UNSPEC, 0, 0, 0]);
+        self.push(&0i32.to_ne_bytes());
+        self.push(&flags.to_ne_bytes());
+        self.push(&change.to_ne_bytes());
+    }
+
+    fn attr(&mut self, ty: u16, data: &[u8]) {
+        let len = 4 + data.len() as u16;
+        self.buffer.extend(len.to_ne_bytes());
+        self.buffer.extend(ty.to_ne_bytes());
+        self.push(data);
+    }
+
+    /// Null terminated string attribute
+    fn attr_str(&mut self, ty: u16, value: &str) {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        self.attr(ty, &data);
+    }
+
+    /// Start a nested attribute. Returns the offset passed to `end`.
+    fn begin(&mut self, ty: u16) -> usize {
+        let offset = self.buffer.len();
+        self.attr(ty, &[]);
+        offset
+    }
+
+    /// Set the length of the nested attribute at `offset`
+    fn end(&mut self, offset: usize) {
+        let len = (self.buffer.len() - offset) as u16;
+        self.buffer[offset..offset + 2].copy_from_slice(&len.to_ne_bytes());
+    }
+
+    fn finish(mut self, seq: u32) -> Vec<u8> {
+        let len = self.buffer.len() as u32;
+        self.buffer[0..4].copy_from_slice(&len.to_ne_bytes());
+        self.buffer[8..12].copy_from_slice(&seq.to_ne_bytes());
+        self.buffer
+    }
+}
+
+/// Netlink messages and attributes are aligned to 4 bytes
+const fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn u32_at(buffer: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes([
+        buffer[offset],
+        buffer[offset + 1],
+        buffer[offset + 2],
+        buffer[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nested_attributes() {
+        let mut message = Message::new(RTM_NEWLINK, NLM_F_CREATE);
+        message.ifinfomsg(0, 0);
+        let linkinfo = message.begin(IFLA_LINKINFO);
+        message.attr_str(IFLA_INFO_KIND, "veth");
+        message.end(linkinfo);
+        let message = message.finish(7);
+
+        // Header, ifinfomsg, linkinfo header and the padded kind attribute
+        assert_eq!(message.len(), NLMSG_HDRLEN + 16 + 4 + 12);
+        assert_eq!(u32_at(&message, 0) as usize, message.len());
+        assert_eq!(u32_at(&message, 8), 7);
+        let linkinfo = &message[NLMSG_HDRLEN + 16..];
+        assert_eq!(u16::from_ne_bytes([linkinfo[0], linkinfo[1]]), 16);
+        // The length of the kind attribute does not include the padding
+        assert_eq!(u16::from_ne_bytes([linkinfo[4], linkinfo[5]]), 9);
+        assert_eq!(&linkinfo[8..13], b"veth\0");
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs::Permissions, os::unix::prelude::PermissionsExt};
+use std::{fs::Permissions, os::unix::prelude::PermissionsExt, path::Path};
 
 use crate::npk::manifest::{mount::Mount, Manifest};
 use anyhow::{Context, Result};
@@ -6,7 +6,7 @@ use log::debug;
 use nix::unistd;
 use tokio::fs;
 
-use super::config;
+use super::{config, core_dumps};
 
 /// Permissions for the persist directory.
 const PERSIST_DIR_PERMISSIONS: u32 = 0o700;
@@ -21,36 +21,69 @@ pub(crate) async fn setup(config: &config::Config, manifest: &Manifest) -> Resul
         // The directory is the data directory + the container name. The version is not included
         // because the persist directory is shared between versions.
         let dir = config.data_dir.join(manifest.name.as_ref());
+        create_dir(&dir, manifest).await?;
+    }
+
+    // Core dumps are only collected if configured in the runtime configuration
+    if let Some(core_dumps) = config.core_dumps.as_ref().filter(|_| {
+        manifest
+            .mounts
+            .iter()
+            .any(|(_, mount)| matches!(mount, Mount::CoreDumps))
+    }) {
+        let dir = core_dumps::dir(core_dumps, &manifest.name);
+        create_dir(&dir, manifest).await?;
+    }
+
+    Ok(())
+}
 
-        // mkdir
-        if !dir.exists() {
-            debug!("Creating {}", dir.display());
-            fs::create_dir_all(&dir)
-                .await
-                .with_context(|| format!("failed to create directory {}", dir.display()))?;
-        }
-
-        // chmod
-        debug!(
-            "Setting directory mode {} on {}",
-            umask::Mode::from(PERSIST_DIR_PERMISSIONS),
-            dir.display(),
-        );
-        fs::set_permissions(&dir, Permissions::from_mode(PERSIST_DIR_PERMISSIONS))
+/// Create `dir` if it does not exist and hand it over to the user and group of `manifest`
+async fn create_dir(dir: &Path, manifest: &Manifest) -> Result<()> {
+    // mkdir
+    if !dir.exists() {
+        debug!("Creating {}", dir.display());
+        fs::create_dir_all(dir)
             .await
-            .with_context(|| format!("failed to set permission on {}", dir.display()))?;
-
-        // chown
-        let uid = unistd::Uid::from_raw(manifest.uid.into());
-        let gid = unistd::Gid::from_raw(manifest.gid.into());
-        debug!("Chowning {} to {uid}:{gid}", dir.display());
-        unistd::chown(dir.as_os_str(), Some(uid), Some(gid)).context(format!(
-            "failed to chown {} to {}:{}",
-            dir.display(),
-            uid,
-            gid
-        ))?;
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
     }
 
+    // chmod
+    debug!(
+        "Setting directory mode {} on {}",
+        umask::Mode::from(PERSIST_DIR_PERMISSIONS),
+        dir.display(),
+    );
+    fs::set_permissions(dir, Permissions::from_mode(PERSIST_DIR_PERMISSIONS))
+        .await
+        .with_context(|| format!("failed to set permission on {}", dir.display()))?;
+
+    // chown
+    let (uid, gid) = owner(manifest)?;
+    debug!("Chowning {} to {uid}:{gid}", dir.display());
+    unistd::chown(dir.as_os_str(), Some(uid), Some(gid)).context(format!(
+        "failed to chown {} to {}:{}",
+        dir.display(),
+        uid,
+        gid
+    ))?;
+
     Ok(())
 }
+
+/// Host user and group of `manifest`. The ids of a container with a user namespace are
+/// mapped to the host ids.
+pub(crate) fn owner(manifest: &Manifest) -> Result<(unistd::Uid, unistd::Gid)> {
+    let (uid, gid) = match &manifest.user_namespace {
+        Some(user_namespace) => (
+            user_namespace
+                .host_uid(manifest.uid.into())
+                .context("uid is not mapped")?,
+            user_namespace
+                .host_gid(manifest.gid.into())
+                .context("gid is not mapped")?,
+        ),
+        None => (manifest.uid.into(), manifest.gid.into()),
+    };
+    Ok((unistd::Uid::from_raw(uid), unistd::Gid::from_raw(gid)))
+}
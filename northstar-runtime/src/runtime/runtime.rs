@@ -2,44 +2,64 @@ use crate::{
     api::model::Container,
     runtime::{
         cgroups,
-        config::Config,
+        config::{Config, ConsoleGlobal},
         console,
-        events::{ContainerEvent, Event},
+        error::Error as RuntimeError,
+        events::{ContainerEvent, Event, EventTx, ExitReason},
         exit_status::ExitStatus,
         fork,
         fork::Streams,
         ipc::AsyncFramedUnixStream,
+        network,
         state::State,
     },
 };
+use anyhow::{anyhow, Context};
 use async_stream::stream;
 use futures::{
     future::{ready, Either},
     FutureExt, StreamExt,
 };
-use log::{debug, info};
+use log::{debug, info, warn};
 use nix::{
     sys::wait::{waitpid, WaitStatus},
     unistd,
 };
-use std::{future::Future, path::Path};
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+};
 use sync::mpsc;
 use thiserror::Error;
 use tokio::{
     pin, select,
-    sync::{self, broadcast},
+    sync::{self, broadcast, oneshot},
     task::{self, JoinHandle},
 };
 use tokio_util::sync::{CancellationToken, DropGuard};
 
 pub(crate) type NotificationTx = broadcast::Sender<(Container, ContainerEvent)>;
 pub(crate) type Pid = u32;
+type ReloadReply = oneshot::Sender<Result<(), RuntimeError>>;
 
 /// Runtime error
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub struct Error(#[from] anyhow::Error);
 
+impl Error {
+    /// Returns true if the runtime stopped because a critical container requested a
+    /// restart of the runtime. The caller is expected to start the runtime again.
+    pub fn is_restart(&self) -> bool {
+        self.0.downcast_ref::<RestartRequested>().is_some()
+    }
+}
+
+/// The runtime stopped on request of a critical container
+#[derive(Error, Debug)]
+#[error("restart requested by critical container {0}")]
+struct RestartRequested(Container);
+
 /// Runtime handle
 #[allow(clippy::large_enum_variant)]
 pub enum Runtime {
@@ -47,6 +67,8 @@ pub enum Runtime {
     Created {
         /// Runtime configuration
         config: Config,
+        /// File the configuration is reloaded from
+        config_file: Option<PathBuf>,
         /// Forker pid and streams
         forker: (Pid, Streams),
     },
@@ -56,6 +78,8 @@ pub enum Runtime {
         guard: DropGuard,
         /// Runtime task
         task: JoinHandle<anyhow::Result<()>>,
+        /// Reload requests to the main loop
+        reload_tx: mpsc::Sender<ReloadReply>,
     },
 }
 
@@ -64,13 +88,36 @@ impl Runtime {
     pub fn new(config: Config) -> Result<Runtime, Error> {
         config.check()?;
         let forker = fork::start()?;
-        Ok(Runtime::Created { config, forker })
+        Ok(Runtime::Created {
+            config,
+            config_file: None,
+            forker,
+        })
+    }
+
+    /// Set the file the configuration is read from on a reload
+    pub fn with_config_file(self, path: PathBuf) -> Runtime {
+        match self {
+            Runtime::Created { config, forker, .. } => Runtime::Created {
+                config,
+                config_file: Some(path),
+                forker,
+            },
+            Runtime::Running { .. } => {
+                panic!("Runtime::with_config_file called on a running runtime")
+            }
+        }
     }
 
     /// Start runtime with configuration `config`
     pub async fn start(self) -> Result<Runtime, Error> {
-        let (config, forker) = if let Runtime::Created { config, forker } = self {
-            (config, forker)
+        let (config, config_file, forker) = if let Runtime::Created {
+            config,
+            config_file,
+            forker,
+        } = self
+        {
+            (config, config_file, forker)
         } else {
             panic!("Runtime::start called on a running runtime");
         };
@@ -78,15 +125,35 @@ impl Runtime {
         let token = CancellationToken::new();
         let guard = token.clone().drop_guard();
 
+        let (reload_tx, reload_rx) = mpsc::channel(1);
+
         // Start a task that drives the main loop and wait for shutdown results
-        let task = task::spawn(run(config, token, forker));
+        let task = task::spawn(run(config, config_file, token, forker, reload_rx));
 
-        Ok(Runtime::Running { guard, task })
+        Ok(Runtime::Running {
+            guard,
+            task,
+            reload_tx,
+        })
+    }
+
+    /// Reload the configuration from the configuration file and apply the changes
+    pub async fn reload_config(&self) -> Result<(), Error> {
+        match self {
+            Runtime::Running { reload_tx, .. } => {
+                let (tx, rx) = oneshot::channel();
+                reload_tx.send(tx).await.context("runtime is stopped")?;
+                rx.await
+                    .context("runtime is stopped")?
+                    .map_err(|e| anyhow!(e).into())
+            }
+            Runtime::Created { .. } => panic!("Runtime::reload_config called on a stopped runtime"),
+        }
     }
 
     /// Stop the runtime and wait for the termination
     pub fn shutdown(self) -> impl Future<Output = Result<(), Error>> {
-        if let Runtime::Running { guard, task } = self {
+        if let Runtime::Running { guard, task, .. } = self {
             drop(guard);
             Either::Left({
                 task.then(|n| match n {
@@ -114,13 +181,21 @@ impl Runtime {
 /// Main loop
 async fn run(
     mut config: Config,
+    config_file: Option<PathBuf>,
     token: CancellationToken,
     forker: (Pid, Streams),
+    mut reload_rx: mpsc::Receiver<ReloadReply>,
 ) -> anyhow::Result<()> {
     // Setup root cgroup(s)
     let cgroup = Path::new(config.cgroup.as_str()).to_owned();
     cgroups::init(&cgroup).await?;
 
+    // Setup the bridge (if any)
+    let bridge = match &config.network {
+        Some(network) => Some(network::init(network)?),
+        None => None,
+    };
+
     // Join forker
     let (forker_pid, forker_channels) = forker;
     let mut join_forker = task::spawn_blocking(move || {
@@ -145,17 +220,8 @@ async fn run(
     let (notification_tx, _) = sync::broadcast::channel(config.notification_buffer_size);
 
     // Initialize the console if bind address configured.
-    let console = if let Some(global) = config.console.global.take() {
-        let mut console = console::Console::new(event_tx.clone(), notification_tx.clone());
-        let options = global.options.unwrap_or_default();
-        let permissions = global.permissions;
-        console
-            .listen(&global.bind, options.into(), permissions.into())
-            .await?;
-        Some(console)
-    } else {
-        None
-    };
+    let mut global = config.console.global.take();
+    let mut console = start_console(global.as_ref(), &event_tx, &notification_tx).await?;
 
     // Destructure the forker stream handle: Merge the exit notification into the main channel
     // and create a handle to the foker process to be used in the state module;
@@ -172,20 +238,29 @@ async fn run(
         loop {
             select! {
                 Some(event) = event_rx.recv() => yield event,
-                Ok(Some(fork::Notification::Exit { container, exit_status })) = exit_notifications.recv() => {
-                    let event = ContainerEvent::Exit(exit_status);
-                    yield Event::Container(container, event);
-                }
+                Some(reply) = reload_rx.recv() => yield Event::ReloadConfig(reply),
+                Ok(Some(notification)) = exit_notifications.recv() => match notification {
+                    fork::Notification::Exit { container, exit_status } => {
+                        let event = ContainerEvent::Exit(exit_status, ExitReason::Exited);
+                        yield Event::Container(container, event);
+                    }
+                    fork::Notification::SpawnExit { container, pid, exit_status } => {
+                        yield Event::ExecExit(container, pid, exit_status);
+                    }
+                },
                 else => unimplemented!(),
             }
         }
     };
     pin!(event_rx);
 
-    let mut state = State::new(config, event_tx.clone(), notification_tx, forker).await?;
+    let mut state = State::new(config, event_tx.clone(), notification_tx.clone(), forker).await?;
 
     info!("Runtime up and running");
 
+    // Critical container that requested a restart of the runtime
+    let mut restart = None;
+
     // Enter main loop
     loop {
         tokio::select! {
@@ -199,14 +274,92 @@ async fn run(
                     // The runtime os commanded to shut down and exit.
                     Event::Shutdown => {
                         debug!("Shutting down Northstar runtime");
-                        if let Some(console) = console {
+                        if let Some(console) = console.take() {
                             debug!("Shutting down console");
                             console.shutdown().await?;
                         }
                         break state.shutdown(event_rx).await;
                     }
+                    // A critical container requested a restart of the runtime
+                    Event::RestartRuntime(container) => {
+                        info!("Restarting Northstar runtime on request of {}", container);
+                        if let Some(console) = console.take() {
+                            debug!("Shutting down console");
+                            console.shutdown().await?;
+                        }
+                        restart = Some(container);
+                        break state.shutdown(event_rx).await;
+                    }
+                    // Reload the configuration file
+                    Event::ReloadConfig(reply) => {
+                        let result = match &config_file {
+                            Some(config_file) => reload_config(config_file, &mut state).await,
+                            None => Err(RuntimeError::Configuration("no configuration file".into())),
+                        };
+                        match result {
+                            Ok(mut config) => {
+                                reply.send(Ok(())).ok();
+                                // Restart the console after the reply because the reload might
+                                // be requested by one of its connections
+                                let reloaded = config.console.global.take();
+                                if reloaded != global {
+                                    info!("Restarting console");
+                                    if let Some(console) = console.take() {
+                                        console.shutdown().await?;
+                                    }
+                                    console = match start_console(reloaded.as_ref(), &event_tx, &notification_tx).await {
+                                        Ok(console) => {
+                                            global = reloaded;
+                                            console
+                                        }
+                                        // Keep the previous listener in order to stay reachable
+                                        Err(e) => {
+                                            warn!("Failed to start console: {e:#}. Restoring the previous console.");
+                                            start_console(global.as_ref(), &event_tx, &notification_tx)
+                                                .await
+                                                .map_err(|e| warn!("Failed to start console: {e:#}"))
+                                                .unwrap_or_default()
+                                        }
+                                    };
+                                }
+                            }
+                            Err(e) => {
+                                warn!("Failed to reload configuration: {e}");
+                                reply.send(Err(e)).ok();
+                            }
+                        }
+                        Ok(())
+                    }
                     // Container event
                     Event::Container(container, event) => state.on_event(&container, &event, false).await,
+                    // Scheduled container restart
+                    Event::Restart(container) => state.on_restart(&container).await,
+                    // Heartbeat from a container console
+                    Event::Heartbeat(container) => state.on_heartbeat(&container),
+                    // Health probe of a container
+                    Event::Probe(container, path, args, reply) => state.on_probe(&container, path, args, reply).await,
+                    // Readiness or status from a container console
+                    Event::Notify(container, notify) => state.on_notify(&container, notify).await,
+                    // Ready timeout of a container elapsed
+                    Event::ReadyTimeout(container) => state.on_ready_timeout(&container).await,
+                    // Maximum run time of a container elapsed
+                    Event::MaxRuntime(container) => state.on_max_runtime(&container).await,
+                    // Stop timeout of a container elapsed
+                    Event::StopTimeout(container) => state.on_stop_timeout(&container).await,
+                    // Process spawned in a container exited
+                    Event::ExecExit(container, pid, exit_status) => state.on_exec_exit(&container, pid, exit_status),
+                    // Probation period of an updated container elapsed
+                    Event::Probation(container) => state.on_probation(&container).await,
+                    // The reboot for a critical container failed. Exit like without a critical action.
+                    Event::RebootFailed(container, exit_status) => Err(RuntimeError::CriticalContainer(container, exit_status)),
+                    // Hooks of a run finished
+                    Event::Hooks(stage, results, action) => state.on_hooks(stage, results, action).await,
+                    // Start of a hook container
+                    Event::HookContainer(request) => state.on_hook_container(request).await,
+                    // Timeout of a hook container elapsed
+                    Event::HookTimeout(container) => state.on_hook_timeout(&container).await,
+                    // Scheduled run of a container is due
+                    Event::Schedule(container) => state.on_schedule(&container).await,
                 } {
                     break Err(e);
                 }
@@ -224,7 +377,50 @@ async fn run(
         .await
         .expect("failed to shutdown cgroups");
 
+    if let Some(bridge) = bridge {
+        info!("Shutting down network");
+        if let Err(e) = bridge.destroy() {
+            warn!("Failed to shutdown network: {:#}", e);
+        }
+    }
+
     info!("Shutdown complete");
 
-    Ok(())
+    match restart {
+        Some(container) => Err(RestartRequested(container).into()),
+        None => Ok(()),
+    }
+}
+
+/// Start the global console if configured
+async fn start_console(
+    global: Option<&ConsoleGlobal>,
+    event_tx: &EventTx,
+    notification_tx: &NotificationTx,
+) -> anyhow::Result<Option<console::Console>> {
+    if let Some(global) = global {
+        let mut console = console::Console::new(event_tx.clone(), notification_tx.clone());
+        let options = global.options.clone().unwrap_or_default();
+        let permissions = global.permissions.clone();
+        console
+            .listen(&global.bind, options.into(), permissions.into())
+            .await?;
+        Ok(Some(console))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Read the configuration from `path` and apply it to `state`. Returns the new
+/// configuration.
+async fn reload_config(path: &Path, state: &mut State) -> Result<Config, RuntimeError> {
+    info!("Reloading configuration from {}", path.display());
+    let config = Config::load(path)
+        .and_then(|config| config.check().map(|_| config))
+        .map_err(|e| RuntimeError::Configuration(format!("{e:#}")))?;
+
+    let mut state_config = config.clone();
+    state_config.console.global = None;
+    state.reload(state_config).await?;
+    Ok(config)
 }
@@ -18,7 +18,10 @@ use tokio::fs;
 
 use crate::{
     common::{container::Container, non_nul_string::NonNulString},
-    npk::manifest::socket::{Socket, Type},
+    npk::manifest::{
+        socket::{Socket, Type},
+        user_namespace::UserNamespace,
+    },
 };
 
 /// Socket set.
@@ -39,11 +42,13 @@ impl Sockets {
     }
 }
 
-/// Open unix sockets for a container.
+/// Open unix sockets for a container. The owner of the sockets of a container with a user
+/// namespace is mapped to the host ids.
 pub(crate) async fn open(
     socket_dir: &Path,
     container: &Container,
     socket_configuration: &HashMap<NonNulString, Socket>,
+    user_namespace: Option<&UserNamespace>,
 ) -> Result<(Vec<OwnedFd>, Sockets)> {
     let mut fds = Vec::with_capacity(socket_configuration.len());
     let mut sockets = Vec::with_capacity(socket_configuration.len());
@@ -51,15 +56,10 @@ pub(crate) async fn open(
     for (name, descriptor) in socket_configuration
         .iter()
         .sorted_by_key(|(name, _)| name.as_str())
-        .map(|(name, descriptor)| {
-            (
-                format!("{}:{}:{name}", container.name(), container.version()),
-                descriptor,
-            )
-        })
     {
         let ty = &descriptor.r#type;
-        let path = socket_dir.join(&name);
+        let path = path(socket_dir, container, name);
+        let name = path.display().to_string();
 
         // Remove stale sockets.
         if path.exists() {
@@ -101,22 +101,30 @@ pub(crate) async fn open(
 
         sockets.push(path);
 
-        if descriptor.uid.is_some() || descriptor.gid.is_some() {
-            debug!(
-                "Setting socket ownership {}:{} on {name}",
+        let (uid, gid) = match user_namespace {
+            Some(user_namespace) => (
                 descriptor
                     .uid
-                    .map(|d| d.to_string())
-                    .unwrap_or_else(|| "-".into()),
+                    .map(|uid| user_namespace.host_uid(uid).context("uid is not mapped"))
+                    .transpose()?,
                 descriptor
                     .gid
-                    .map(|d| d.to_string())
-                    .unwrap_or_else(|| "-".into()),
+                    .map(|gid| user_namespace.host_gid(gid).context("gid is not mapped"))
+                    .transpose()?,
+            ),
+            None => (descriptor.uid, descriptor.gid),
+        };
+
+        if uid.is_some() || gid.is_some() {
+            debug!(
+                "Setting socket ownership {}:{} on {name}",
+                uid.map(|d| d.to_string()).unwrap_or_else(|| "-".into()),
+                gid.map(|d| d.to_string()).unwrap_or_else(|| "-".into()),
             );
             fchown(
                 socket.as_raw_fd(),
-                descriptor.uid.map(Uid::from_raw),
-                descriptor.gid.map(Gid::from_raw),
+                uid.map(Uid::from_raw),
+                gid.map(Gid::from_raw),
             )
             .context("failed to set socket ownership")?;
         }
@@ -126,3 +134,12 @@ pub(crate) async fn open(
 
     Ok((fds, Sockets { sockets }))
 }
+
+/// Path of the socket `name` of `container`.
+pub(crate) fn path(socket_dir: &Path, container: &Container, name: &str) -> PathBuf {
+    socket_dir.join(format!(
+        "{}:{}:{name}",
+        container.name(),
+        container.version()
+    ))
+}
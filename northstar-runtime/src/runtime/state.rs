@@ -3,26 +3,44 @@ use crate::{
     common::{container::Container, name::Name, non_nul_string::NonNulString, version::VersionReq},
     npk::manifest::{
         autostart::Autostart,
+        critical::Critical,
+        hooks::{Hook, Stage},
         mount::{Mount, Resource},
+        network::Network,
+        ready::Ready,
+        restart::{Policy as RestartPolicy, Restart},
         Manifest,
     },
     runtime::{
+        budgets::{self, Demand},
         cgroups,
-        config::{Config, RepositoryType},
+        config::{self, Config, RepositoryType},
         console::{Console, Peer, Request},
+        core_dumps,
+        dependency::{self, Graph},
         env,
         error::Error,
-        events::{CGroupEvent, ContainerEvent, Event, EventTx},
+        events::{
+            CGroupEvent, ContainerEvent, CriticalAction, Event, EventTx, ExitReason, Health,
+            ScheduledRun, UpdateProgress,
+        },
         exit_status::ExitStatus,
         fork::Forker,
+        health,
+        history::History,
+        hooks::{self, Action},
         io,
-        io::ContainerIo,
+        io::{Attachment, ContainerIo, Devpts, ExecIo, Terminal},
+        journal::Journal,
+        logs::{self, LogBuffer},
         mount::MountControl,
+        network::{self, Namespace, Veth},
         persistence,
         repository::{DirRepository, MemRepository, Npk, RepositoryId},
         runtime::{NotificationTx, Pid},
         sockets,
         sockets::Sockets,
+        uts::{self, Uts},
     },
 };
 use anyhow::{Context, Result};
@@ -34,6 +52,7 @@ use futures::{
 use itertools::Itertools;
 use log::{debug, error, info, warn};
 use nix::sys::signal::Signal;
+use rand_core::{OsRng, RngCore};
 use std::{
     collections::{HashMap, HashSet},
     convert::TryFrom,
@@ -42,20 +61,27 @@ use std::{
     os::unix::{net::UnixStream as StdUnixStream, prelude::OwnedFd},
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
     fs,
     net::UnixStream,
-    pin,
-    sync::{mpsc, oneshot},
+    pin, process, select,
+    sync::{broadcast, mpsc, oneshot},
     task::{self},
     time,
 };
-use tokio_util::sync::CancellationToken;
+use tokio_util::sync::{CancellationToken, DropGuard};
 
 /// Repository
 type Repository = Box<dyn super::repository::Repository + Send + Sync>;
 
+/// Time granted to a container to exit after the stop signal if not set in the manifest
+const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time granted to a container to report its readiness if not set in the manifest
+const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 pub(super) struct State {
     config: Config,
@@ -65,6 +91,14 @@ pub(super) struct State {
     forker: Forker,
     containers: HashMap<Container, ContainerState>,
     repositories: HashMap<RepositoryId, Repository>,
+    /// Updates in progress by container name
+    updates: HashMap<Name, Update>,
+    /// Desired state journal if enabled
+    journal: Option<Journal>,
+    /// Terminated runs of the containers
+    history: History,
+    /// Named network namespaces created by the runtime
+    network_namespaces: Vec<Namespace>,
     /// Is SELinux enabled on the host.
     selinux_enabled: bool,
 }
@@ -77,6 +111,16 @@ pub(super) struct ContainerState {
     pub root: Option<PathBuf>,
     /// Process information when started
     pub process: Option<ContainerContext>,
+    /// Restart bookkeeping
+    pub restarts: Restarts,
+    /// Next scheduled run if a schedule is configured
+    schedule: Option<Scheduled>,
+    /// Start that waits for dependencies to become ready or for its hooks
+    pending_start: Option<PendingStart>,
+    /// Sender of the exit status if the container is started as hook container
+    hook_exit: Option<oneshot::Sender<ExitStatus>>,
+    /// Captured output. Created on the first start.
+    pub logs: Option<Arc<LogBuffer>>,
 }
 
 impl ContainerState {
@@ -85,12 +129,328 @@ impl ContainerState {
     }
 }
 
+/// Consecutive restarts and the pending restart of a container
+#[derive(Debug, Default)]
+pub(super) struct Restarts {
+    /// Number of consecutive restarts
+    retries: u32,
+    /// Number of consecutive restarts by the critical policy
+    critical: u32,
+    /// Scheduled restart
+    pending: Option<PendingRestart>,
+}
+
+#[derive(Debug)]
+struct PendingRestart {
+    /// Time of the restart
+    at: time::Instant,
+    /// Arguments of the previous start
+    arguments: StartArguments,
+    /// Cancels the restart timer when dropped
+    _timer: DropGuard,
+}
+
+/// Next scheduled run of a container
+#[derive(Debug)]
+struct Scheduled {
+    /// Time of the next run
+    next: SystemTime,
+    /// Set if a run is deferred until the container exited
+    deferred: bool,
+    /// Cancels the schedule timer when dropped
+    _timer: DropGuard,
+}
+
+/// Start of a container that waits for its dependencies to become ready or for its
+/// pre mount and pre start hooks
+#[derive(Debug, Default)]
+struct PendingStart {
+    /// Arguments of the start
+    arguments: StartArguments,
+    /// Set while the hooks of the start run
+    hooks: bool,
+    /// Replies sent once the container is started
+    replies: Vec<oneshot::Sender<model::Response>>,
+    /// Replies sent once the container is ready
+    ready_replies: Vec<oneshot::Sender<model::Response>>,
+}
+
+/// Start of a container whose resources are set up. The process is created without
+/// access to the state in order to create the processes of independent containers
+/// concurrently.
+#[derive(Debug)]
+struct Launch {
+    container: Container,
+    manifest: Manifest,
+    arguments: StartArguments,
+    start: time::Instant,
+    init: NonNulString,
+    args: Vec<NonNulString>,
+    env: Vec<NonNulString>,
+    /// Index of the bridge address if connected to the bridge
+    address: Option<u32>,
+    /// Host ids of the container
+    owner: (nix::unistd::Uid, nix::unistd::Gid),
+    io: [OwnedFd; 3],
+    console: Option<OwnedFd>,
+    socket_fds: Vec<OwnedFd>,
+    /// Installed containers
+    containers: Vec<Container>,
+    setup: Setup,
+}
+
+/// Resources of a starting container that are moved into its context
+#[derive(Debug)]
+struct Setup {
+    stop: CancellationToken,
+    journal_start: model::JournalStart,
+    capture: logs::Capture,
+    terminal: Option<Terminal>,
+    devpts: Option<Devpts>,
+    sockets: Sockets,
+    uts: Option<Uts>,
+    resources: HashSet<Container>,
+}
+
+/// Container whose process is created and executed
+struct Spawned {
+    container: Container,
+    manifest: Manifest,
+    arguments: StartArguments,
+    start: time::Instant,
+    pid: Pid,
+    network: Option<Veth>,
+    cgroups: cgroups::CGroups,
+    setup: Setup,
+}
+
+impl Launch {
+    /// Create the process, connect it to the bridge, create its cgroups and execute init
+    async fn spawn(
+        self,
+        forker: &Forker,
+        config: &Config,
+        events_tx: EventTx,
+        selinux: bool,
+    ) -> Result<Spawned, Error> {
+        let Launch {
+            container,
+            manifest,
+            arguments,
+            start,
+            init,
+            args,
+            env,
+            address,
+            owner,
+            io,
+            console,
+            socket_fds,
+            containers,
+            setup,
+        } = self;
+
+        // Create container.
+        let pid = match forker
+            .create(
+                &container,
+                config,
+                &manifest,
+                io,
+                console,
+                socket_fds,
+                containers.iter(),
+                selinux,
+            )
+            .await
+        {
+            Ok(pid) => pid,
+            Err(e) => {
+                setup.stop.cancel();
+                return Err(e);
+            }
+        };
+
+        // Debug
+        super::debug::start(config, &container, pid).await?;
+
+        // Connect the network namespace to the bridge
+        let network = match (address, &config.network) {
+            (Some(address), Some(network)) => {
+                let netns = PathBuf::from(format!("/proc/{pid}/ns/net"));
+                match network::setup(network, address, &netns) {
+                    Ok(veth) => Some(veth),
+                    Err(e) => {
+                        warn!("Failed to setup network of {}: {:#}", container, e);
+                        let pid = nix::unistd::Pid::from_raw(pid as i32);
+                        nix::sys::signal::kill(pid, Some(Signal::SIGKILL)).ok();
+                        setup.stop.cancel();
+                        return Err(e.into());
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        // CGroups
+        let cgroups = {
+            let cgroups = manifest.cgroups.clone().unwrap_or_default();
+
+            // Creating a cgroup is a northstar internal thing. If it fails it's not recoverable.
+            cgroups::CGroups::new(&config.cgroup, events_tx, &container, &cgroups, owner, pid)
+                .await
+                .expect("failed to create cgroup")
+        };
+
+        debug!("Container {} init is {:?}", container, init);
+        debug!(
+            "Container {} argv is \"{}\"",
+            container,
+            args.iter().join(" ")
+        );
+        debug!(
+            "Container {} env is \"{}\"",
+            container,
+            env.iter().join(", ")
+        );
+
+        // Send exec request to launcher
+        if let Err(e) = forker.exec(container.clone(), init, args, env).await {
+            warn!("Failed to exec {} ({}): {}", container, pid, e);
+            setup.stop.cancel();
+            cgroups.destroy().await;
+            if let Some(veth) = network {
+                veth.destroy();
+            }
+            return Err(e);
+        }
+
+        Ok(Spawned {
+            container,
+            manifest,
+            arguments,
+            start,
+            pid,
+            network,
+            cgroups,
+            setup,
+        })
+    }
+}
+
+/// Readiness that is not yet reported by a container with `ready: notify`
+#[derive(Debug)]
+struct PendingReady {
+    /// Replies sent once the container is ready
+    replies: Vec<oneshot::Sender<model::Response>>,
+    /// Cancels the ready timer when dropped
+    _timer: DropGuard,
+}
+
+/// Stop request in progress
+#[derive(Debug)]
+struct Stopping {
+    /// Replies sent once the container exited
+    replies: Vec<oneshot::Sender<model::Response>>,
+    /// Cancels the stop timer when dropped
+    _timer: DropGuard,
+}
+
+/// Update of a started container to a new version
+#[derive(Debug)]
+struct Update {
+    from: Container,
+    to: Container,
+    /// Arguments `from` was started with. Used for the start of `to` and the rollback.
+    arguments: StartArguments,
+    probation: Duration,
+    phase: UpdatePhase,
+}
+
+#[derive(Debug)]
+enum UpdatePhase {
+    /// The previous version is stopped
+    Stopping,
+    /// The new version is started
+    Probation {
+        /// Cancels the probation timer when dropped
+        _timer: DropGuard,
+    },
+    /// The new version failed and is stopped
+    RollingBack,
+}
+
+/// Optional arguments of a container start
+#[derive(Clone, Debug, Default)]
+struct StartArguments {
+    /// Init that overrides the init of the manifest
+    init: Option<NonNulString>,
+    /// Arguments that override the arguments of the manifest
+    args: Vec<NonNulString>,
+    /// Environment that overrides the environment of the manifest
+    env: HashMap<NonNulString, NonNulString>,
+    /// Maximum run time that overrides the maximum run time of the manifest
+    max_runtime: Option<Duration>,
+    /// Set if the container is started as hook container. Hook containers do not run hooks.
+    hook: bool,
+}
+
+impl From<&StartArguments> for model::JournalStart {
+    fn from(arguments: &StartArguments) -> Self {
+        model::JournalStart {
+            init: arguments.init.clone(),
+            arguments: arguments.args.clone(),
+            environment: arguments.env.clone(),
+            max_runtime: arguments
+                .max_runtime
+                .map(|max_runtime| max_runtime.as_secs()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct ContainerContext {
     pid: Pid,
     started: time::Instant,
+    /// Arguments the container was started with
+    arguments: StartArguments,
+    /// Set if the process is terminated on request
+    killed: bool,
+    /// Signal of the last kill request that does not necessarily terminate the process.
+    /// The process counts as terminated on request if it is terminated by this signal.
+    kill_signal: Option<Signal>,
+    /// Health monitor if configured
+    health: Option<health::Monitor>,
+    /// Set if the container is stopped
+    stopping: Option<Stopping>,
+    /// Set if the processes of the container are frozen
+    paused: bool,
+    /// Set if the container is ready
+    ready: bool,
+    /// Last status reported by the container
+    status: Option<String>,
+    /// Set until a container with `ready: notify` reports its readiness
+    pending_ready: Option<PendingReady>,
+    /// Stops the container once its maximum run time elapsed. Cancelled when dropped.
+    _max_runtime: Option<DropGuard>,
+    /// Set if the runtime terminated the container for a reason other than a stop
+    exit_reason: Option<ExitReason>,
+    /// Number of processes killed by the OOM killer
+    oom_kills: u64,
+    /// Processes spawned with exec requests and the senders of their exit status
+    execs: HashMap<Pid, oneshot::Sender<ExitStatus>>,
+    /// Lines written by the container. Followers subscribe here.
+    logs: broadcast::Sender<model::LogLine>,
+    /// Pseudo terminal if configured in the manifest
+    terminal: Option<Terminal>,
+    /// Devpts instance the terminals are allocated from
+    devpts: Option<Devpts>,
     cgroups: cgroups::CGroups,
     sockets: Sockets,
+    /// Tmpfs with the generated /etc/hostname and /etc/hosts
+    uts: Option<Uts>,
+    /// Veth pair if the container is connected to the bridge
+    network: Option<Veth>,
     stop: CancellationToken,
     /// Resources used by this container. This list differs from
     /// manifest because the manifest just containers version
@@ -105,6 +465,14 @@ impl ContainerContext {
         self.cgroups.destroy().await;
 
         self.sockets.destroy().await;
+
+        // Unmount the uts tmpfs and the devpts instance
+        drop(self.uts);
+        drop(self.devpts);
+
+        if let Some(veth) = self.network {
+            veth.destroy();
+        }
     }
 }
 
@@ -124,6 +492,22 @@ impl State {
                 .await
                 .expect("failed to initialize mount control"),
         );
+        let journal = if config.journal {
+            Some(Journal::load(&config.data_dir).await)
+        } else {
+            None
+        };
+        let history = if config.history.persist {
+            History::load(&config.data_dir).await
+        } else {
+            History::default()
+        };
+        if config.core_dumps.is_some() {
+            core_dumps::check_pattern()
+                .await
+                .map_err(|e| Error::Configuration(format!("{e:#}")))?;
+        }
+        let network_namespaces = network::create_namespaces(&config)?;
 
         let mut state = State {
             events_tx,
@@ -133,6 +517,10 @@ impl State {
             config,
             forker,
             mount_control,
+            updates: HashMap::new(),
+            journal,
+            history,
+            network_namespaces,
             selinux_enabled,
         };
 
@@ -145,6 +533,9 @@ impl State {
         // Start containers flagged with autostart
         state.autostart().await?;
 
+        // Restore the state recorded in the journal
+        state.restore().await;
+
         Ok(state)
     }
 
@@ -154,43 +545,244 @@ impl State {
         let mut mount_repositories = HashSet::with_capacity(self.config.repositories.len());
 
         // Build a map of repositories from the configuration
-        for (id, repository) in &self.config.repositories {
-            if repository.mount_on_start {
+        for (id, configuration) in self.config.repositories.clone() {
+            if configuration.mount_on_start {
                 mount_repositories.insert(id.clone());
             }
 
-            let repository = match &repository.r#type {
-                RepositoryType::Fs { dir } => {
-                    let repository = DirRepository::new(dir, repository).await?;
-                    Box::new(repository) as Repository
+            let repository = load_repository(&configuration).await?;
+            self.add_repository(id, repository);
+        }
+
+        Ok(mount_repositories)
+    }
+
+    /// Add `repository` and the containers it contains
+    fn add_repository(&mut self, id: RepositoryId, repository: Repository) {
+        let mut added = Vec::new();
+        for npk in repository.containers() {
+            let name = npk.manifest().name.clone();
+            let version = npk.manifest().version.clone();
+            let container = Container::new(name, version);
+
+            if let Ok(state) = self.state(&container) {
+                warn!(
+                    "Skipping duplicate container {} which is already loaded from repository {}",
+                    container, state.repository
+                );
+            } else {
+                self.containers.insert(
+                    container.clone(),
+                    ContainerState {
+                        repository: id.clone(),
+                        ..Default::default()
+                    },
+                );
+                added.push(container);
+            }
+        }
+        self.repositories.insert(id, repository);
+
+        for container in added {
+            self.schedule(&container);
+        }
+    }
+
+    /// Apply the reloaded configuration `config`. Repositories that are added, removed or
+    /// changed are applied immediately. All other settings apply to subsequent starts of
+    /// containers. Started containers are not touched. The configuration is left untouched
+    /// if the reload fails.
+    pub(super) async fn reload(&mut self, config: Config) -> Result<(), Error> {
+        self.config
+            .check_reload(&config)
+            .map_err(|e| Error::Configuration(e.to_string()))?;
+        if config.core_dumps.is_some() {
+            core_dumps::check_pattern()
+                .await
+                .map_err(|e| Error::Configuration(format!("{e:#}")))?;
+        }
+
+        // Changed repositories are removed and added again
+        let removed = self
+            .config
+            .repositories
+            .iter()
+            .filter(|(id, repository)| config.repositories.get(*id) != Some(repository))
+            .map(|(id, _)| id.clone())
+            .collect::<HashSet<_>>();
+        let added = config
+            .repositories
+            .iter()
+            .filter(|(id, repository)| self.config.repositories.get(*id) != Some(repository))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        // Containers of removed repositories must not be in use
+        let containers = self
+            .containers
+            .iter()
+            .filter(|(_, state)| removed.contains(&state.repository))
+            .map(|(container, _)| container.clone())
+            .collect::<Vec<_>>();
+        for container in &containers {
+            let state = self.state(container)?;
+            let updating = self
+                .updates
+                .values()
+                .any(|update| &update.from == container || &update.to == container);
+            let used = self
+                .containers
+                .values()
+                .filter_map(|state| state.process.as_ref())
+                .any(|process| process.resources.contains(container));
+            if state.process.is_some() || updating || used {
+                return Err(Error::Configuration(format!(
+                    "repository {} cannot be removed: {} is in use",
+                    state.repository, container
+                )));
+            }
+        }
+
+        // Load the new repositories before anything is changed
+        let mut repositories = Vec::with_capacity(added.len());
+        for id in added {
+            let repository = load_repository(&config.repositories[&id])
+                .await
+                .map_err(|e| {
+                    Error::Configuration(format!("failed to load repository {id}: {e:#}"))
+                })?;
+            repositories.push((id, repository));
+        }
+
+        let mounted = containers
+            .iter()
+            .filter(|container| {
+                self.state(container)
+                    .map(|s| s.is_mounted())
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let results = self.umount_all(&mounted).await;
+        if results.iter().any(Result::is_err) {
+            // Mount the containers unmounted so far in order to restore the previous state
+            let (unmounted, errors): (Vec<_>, Vec<_>) = results.into_iter().partition_result();
+            for result in self.mount_all(&unmounted).await {
+                if let Err(e) = result {
+                    warn!("Failed to remount: {}", e);
                 }
-                RepositoryType::Memory => {
-                    let repository = MemRepository::new(repository).await?;
-                    Box::new(repository) as Repository
+            }
+            return Err(errors.into_iter().next().expect("internal error"));
+        }
+        for container in &containers {
+            self.containers.remove(container);
+        }
+        for id in &removed {
+            info!("Removing repository {id}");
+            self.repositories.remove(id);
+        }
+
+        let mount_repositories = repositories
+            .iter()
+            .filter(|(id, _)| config.repositories[id].mount_on_start)
+            .map(|(id, _)| id.clone())
+            .collect::<HashSet<_>>();
+        for (id, repository) in repositories {
+            info!("Adding repository {id}");
+            self.add_repository(id, repository);
+        }
+
+        self.config = config;
+
+        // The configuration is applied. Containers that fail to mount stay unmounted.
+        if let Err(e) = self.automount(&mount_repositories).await {
+            warn!(
+                "Failed to mount the containers of the added repositories: {:#}",
+                e
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Mount and start the containers according to the journal. Containers that are
+    /// no longer installed are removed from the journal.
+    async fn restore(&mut self) {
+        let entries = match &self.journal {
+            Some(journal) => journal.journal().containers.clone(),
+            None => return,
+        };
+
+        let mut mount = Vec::new();
+        let mut start = Vec::new();
+        for (container, entry) in entries {
+            let state = match self.state(&container) {
+                Ok(state) => state,
+                Err(_) => {
+                    warn!("Removing {} from the journal: not installed", container);
+                    if let Some(journal) = self.journal.as_mut() {
+                        journal.uninstalled(&container).await;
+                    }
+                    continue;
                 }
             };
+            if entry.mounted && !state.is_mounted() {
+                mount.push(container.clone());
+            }
+            if let Some(arguments) = entry.started {
+                if state.process.is_none() {
+                    start.push((container, arguments));
+                }
+            }
+        }
 
-            for npk in repository.containers() {
-                let name = npk.manifest().name.clone();
-                let version = npk.manifest().version.clone();
-                let container = Container::new(name, version);
+        // Containers that are started are mounted with their start
+        mount.retain(|container| !start.iter().any(|(c, _)| c == container));
+        if !mount.is_empty() {
+            info!("Restoring mounts of {}", mount.iter().join(", "));
+            // Failures are logged by mount_all
+            self.mount_hooked(&mount).await;
+        }
 
-                if let Ok(state) = self.state(&container) {
-                    warn!("Skipping duplicate container {} which is already loaded from repository {}", container, state.repository);
-                } else {
-                    self.containers.insert(
-                        container,
-                        ContainerState {
-                            repository: id.clone(),
-                            ..Default::default()
-                        },
-                    );
-                }
+        for (container, arguments) in start {
+            // The container might be started as a dependency in the meantime
+            if self
+                .state(&container)
+                .map(|state| state.process.is_some())
+                .unwrap_or(true)
+            {
+                continue;
+            }
+            info!("Restoring start of {}", container);
+            let model::JournalStart {
+                init,
+                arguments,
+                environment,
+                max_runtime,
+            } = arguments;
+            let arguments = StartArguments {
+                init,
+                args: arguments,
+                env: environment,
+                max_runtime: max_runtime.map(Duration::from_secs),
+                ..Default::default()
+            };
+            if let Err(e) = self.start(&container, arguments).await {
+                warn!("Failed to restore start of {}: {}", container, e);
             }
-            self.repositories.insert(id.clone(), repository);
         }
+    }
 
-        Ok(mount_repositories)
+    /// Record a change of the desired state of `container` in the journal if enabled
+    async fn journal<F>(&mut self, container: &Container, f: F)
+    where
+        F: FnOnce(&mut model::JournalEntry),
+    {
+        if let (Some(journal), Some(state)) =
+            (self.journal.as_mut(), self.containers.get(container))
+        {
+            journal.update(container, &state.repository, f).await;
+        }
     }
 
     /// Try to mount all installed continers
@@ -212,7 +804,7 @@ impl State {
             .collect::<Vec<Container>>();
 
         if !containers.is_empty() {
-            for result in self.mount_all(&containers).await {
+            for result in self.mount_hooked(&containers).await {
                 result?;
             }
         }
@@ -220,37 +812,88 @@ impl State {
         Ok(())
     }
 
+    /// Mount `containers` in the background. Containers with pre mount hooks are mounted
+    /// once their hooks passed. Returns the results of the containers without hooks.
+    async fn mount_hooked(&mut self, containers: &[Container]) -> Vec<Result<Container, Error>> {
+        let hooks = self.pre_mount_hooks(containers);
+        let mount = containers
+            .iter()
+            .filter(|container| !hooks.iter().any(|(c, _)| c == *container))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !hooks.is_empty() {
+            let hooked = hooks.iter().map(|(c, _)| c.clone()).collect();
+            self.run_hooks(
+                Stage::PreMount,
+                hooks,
+                Vec::new(),
+                Action::Mount(hooked, None),
+            );
+        }
+        self.mount_all(&mount).await
+    }
+
     async fn autostart(&mut self) -> Result<(), Error> {
         let start = time::Instant::now();
         // List of containers from all repositories with the autostart flag set
-        let mut autostarts = Vec::with_capacity(self.containers.len());
-        // List of containers that need to be mounted
-        let mut to_mount = Vec::with_capacity(self.containers.len());
+        let mut autostarts = HashMap::with_capacity(self.containers.len());
 
-        for (container, state) in self.containers.iter() {
+        for container in self.containers.keys() {
             if let Some(autostart) = self
                 .manifest(container)
                 .expect("internal error")
                 .autostart
                 .as_ref()
             {
-                autostarts.push((container.clone(), autostart.clone()));
-                if !state.is_mounted() {
-                    to_mount.push(container.clone())
-                }
+                autostarts.insert(container.clone(), autostart.clone());
             }
         }
 
-        // Add resources of containers that have the autostart flag set
+        if autostarts.is_empty() {
+            return Ok(());
+        }
+
+        // Dependency graph of all containers to start
+        let mut graph = Graph::with_capacity(autostarts.len());
         for (container, autostart) in &autostarts {
+            match self.dependency_graph(once(container), false) {
+                Ok(dependencies) => graph.extend(dependencies),
+                Err(e) => Self::warn_autostart_failure(container, autostart, e)?,
+            }
+        }
+
+        // Sort the graph into layers of independent containers. Containers that are
+        // part of a cycle cannot be started.
+        let layers = match dependency::layers(&graph) {
+            Ok(layers) => layers,
+            Err(cycle) => {
+                for (container, autostart) in autostarts.iter().filter(|(c, _)| cycle.contains(c)) {
+                    let error = Error::DependencyCycle(container.clone());
+                    Self::warn_autostart_failure(container, autostart, error)?;
+                }
+                graph.retain(|container, _| !cycle.contains(container));
+                dependency::layers(&graph).expect("internal error")
+            }
+        };
+
+        // List of containers that need to be mounted
+        let mut to_mount = HashSet::with_capacity(graph.len());
+
+        // Add the containers and their resources
+        for container in graph.keys() {
+            if !self.state(container)?.is_mounted() {
+                to_mount.insert(container.clone());
+            }
             let manifest = self.manifest(container)?;
             for mount in manifest.mounts.values() {
                 if let Mount::Resource(Resource { name, version, .. }) = mount {
                     if let Some(resource) =
                         State::match_container(name, version, self.containers.keys())
                     {
-                        to_mount.push(resource.clone());
-                    } else {
+                        if !self.state(resource)?.is_mounted() {
+                            to_mount.insert(resource.clone());
+                        }
+                    } else if let Some(autostart) = autostarts.get(container) {
                         let error = Error::StartContainerMissingResource(
                             container.clone(),
                             name.clone(),
@@ -262,26 +905,92 @@ impl State {
             }
         }
 
+        // Containers with pre mount hooks are mounted with the start once their hooks passed
+        for (container, _) in self.pre_mount_hooks(&Vec::from_iter(to_mount.iter().cloned())) {
+            to_mount.remove(&container);
+        }
+
         // Mount (parallel). Do not care about the result - this normally is fine. If not, the container will not start.
         if !to_mount.is_empty() {
-            self.mount_all(&to_mount).await;
+            self.mount_all(&Vec::from_iter(to_mount)).await;
         }
 
-        if !autostarts.is_empty() {
-            for (container, autostart) in &autostarts {
-                info!("Autostarting {} ({:?})", container, autostart);
-                if let Err(e) = self
-                    .start(container, None, &[], &HashMap::with_capacity(0))
-                    .await
-                {
-                    Self::warn_autostart_failure(container, autostart, e)?
+        // Start the containers layer by layer. A container is not started if one of its
+        // dependencies failed to start. The resources of the containers of a layer are
+        // set up one after another and their processes are created concurrently.
+        let mut failed = HashSet::new();
+        for layer in &layers {
+            let mut failures = Vec::new();
+            let mut launches = Vec::with_capacity(layer.len());
+            for container in layer {
+                let failed_dependency = graph[container].iter().find(|d| failed.contains(*d));
+                let result = if let Some(dependency) = failed_dependency {
+                    // Dependents that still run from an earlier start are stopped
+                    if self.state(container)?.process.is_some() {
+                        info!(
+                            "Stopping {} because {} failed to start",
+                            container, dependency
+                        );
+                        self.stop(container, None).await?;
+                    }
+                    Err(Error::StartContainerFailed(
+                        container.clone(),
+                        format!("dependency {dependency} failed to start"),
+                    ))
+                } else if self.state(container)?.process.is_some() {
+                    Ok(None)
+                } else if !matches!(self.dependencies_ready(container), Ok(true)) {
+                    info!("Deferring {} until its dependencies are ready", container);
+                    self.state_mut(container)?.pending_start = Some(PendingStart::default());
+                    Ok(None)
+                } else {
+                    match autostarts.get(container) {
+                        Some(autostart) => info!("Autostarting {} ({:?})", container, autostart),
+                        None => info!("Starting dependency {}", container),
+                    }
+                    self.prepare_start(container, StartArguments::default(), true, &launches)
+                        .await
+                };
+                match result {
+                    Ok(Some(launch)) => launches.push(launch),
+                    Ok(None) => (),
+                    Err(e) => failures.push((container.clone(), e)),
+                }
+            }
+
+            // Create the processes of the layer concurrently
+            let containers = launches
+                .iter()
+                .map(|launch| launch.container.clone())
+                .collect::<Vec<_>>();
+            let spawned = join_all(launches.into_iter().map(|launch| {
+                let events_tx = self.events_tx.clone();
+                launch.spawn(&self.forker, &self.config, events_tx, self.selinux_enabled)
+            }))
+            .await;
+            for (container, spawned) in containers.into_iter().zip(spawned) {
+                let result = match spawned {
+                    Ok(spawned) => self.finish_start(spawned).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    failures.push((container, e));
+                }
+            }
+
+            for (container, e) in failures {
+                failed.insert(container.clone());
+                match autostarts.get(&container) {
+                    Some(autostart) => Self::warn_autostart_failure(&container, autostart, e)?,
+                    None => warn!("Failed to start dependency {}: {}", container, e),
                 }
             }
-            let duration = start.elapsed();
-            let containers = autostarts.len();
-            info!("Successfully started {containers} container(s) in {duration:?}",);
         }
 
+        let duration = start.elapsed();
+        let containers = graph.len() - failed.len();
+        info!("Successfully started {containers} container(s) in {duration:?}",);
+
         Ok(())
     }
 
@@ -348,35 +1057,341 @@ impl State {
         }
     }
 
-    /// Start a container
+    /// Start a container and the dependencies that are not started yet
     /// `container`: Container to start
-    /// `args_extra`: Optional command line arguments that overwrite the values from the manifest
-    /// `env_extra`: Optional env variables that overwrite the values from the manifest
-    pub(super) async fn start(
+    /// `arguments`: Optional init, arguments, environment and maximum run time that
+    /// overwrite the values from the manifest
+    async fn start(
         &mut self,
         container: &Container,
-        init: Option<NonNulString>,
-        args_extra: &[NonNulString],
-        env_extra: &HashMap<NonNulString, NonNulString>,
+        arguments: StartArguments,
     ) -> Result<(), Error> {
-        let start = time::Instant::now();
-        info!("Trying to start {}", container);
+        let graph = self.dependency_graph(once(container), false)?;
+        let layers =
+            dependency::layers(&graph).map_err(|_| Error::DependencyCycle(container.clone()))?;
+
+        // Start the dependencies in dependency order. The container is the last one.
+        // Containers that wait for a dependency to become ready are started later.
+        for dependency in layers.iter().flatten().filter(|c| *c != container) {
+            let state = self.state(dependency)?;
+            if state.process.is_none() && state.pending_start.is_none() {
+                if !matches!(self.dependencies_ready(dependency), Ok(true)) {
+                    info!(
+                        "Deferring {} as dependency of {} until its dependencies are ready",
+                        dependency, container
+                    );
+                    self.state_mut(dependency)?.pending_start = Some(PendingStart::default());
+                    continue;
+                }
+                info!("Starting {} as dependency of {}", dependency, container);
+                self.start_process(dependency, StartArguments::default(), true)
+                    .await
+                    .map_err(|e| {
+                        let error = format!("dependency {dependency} failed to start: {e}");
+                        Error::StartContainerFailed(container.clone(), error)
+                    })?;
+            }
+        }
 
-        // Check if the container is already running
-        let container_state = self.state(container)?;
-        if container_state.process.is_some() {
-            warn!("Application {} is already running", container);
-            return Err(Error::StartContainerStarted(container.clone()));
+        // The hooks of a previous start are still running
+        if matches!(&self.state(container)?.pending_start, Some(pending) if pending.hooks) {
+            return Ok(());
         }
 
-        // Check if a container with same name but different version is running
-        if let Some(container) = self
-            .containers
-            .iter()
-            .filter_map(|(k, v)| v.process.as_ref().map(|_| k))
-            .find(|c| c.name() == container.name())
+        if self.state(container)?.process.is_none()
+            && !matches!(self.dependencies_ready(container), Ok(true))
         {
-            warn!("Application {} is already running", container);
+            info!(
+                "Deferring the start of {} until its dependencies are ready",
+                container
+            );
+            let pending = self
+                .state_mut(container)?
+                .pending_start
+                .get_or_insert_with(PendingStart::default);
+            pending.arguments = arguments;
+            return Ok(());
+        }
+
+        self.start_process(container, arguments, true).await
+    }
+
+    /// Check if the dependencies of `container` allow its start. A dependency blocks the
+    /// start while its own start is pending or if `ready` is set and it is not ready.
+    /// Returns an error if a dependency is neither started nor pending.
+    fn dependencies_ready(&self, container: &Container) -> Result<bool, String> {
+        let manifest = match self.manifest(container) {
+            Ok(manifest) => manifest,
+            Err(_) => return Ok(true),
+        };
+        let mut ready = true;
+        for (name, dependency) in &manifest.depends_on {
+            let matched = self
+                .match_dependency(name, &dependency.version)
+                .ok_or_else(|| format!("dependency {name} is not installed"))?;
+            let state = self.state(matched).map_err(|e| e.to_string())?;
+            match &state.process {
+                Some(process) => ready &= !dependency.ready || process.ready,
+                None if state.pending_start.is_some() => ready = false,
+                None => return Err(format!("dependency {matched} is not started")),
+            }
+        }
+        Ok(ready)
+    }
+
+    /// Start the containers whose start is pending once their dependencies are ready.
+    /// Pending starts with a dependency that is neither started nor pending fail.
+    async fn start_pending(&mut self) {
+        loop {
+            let next = self
+                .containers
+                .iter()
+                .filter(|(_, state)| matches!(&state.pending_start, Some(p) if !p.hooks))
+                .find_map(|(container, state)| {
+                    if state.process.is_some() {
+                        return Some((container.clone(), Ok(false)));
+                    }
+                    match self.dependencies_ready(container) {
+                        Ok(true) => Some((container.clone(), Ok(true))),
+                        Ok(false) => None,
+                        Err(e) => Some((container.clone(), Err(e))),
+                    }
+                });
+            let (container, start) = match next {
+                Some(next) => next,
+                None => break,
+            };
+            let pending = self
+                .state_mut(&container)
+                .ok()
+                .and_then(|state| state.pending_start.take())
+                .expect("internal error");
+
+            let PendingStart {
+                arguments,
+                replies,
+                ready_replies,
+                ..
+            } = pending;
+
+            let result = match start {
+                // Started in the meantime
+                Ok(false) => Ok(()),
+                Ok(true) => {
+                    info!("Starting {} after its dependencies are ready", container);
+                    self.start_process(&container, arguments, true).await
+                }
+                Err(e) => {
+                    warn!("Failed to start pending {}: {}", container, e);
+                    Err(Error::StartContainerFailed(container.clone(), e))
+                }
+            };
+            self.reply_start(&container, result, replies, ready_replies);
+        }
+    }
+
+    /// Reply to the start requests of a pending start of `container` with `result`. The
+    /// replies are deferred again if the start is pending again, e.g. for its hooks.
+    fn reply_start(
+        &mut self,
+        container: &Container,
+        result: Result<(), Error>,
+        mut replies: Vec<oneshot::Sender<model::Response>>,
+        ready_replies: Vec<oneshot::Sender<model::Response>>,
+    ) {
+        let state = match self.state_mut(container) {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        if result.is_err() {
+            // The hook that waits for the exit of the container fails
+            state.hook_exit.take();
+        }
+        if let Some(pending) = state.pending_start.as_mut().filter(|_| result.is_ok()) {
+            pending.replies.extend(replies);
+            pending.ready_replies.extend(ready_replies);
+            return;
+        }
+
+        let result = result.map_err(model::Error::from);
+        match state
+            .process
+            .as_mut()
+            .and_then(|process| process.pending_ready.as_mut())
+        {
+            // Reply once the container is ready
+            Some(pending_ready) if result.is_ok() => pending_ready.replies.extend(ready_replies),
+            _ => replies.extend(ready_replies),
+        }
+        for reply in replies {
+            reply
+                .send(Self::start_response(container, result.clone()))
+                .ok();
+        }
+    }
+
+    /// Response to a start request with `result`
+    fn start_response(container: &Container, result: Result<(), model::Error>) -> model::Response {
+        let container = container.clone();
+        model::Response::Start(match result {
+            Ok(()) => model::StartResult::Ok { container },
+            Err(error) => model::StartResult::Error { container, error },
+        })
+    }
+
+    /// Arm a timer that sends `event` after `timeout`. The timer is cancelled when the
+    /// returned guard is dropped.
+    fn timer(&self, timeout: Duration, event: Event) -> DropGuard {
+        let timer = CancellationToken::new();
+        task::spawn({
+            let timer = timer.clone();
+            let events_tx = self.events_tx.clone();
+            async move {
+                select! {
+                    _ = timer.cancelled() => (),
+                    _ = time::sleep(timeout) => {
+                        // The event loop is gone if the runtime shuts down.
+                        events_tx.send(event).await.ok();
+                    }
+                }
+            }
+        });
+        timer.drop_guard()
+    }
+
+    /// Handle a readiness or status notification received from the console of a container
+    pub(super) async fn on_notify(
+        &mut self,
+        container: &Container,
+        notify: model::Notify,
+    ) -> Result<(), Error> {
+        let process = match self
+            .state_mut(container)
+            .ok()
+            .and_then(|state| state.process.as_mut())
+        {
+            Some(process) => process,
+            None => return Ok(()),
+        };
+
+        match notify {
+            model::Notify::Ready if process.ready => (),
+            model::Notify::Ready => {
+                info!("Container {} is ready", container);
+                process.ready = true;
+                let replies = process
+                    .pending_ready
+                    .take()
+                    .map(|pending| pending.replies)
+                    .unwrap_or_default();
+                for reply in replies {
+                    reply.send(Self::start_response(container, Ok(()))).ok();
+                }
+                self.container_event(container, ContainerEvent::Ready);
+                self.start_pending().await;
+            }
+            model::Notify::Status { status } => {
+                debug!("Container {} reports status \"{}\"", container, status);
+                process.status = Some(status);
+            }
+            model::Notify::Stopping => {
+                info!("Container {} is stopping", container);
+                process.ready = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Kill a container that did not report its readiness within its ready timeout
+    pub(super) async fn on_ready_timeout(&mut self, container: &Container) -> Result<(), Error> {
+        let pending = self
+            .state(container)
+            .ok()
+            .and_then(|state| state.process.as_ref())
+            .map(|process| process.pending_ready.is_some())
+            .unwrap_or(false);
+        if pending {
+            warn!(
+                "Container {} did not report its readiness within the ready timeout",
+                container
+            );
+            if let Some(process) = self.state_mut(container)?.process.as_mut() {
+                process.exit_reason = Some(ExitReason::NotReady);
+            }
+            self.kill(container, Signal::SIGKILL).await?;
+        }
+        Ok(())
+    }
+
+    /// Stop a container whose maximum run time elapsed
+    pub(super) async fn on_max_runtime(&mut self, container: &Container) -> Result<(), Error> {
+        let process = match self
+            .state_mut(container)
+            .ok()
+            .and_then(|state| state.process.as_mut())
+        {
+            // A container that is already stopped keeps the reason of the stop
+            Some(process) if process.stopping.is_none() => process,
+            _ => return Ok(()),
+        };
+        warn!("Container {} exceeded its maximum run time", container);
+        process.exit_reason = Some(ExitReason::MaxRuntime);
+        self.stop(container, None).await
+    }
+
+    /// Start a container without considering dependencies. If `hooks` is set the start is
+    /// pending until the pre mount and pre start hooks passed.
+    async fn start_process(
+        &mut self,
+        container: &Container,
+        arguments: StartArguments,
+        hooks: bool,
+    ) -> Result<(), Error> {
+        let launch = match self.prepare_start(container, arguments, hooks, &[]).await? {
+            Some(launch) => launch,
+            None => return Ok(()),
+        };
+        let events_tx = self.events_tx.clone();
+        let spawned = launch
+            .spawn(&self.forker, &self.config, events_tx, self.selinux_enabled)
+            .await?;
+        self.finish_start(spawned).await
+    }
+
+    /// Check the start of `container` and set up its resources. Returns `None` if the
+    /// start is pending on hooks. The starts in `pending` are not finished yet and hold
+    /// their bridge addresses and user namespace ids.
+    async fn prepare_start(
+        &mut self,
+        container: &Container,
+        arguments: StartArguments,
+        hooks: bool,
+        pending: &[Launch],
+    ) -> Result<Option<Launch>, Error> {
+        let StartArguments {
+            init,
+            args: args_extra,
+            env: env_extra,
+            ..
+        } = arguments.clone();
+        let start = time::Instant::now();
+        info!("Trying to start {}", container);
+
+        // Check if the container is already running
+        let container_state = self.state(container)?;
+        if container_state.process.is_some() {
+            warn!("Application {} is already running", container);
+            return Err(Error::StartContainerStarted(container.clone()));
+        }
+
+        // Check if a container with same name but different version is running
+        if let Some(container) = self
+            .containers
+            .iter()
+            .filter_map(|(k, v)| v.process.as_ref().map(|_| k))
+            .find(|c| c.name() == container.name())
+        {
+            warn!("Application {} is already running", container);
             return Err(Error::StartContainerStarted(container.clone()));
         }
 
@@ -398,6 +1413,83 @@ impl State {
 
         let manifest = self.manifest(container)?.clone();
 
+        // Check the admission budgets
+        budgets::admit(
+            container,
+            &container_state.repository,
+            &Demand::of(&manifest),
+            &self.budgets(),
+        )?;
+
+        // The host ids of a user namespace must lie within the configured pools
+        if let Some(user_namespace) = &manifest.user_namespace {
+            match &self.config.user_namespaces {
+                Some(pools) if pools.allows(user_namespace) => (),
+                Some(_) => {
+                    return Err(Error::StartContainerFailed(
+                        container.clone(),
+                        "user namespace maps ids outside of the configured pools".into(),
+                    ))
+                }
+                None => {
+                    return Err(Error::StartContainerFailed(
+                        container.clone(),
+                        "user namespaces are not configured".into(),
+                    ))
+                }
+            }
+
+            // The host ids must not be mapped by a started container
+            let started = self
+                .containers
+                .iter()
+                .filter(|(_, state)| state.process.is_some())
+                .filter_map(|(other, _)| Some((other, self.manifest(other).ok()?)))
+                .chain(
+                    pending
+                        .iter()
+                        .map(|launch| (&launch.container, &launch.manifest)),
+                );
+            for (other, manifest) in started {
+                if let Some(other_user_namespace) = &manifest.user_namespace {
+                    if user_namespace.overlaps(other_user_namespace) {
+                        return Err(Error::StartContainerFailed(
+                            container.clone(),
+                            format!("user namespace overlaps the host ids of {other}"),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Check the named network namespace or allocate the address on the bridge
+        let address = match (&manifest.network, &self.config.network) {
+            (Some(Network::Namespace(name)), _) if !network::namespace(name).exists() => {
+                return Err(Error::StartContainerFailed(
+                    container.clone(),
+                    format!("network namespace {name} does not exist"),
+                ))
+            }
+            (Some(Network::Bridge), Some(network)) => {
+                let used = self
+                    .containers
+                    .values()
+                    .filter_map(|state| state.process.as_ref()?.network.as_ref())
+                    .chain(self.network_namespaces.iter().filter_map(Namespace::veth))
+                    .map(|veth| veth.index())
+                    .chain(pending.iter().filter_map(|launch| launch.address))
+                    .collect::<Vec<_>>();
+                Some(network::allocate(network, &used)?)
+            }
+            (Some(Network::Bridge), None) => {
+                return Err(Error::StartContainerFailed(
+                    container.clone(),
+                    "network bridge is not configured".into(),
+                ))
+            }
+            _ => None,
+        };
+
         // Check if the container is not a resource
         let init = if let Some(init) = init {
             // Replace the string <INIT> with the init from the manifest.
@@ -453,6 +1545,31 @@ impl State {
             }
         }
 
+        // Pre mount and pre start hooks can veto the start. The start continues once they passed.
+        if hooks && !arguments.hook {
+            let need_mount = Vec::from_iter(need_mount.iter().cloned());
+            let pre_mount = self.pre_mount_hooks(&need_mount);
+            let pre_start = self.hooks(container, Stage::PreStart)?;
+            let run = if !pre_mount.is_empty() {
+                Some((Stage::PreMount, pre_mount))
+            } else if !pre_start.is_empty() {
+                Some((Stage::PreStart, vec![(container.clone(), pre_start)]))
+            } else {
+                None
+            };
+            if let Some((stage, hooks)) = run {
+                let action = Action::Start(container.clone());
+                self.run_hooks(stage, hooks, Vec::new(), action);
+                let pending = self
+                    .state_mut(container)?
+                    .pending_start
+                    .get_or_insert_with(PendingStart::default);
+                pending.arguments = arguments;
+                pending.hooks = true;
+                return Ok(None);
+            }
+        }
+
         // Mount containers
         if !need_mount.is_empty() {
             info!(
@@ -475,8 +1592,52 @@ impl State {
         // Create a token to stop tasks spawned related to this container
         let stop = CancellationToken::new();
 
+        let journal_start = model::JournalStart::from(&arguments);
+
+        // Capture the output in the buffer that is kept across restarts
+        let buffer_lines = self.config.logs.buffer_lines;
+        let buffer = self
+            .state_mut(container)?
+            .logs
+            .get_or_insert_with(|| Arc::new(LogBuffer::new(buffer_lines)))
+            .clone();
+        let capture = logs::Capture::new(container, buffer, &self.config.logs);
+
+        // Host ids of the container. Used for the delegation of the cgroup and the terminal.
+        let owner = persistence::owner(&manifest)?;
+
+        // Private devpts instance if a terminal is configured in the manifest
+        let manifest_io = manifest.io.clone().unwrap_or_default();
+        let devpts = if manifest_io.tty {
+            Some(io::devpts(&self.config.run_dir, container, owner)?)
+        } else {
+            None
+        };
+
+        // Open a file handle for stdin, stdout and stderr according to the manifest.
+        let ContainerIo { io, terminal } =
+            io::open(container, &manifest_io, devpts.as_ref(), &capture)
+                .await
+                .context("failed to open io")?;
+
+        // Open sockets if any configured in manifest.
+        let (socket_fds, sockets) = sockets::open(
+            self.config.socket_dir.as_path(),
+            container,
+            &manifest.sockets,
+            manifest.user_namespace.as_ref(),
+        )
+        .await
+        .expect("Socket setup error");
+
+        // Setup persistent storage (if any)
+        persistence::setup(&self.config, &manifest).await?;
+
+        // Generated /etc/hostname and /etc/hosts (if any)
+        let uts = uts::setup(&self.config.run_dir, &manifest).await?;
+
         // We send the fd to the forker so that it can pass it to the init
-        let console_fd = if let Some(contianer_configuration) = manifest.console.clone() {
+        let console = if let Some(contianer_configuration) = manifest.console.clone() {
             let peer = Peer::Container(container.clone());
             let (runtime_stream, container_stream) =
                 StdUnixStream::pair().expect("failed to create socketpair");
@@ -519,54 +1680,6 @@ impl State {
             None
         };
 
-        // Open a file handle for stdin, stdout and stderr according to the manifest.
-        let ContainerIo { io } = io::open(container, &manifest.io.clone().unwrap_or_default())
-            .await
-            .expect("IO setup error");
-
-        // Open sockets if any configured in manifest.
-        let (socket_fds, sockets) = sockets::open(
-            self.config.socket_dir.as_path(),
-            container,
-            &manifest.sockets,
-        )
-        .await
-        .expect("Socket setup error");
-
-        // Setup persistent storage (if any)
-        persistence::setup(&self.config, &manifest).await?;
-
-        // Create container.
-        let config = &self.config;
-        let containers = self.containers.keys();
-        let pid = self
-            .forker
-            .create(
-                container,
-                config,
-                &manifest,
-                io,
-                console_fd,
-                socket_fds,
-                containers,
-                self.selinux_enabled,
-            )
-            .await?;
-
-        // Debug
-        super::debug::start(&self.config, container, pid).await?;
-
-        // CGroups
-        let cgroups = {
-            let config = manifest.cgroups.clone().unwrap_or_default();
-            let events_tx = self.events_tx.clone();
-
-            // Creating a cgroup is a northstar internal thing. If it fails it's not recoverable.
-            cgroups::CGroups::new(&self.config.cgroup, events_tx, container, &config, pid)
-                .await
-                .expect("failed to create cgroup")
-        };
-
         // Binary arguments
         let mut args = Vec::with_capacity(
             1 + if args_extra.is_empty() {
@@ -587,7 +1700,7 @@ impl State {
         let env = if env_extra.is_empty() {
             &manifest.env
         } else {
-            env_extra
+            &env_extra
         };
 
         let env = env
@@ -599,36 +1712,116 @@ impl State {
             .map(|s| unsafe { NonNulString::from_string_unchecked(s) })
             .collect::<Vec<_>>();
 
-        debug!("Container {} init is {:?}", container, init);
-        debug!(
-            "Container {} argv is \"{}\"",
+        let containers = self.containers.keys().cloned().collect();
+        Ok(Some(Launch {
+            container: container.clone(),
+            manifest,
+            arguments,
+            start,
+            init,
+            args,
+            env,
+            address,
+            owner,
+            io,
+            console,
+            socket_fds,
+            containers,
+            setup: Setup {
+                stop,
+                journal_start,
+                capture,
+                terminal,
+                devpts,
+                sockets,
+                uts,
+                resources,
+            },
+        }))
+    }
+
+    /// Record the process of a spawned container, start its monitors and run the post
+    /// start hooks
+    async fn finish_start(&mut self, spawned: Spawned) -> Result<(), Error> {
+        let Spawned {
             container,
-            args.iter().join(" ")
-        );
-        debug!(
-            "Container {} env is \"{}\"",
+            manifest,
+            arguments,
+            start,
+            pid,
+            network,
+            cgroups,
+            setup,
+        } = spawned;
+        let container = &container;
+        let Setup {
+            stop,
+            journal_start,
+            capture,
+            terminal,
+            devpts,
+            sockets,
+            uts,
+            resources,
+        } = setup;
+
+        // Health monitor
+        let health = health::Monitor::start(
             container,
-            env.iter().join(", ")
+            &manifest,
+            &self.config.socket_dir,
+            self.events_tx.clone(),
+            stop.clone(),
         );
 
-        // Send exec request to launcher
-        if let Err(e) = self.forker.exec(container.clone(), init, args, env).await {
-            warn!("Failed to exec {} ({}): {}", container, pid, e);
-            stop.cancel();
-            cgroups.destroy().await;
-            return Err(e);
-        }
+        // Containers with `ready: notify` are killed if they do not report their readiness in time
+        let pending_ready = match manifest.ready.unwrap_or_default() {
+            Ready::Started => None,
+            Ready::Notify => {
+                let timeout = manifest.ready_timeout.unwrap_or(DEFAULT_READY_TIMEOUT);
+                Some(PendingReady {
+                    replies: Vec::new(),
+                    _timer: self.timer(timeout, Event::ReadyTimeout(container.clone())),
+                })
+            }
+        };
+        let ready = pending_ready.is_none();
+
+        // Containers are stopped once their maximum run time elapsed
+        let max_runtime = arguments
+            .max_runtime
+            .or(manifest.max_runtime)
+            .map(|timeout| self.timer(timeout, Event::MaxRuntime(container.clone())));
 
         // Get a mutable reference to the container state in order to update the process field
         let container_state = self.containers.get_mut(container).expect("Internal error");
 
         // Add process context to process
         let started = time::Instant::now();
+        let is_hook = arguments.hook;
         container_state.process = Some(ContainerContext {
             pid,
             started,
+            arguments,
+            killed: false,
+            kill_signal: None,
+            health,
+            stopping: None,
+            paused: false,
+            ready,
+            status: None,
+            pending_ready,
+            _max_runtime: max_runtime,
+            exit_reason: None,
+            oom_kills: 0,
+            execs: HashMap::new(),
+            logs: capture.sender(),
+            terminal,
+            devpts,
             cgroups,
             sockets,
+            uts,
+            network,
             stop,
             resources,
         });
@@ -638,61 +1831,706 @@ impl State {
 
         // Send container started event
         self.container_event(container, ContainerEvent::Started);
+        if ready {
+            self.container_event(container, ContainerEvent::Ready);
+        }
 
+        self.journal(container, |entry| entry.started = Some(journal_start))
+            .await;
+
+        if !is_hook {
+            let hooks = self.hooks(container, Stage::PostStart)?;
+            if !hooks.is_empty() {
+                let env = vec![(env::PID, pid.to_string())];
+                self.run_hooks(
+                    Stage::PostStart,
+                    vec![(container.clone(), hooks)],
+                    env,
+                    Action::None,
+                );
+            }
+        }
         Ok(())
     }
 
-    /// Send signal `signal` to container if running
-    pub(super) async fn kill(
-        &mut self,
-        container: &Container,
-        signal: Signal,
-    ) -> Result<(), Error> {
-        let container_state = self.state_mut(container)?;
+    /// Hooks of `stage` from the runtime configuration and the manifest of `container`
+    fn hooks(&self, container: &Container, stage: Stage) -> Result<Vec<Hook>, Error> {
+        Ok(self
+            .config
+            .hooks
+            .stage(stage)
+            .iter()
+            .chain(self.manifest(container)?.hooks.stage(stage))
+            .cloned()
+            .collect())
+    }
 
-        match &mut container_state.process {
-            Some(context) => {
-                info!("Killing {} with {}", container, signal.as_str());
-                let pid = context.pid;
-                let process_group = nix::unistd::Pid::from_raw(-(pid as i32));
-                match nix::sys::signal::kill(process_group, Some(signal)) {
-                    Ok(_) => Ok(()),
-                    Err(nix::Error::ESRCH) => {
-                        debug!("Process {} already exited", pid);
-                        Ok(())
-                    }
-                    Err(e) => unimplemented!("Kill error {}", e),
-                }
-            }
-            None => Err(Error::StopContainerNotStarted(container.clone())),
-        }
+    /// Pre mount hooks of the containers in `containers` that are not mounted
+    fn pre_mount_hooks(&self, containers: &[Container]) -> Vec<(Container, Vec<Hook>)> {
+        containers
+            .iter()
+            .filter(|container| {
+                self.state(container)
+                    .map(|s| !s.is_mounted())
+                    .unwrap_or(false)
+            })
+            .filter_map(|container| {
+                let hooks = self.hooks(container, Stage::PreMount).ok()?;
+                (!hooks.is_empty()).then(|| (container.clone(), hooks))
+            })
+            .collect()
     }
 
-    /// Shutdown the runtime: stop running applications and umount npks
-    pub(super) async fn shutdown(
-        mut self,
-        event_rx: impl Stream<Item = Event>,
+    /// Run `hooks` of `stage` in the background. The hooks get the stage and `env` in their
+    /// environment. `action` continues once all hooks ran.
+    fn run_hooks(
+        &self,
+        stage: Stage,
+        hooks: Vec<(Container, Vec<Hook>)>,
+        mut env: Vec<(&'static str, String)>,
+        action: Action,
+    ) {
+        env.push((env::HOOK, stage.to_string()));
+        let run = hooks::Run {
+            stage,
+            hooks,
+            env,
+            action,
+        };
+        hooks::spawn(run, self.events_tx.clone());
+    }
+
+    /// Continue the action of a hook run. A failing hook of a pre stage vetoes the action.
+    pub(super) async fn on_hooks(
+        &mut self,
+        stage: Stage,
+        results: hooks::Results,
+        action: Action,
     ) -> Result<(), Error> {
-        let started_containers = self
-            .containers
+        let hooked = results
             .iter()
-            .filter_map(|(container, state)| state.process.as_ref().map(|_| container.clone()))
+            .map(|(container, _)| container.clone())
             .collect::<Vec<_>>();
+        let mut failed = results
+            .into_iter()
+            .filter_map(|(container, result)| {
+                let error = Error::HookFailed(container.clone(), stage.to_string(), result.err()?);
+                Some((container, error))
+            })
+            .collect::<HashMap<_, _>>();
+
+        match action {
+            Action::Mount(containers, reply) => {
+                let mut mount = containers
+                    .iter()
+                    .filter(|container| !failed.contains_key(*container))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // Containers mounted in the meantime, e.g. for a start, are fine
+                if reply.is_none() {
+                    mount.retain(|container| {
+                        self.state(container)
+                            .map(|s| !s.is_mounted())
+                            .unwrap_or(false)
+                    });
+                }
+                let results = self.mount_all(&mount).await;
+                if let Some(reply) = reply {
+                    let mut mounted = mount.into_iter().zip(results).collect::<HashMap<_, _>>();
+                    let results = containers
+                        .iter()
+                        .map(|container| match failed.remove(container) {
+                            Some(e) => Err(e),
+                            None => mounted.remove(container).expect("internal error"),
+                        })
+                        .collect();
+                    reply.send(Self::mount_response(&containers, results)).ok();
+                }
+            }
+            Action::Start(container) => {
+                let pending = match self
+                    .state_mut(&container)
+                    .ok()
+                    .and_then(|state| state.pending_start.take())
+                {
+                    Some(pending) => pending,
+                    None => return Ok(()),
+                };
+                let PendingStart {
+                    arguments,
+                    replies,
+                    ready_replies,
+                    ..
+                } = pending;
+
+                let result = match failed.into_values().next() {
+                    Some(e) => Err(e),
+                    // Mount the containers whose pre mount hooks passed and continue with
+                    // the pre start hooks
+                    None if stage == Stage::PreMount => {
+                        let mount = hooked
+                            .into_iter()
+                            .filter(|c| self.state(c).map(|s| !s.is_mounted()).unwrap_or(false))
+                            .collect::<Vec<_>>();
+                        match self
+                            .mount_all(&mount)
+                            .await
+                            .into_iter()
+                            .find_map(Result::err)
+                        {
+                            Some(e) => Err(e),
+                            None => self.start_process(&container, arguments, true).await,
+                        }
+                    }
+                    None => self.start_process(&container, arguments, false).await,
+                };
+                if let Err(e) = &result {
+                    warn!("Failed to start {}: {}", container, e);
+                }
+                let failed = result.is_err();
+                self.reply_start(&container, result, replies, ready_replies);
 
-        // Send a SIGKILL to each started container
-        for container in &started_containers {
+                // The update to a container that failed to start is rolled back
+                if failed
+                    && matches!(self.updates.get(container.name()), Some(update) if update.to == container)
+                {
+                    self.rollback(container.name()).await?;
+                }
+                self.start_pending().await;
+            }
+            Action::Uninstall(container, wipe, reply) => {
+                let result = match failed.into_values().next() {
+                    Some(e) => Err(e),
+                    None => self.uninstall(&container, wipe).await,
+                };
+                self.reply_uninstall(&container, result, reply);
+            }
+            Action::None => (),
+        }
+        Ok(())
+    }
+
+    /// Start the hook container of `request` and send it with a receiver for its exit status
+    pub(super) async fn on_hook_container(
+        &mut self,
+        request: hooks::ContainerRequest,
+    ) -> Result<(), Error> {
+        let result = self.start_hook(&request).await;
+        request.reply.send(result).ok();
+        Ok(())
+    }
+
+    /// Start the hook container of `request` with the hook environment. Hook containers
+    /// do not run hooks.
+    async fn start_hook(
+        &mut self,
+        request: &hooks::ContainerRequest,
+    ) -> Result<(Container, oneshot::Receiver<ExitStatus>), String> {
+        let hooks::ContainerRequest {
+            name,
+            version,
+            args,
+            env,
+            ..
+        } = request;
+        let hook = self
+            .match_dependency(name, version)
+            .cloned()
+            .ok_or_else(|| format!("hook container {name} {} is missing", version.to_string()))?;
+        let mut env_extra = self.manifest(&hook).map_err(|e| e.to_string())?.env.clone();
+        env_extra.extend(env.iter().map(|(key, value)| unsafe {
+            (
+                NonNulString::from_str_unchecked(key),
+                NonNulString::from_string_unchecked(value.clone()),
+            )
+        }));
+        let arguments = StartArguments {
+            args: args.clone(),
+            env: env_extra,
+            hook: true,
+            ..Default::default()
+        };
+        self.start(&hook, arguments)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let (exit_tx, exit_rx) = oneshot::channel();
+        self.state_mut(&hook).map_err(|e| e.to_string())?.hook_exit = Some(exit_tx);
+        Ok((hook, exit_rx))
+    }
+
+    /// Kill a hook container that is still running after its timeout
+    pub(super) async fn on_hook_timeout(&mut self, container: &Container) -> Result<(), Error> {
+        let is_hook = self
+            .state(container)
+            .ok()
+            .and_then(|state| state.process.as_ref())
+            .map(|process| process.arguments.hook)
+            .unwrap_or(false);
+        if is_hook {
+            warn!("Hook container {} timed out", container);
             self.kill(container, Signal::SIGKILL).await?;
+        } else if let Ok(state) = self.state_mut(container) {
+            // A hook container that waits for its dependencies is not started
+            if state.hook_exit.is_some() {
+                if let Some(pending) = state.pending_start.take() {
+                    warn!("Hook container {} timed out before its start", container);
+                    let error = Error::StartContainerFailed(container.clone(), "timeout".into());
+                    self.reply_start(
+                        container,
+                        Err(error),
+                        pending.replies,
+                        pending.ready_replies,
+                    );
+                }
+            }
         }
+        Ok(())
+    }
 
-        // Wait until all processes are gone
-        pin!(event_rx);
-        while self
-            .containers
-            .values()
-            .any(|state| state.process.is_some())
+    /// Send signal `signal` to container if running
+    pub(super) async fn kill(
+        &mut self,
+        container: &Container,
+        signal: Signal,
+    ) -> Result<(), Error> {
+        let container_state = self.state_mut(container)?;
+
+        match &mut container_state.process {
+            Some(context) => {
+                info!("Killing {} with {}", container, signal.as_str());
+                let pid = context.pid;
+                let process_group = nix::unistd::Pid::from_raw(-(pid as i32));
+                let paused = context.paused;
+                match nix::sys::signal::kill(process_group, Some(signal)) {
+                    Ok(_) => (),
+                    Err(nix::Error::ESRCH) => debug!("Process {} already exited", pid),
+                    Err(e) => unimplemented!("Kill error {}", e),
+                }
+
+                // Frozen processes do not handle signals
+                if paused {
+                    self.resume(container)?;
+                }
+                Ok(())
+            }
+            None => Err(Error::StopContainerNotStarted(container.clone())),
+        }
+    }
+
+    /// Freeze all processes of `container`
+    fn pause(&mut self, container: &Container) -> Result<(), Error> {
+        let process = self
+            .state_mut(container)?
+            .process
+            .as_mut()
+            .ok_or_else(|| Error::ContainerNotStarted(container.clone()))?;
+        if process.paused {
+            return Err(Error::ContainerPaused(container.clone()));
+        }
+
+        info!("Pausing {}", container);
+        process.cgroups.freeze()?;
+        process.paused = true;
+        self.container_event(container, ContainerEvent::Paused);
+        Ok(())
+    }
+
+    /// Spawn a process in the namespaces of the started `container`. The process
+    /// is placed in the cgroups of the container and has the same credentials and
+    /// restrictions as the container init. `exit` receives the exit status.
+    #[allow(clippy::too_many_arguments)]
+    async fn exec(
+        &mut self,
+        container: &Container,
+        path: NonNulString,
+        args: Vec<NonNulString>,
+        env_extra: HashMap<NonNulString, NonNulString>,
+        tty: bool,
+        io: [OwnedFd; 3],
+        exit: oneshot::Sender<ExitStatus>,
+    ) -> Result<Pid, Error> {
+        let process = self
+            .state(container)?
+            .process
+            .as_ref()
+            .ok_or_else(|| Error::ContainerNotStarted(container.clone()))?;
+        if process.paused {
+            return Err(Error::ContainerPaused(container.clone()));
+        }
+        let cgroups = process.cgroups.procs();
+
+        // Check optional env variables for reserved keys which cannot be overwritten
+        if env_extra.keys().any(|k| {
+            k.as_str() == env::NAME
+                || k.as_str() == env::VERSION
+                || k.as_str() == env::CONTAINER
+                || k.as_str() == env::CONSOLE
+        }) {
+            return Err(Error::InvalidArguments(format!(
+                "env contains reserved key {} or {} or {} or {}",
+                env::NAME,
+                env::VERSION,
+                env::CONTAINER,
+                env::CONSOLE
+            )));
+        }
+
+        // The env of the manifest is used unless variables are provided with the request
+        let manifest = self.manifest(container)?;
+        let env = if env_extra.is_empty() {
+            &manifest.env
+        } else {
+            &env_extra
+        };
+        let env = env
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .chain(once(format!("{}={}", env::CONTAINER, container)))
+            .chain(once(format!("{}={}", env::NAME, container.name())))
+            .chain(once(format!("{}={}", env::VERSION, container.version())))
+            .map(|s| unsafe { NonNulString::from_string_unchecked(s) })
+            .collect::<Vec<_>>();
+        let args = once(path.clone()).chain(args).collect::<Vec<_>>();
+
+        info!("Executing \"{}\" in {}", args.iter().join(" "), container);
+        let pid = self
+            .forker
+            .spawn(container.clone(), path, args, env, tty, cgroups, io)
+            .await?;
+
+        // The container might have exited in the meantime. Dropping `exit` is fine then.
+        if let Some(process) = self.state_mut(container)?.process.as_mut() {
+            process.execs.insert(pid, exit);
+        }
+        Ok(pid)
+    }
+
+    /// Open the io of a process spawned in `container` with an exec request. Terminals are
+    /// allocated from the devpts instance of the container.
+    fn exec_io(&self, container: &Container, tty: bool) -> Result<([OwnedFd; 3], ExecIo), Error> {
+        let process = self
+            .state(container)?
+            .process
+            .as_ref()
+            .ok_or_else(|| Error::ContainerNotStarted(container.clone()))?;
+        let devpts = match (tty, process.devpts.as_ref()) {
+            (false, _) => None,
+            (true, Some(devpts)) => Some(devpts),
+            (true, None) => {
+                return Err(Error::InvalidArguments(format!(
+                    "{container} has no terminal"
+                )))
+            }
+        };
+        io::open_exec(devpts)
+            .context("failed to open exec io")
+            .map_err(Into::into)
+    }
+
+    /// Core dumps of `container` starting with the oldest
+    async fn core_dumps(&self, container: &Container) -> Result<Vec<model::CoreDump>, Error> {
+        let configuration = self
+            .config
+            .core_dumps
+            .as_ref()
+            .ok_or_else(|| Error::Configuration("core dumps are disabled".into()))?;
+        let dir = core_dumps::dir(configuration, container.name());
+        // The container might have written to the directory since its last exit
+        core_dumps::retain(&dir, configuration.retention, configuration.quota).await?;
+        Ok(core_dumps::list(&dir).await?)
+    }
+
+    /// Open the core dump `name` of `container` and send the file to `file`
+    async fn core_dump(
+        &self,
+        container: &Container,
+        name: &str,
+        file: oneshot::Sender<std::fs::File>,
+    ) -> Result<model::CoreDump, Error> {
+        // Only listed core dumps can be downloaded. This rejects paths.
+        let core_dump = self
+            .core_dumps(container)
+            .await?
+            .into_iter()
+            .find(|core_dump| core_dump.name == name)
+            .ok_or_else(|| Error::InvalidArguments(format!("invalid core dump {name}")))?;
+        let configuration = self.config.core_dumps.as_ref().expect("internal error");
+        let path = core_dumps::dir(configuration, container.name()).join(name);
+        let content = std::fs::File::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        file.send(content).ok();
+        Ok(core_dump)
+    }
+
+    /// Captured output of `container` since `since` limited to the last `tail` lines.
+    /// If `follow` is set and the container is started, a receiver for the lines
+    /// written afterwards is sent to `follow`.
+    fn logs(
+        &self,
+        container: &Container,
+        tail: Option<usize>,
+        since: Option<u64>,
+        follow: Option<oneshot::Sender<broadcast::Receiver<model::LogLine>>>,
+    ) -> Result<Vec<model::LogLine>, Error> {
+        let state = self.state(container)?;
+        let sender = state
+            .process
+            .as_ref()
+            .filter(|_| follow.is_some())
+            .map(|process| &process.logs);
+        let (lines, receiver) = match &state.logs {
+            Some(buffer) => buffer.lines(tail, since, sender),
+            None => (Vec::with_capacity(0), None),
+        };
+        if let (Some(follow), Some(receiver)) = (follow, receiver) {
+            follow.send(receiver).ok();
+        }
+        Ok(lines)
+    }
+
+    /// Attach to the terminal of `container`
+    fn attach(&self, container: &Container) -> Result<Attachment, Error> {
+        let process = self
+            .state(container)?
+            .process
+            .as_ref()
+            .ok_or_else(|| Error::ContainerNotStarted(container.clone()))?;
+        process
+            .terminal
+            .as_ref()
+            .map(Terminal::attach)
+            .ok_or_else(|| Error::ContainerNoTerminal(container.clone()))
+    }
+
+    /// Spawn the health probe `path` in `container` with stdin, stdout and stderr
+    /// connected to /dev/null. The probe fails if the reply is dropped.
+    pub(super) async fn on_probe(
+        &mut self,
+        container: &Container,
+        path: NonNulString,
+        args: Vec<NonNulString>,
+        reply: oneshot::Sender<(Pid, oneshot::Receiver<ExitStatus>)>,
+    ) -> Result<(), Error> {
+        let null = || -> Result<OwnedFd, Error> {
+            let null = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/null")
+                .context("failed to open /dev/null")?;
+            Ok(null.into())
+        };
+        let io = [null()?, null()?, null()?];
+        let (exit_tx, exit_rx) = oneshot::channel();
+        match self
+            .exec(container, path, args, HashMap::new(), false, io, exit_tx)
+            .await
         {
-            if let Some(Event::Container(container, event)) = event_rx.next().await {
-                self.on_event(&container, &event, true).await?;
+            Ok(pid) => {
+                reply.send((pid, exit_rx)).ok();
+            }
+            Err(e) => debug!("Failed to spawn health probe of {}: {}", container, e),
+        }
+        Ok(())
+    }
+
+    /// Forward the exit status of a process spawned with an exec request
+    pub(super) fn on_exec_exit(
+        &mut self,
+        container: &Container,
+        pid: Pid,
+        exit_status: ExitStatus,
+    ) -> Result<(), Error> {
+        info!(
+            "Process {} of {} exited with {}",
+            pid, container, exit_status
+        );
+        if let Some(exit) = self
+            .state_mut(container)
+            .ok()
+            .and_then(|state| state.process.as_mut())
+            .and_then(|process| process.execs.remove(&pid))
+        {
+            exit.send(exit_status).ok();
+        }
+        Ok(())
+    }
+
+    /// Thaw all processes of a paused `container`
+    fn resume(&mut self, container: &Container) -> Result<(), Error> {
+        let process = self
+            .state_mut(container)?
+            .process
+            .as_mut()
+            .ok_or_else(|| Error::ContainerNotStarted(container.clone()))?;
+        if !process.paused {
+            return Err(Error::ContainerNotPaused(container.clone()));
+        }
+
+        info!("Resuming {}", container);
+        process.cgroups.thaw()?;
+        process.paused = false;
+        self.container_event(container, ContainerEvent::Resumed);
+        Ok(())
+    }
+
+    /// Stop `container` gracefully. The stop signal of the manifest is sent to the container
+    /// and it is killed if it did not exit within `timeout` or the stop timeout of the manifest.
+    async fn stop(
+        &mut self,
+        container: &Container,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let manifest = self.manifest(container)?;
+        let signal = manifest.stop_signal.unwrap_or_default();
+        let timeout = timeout
+            .or(manifest.stop_timeout)
+            .unwrap_or(DEFAULT_STOP_TIMEOUT);
+
+        match self.state(container)?.process.as_ref() {
+            None => return Err(Error::StopContainerNotStarted(container.clone())),
+            // The stop timer is already running
+            Some(process) if process.stopping.is_some() => return Ok(()),
+            Some(_) => (),
+        }
+
+        info!(
+            "Stopping {} with {:?} and a timeout of {:?}",
+            container, signal, timeout
+        );
+
+        let timer = CancellationToken::new();
+        task::spawn({
+            let timer = timer.clone();
+            let events_tx = self.events_tx.clone();
+            let container = container.clone();
+            async move {
+                select! {
+                    _ = timer.cancelled() => (),
+                    _ = time::sleep(timeout) => {
+                        // The event loop is gone if the runtime shuts down.
+                        events_tx.send(Event::StopTimeout(container)).await.ok();
+                    }
+                }
+            }
+        });
+
+        let process = self
+            .state_mut(container)?
+            .process
+            .as_mut()
+            .expect("internal error");
+        // Containers stopped on request are not restarted
+        process.killed = true;
+        process.stopping = Some(Stopping {
+            replies: Vec::new(),
+            _timer: timer.drop_guard(),
+        });
+
+        self.kill(container, signal.into()).await
+    }
+
+    /// Kill a container that did not exit within its stop timeout
+    pub(super) async fn on_stop_timeout(&mut self, container: &Container) -> Result<(), Error> {
+        let stopping = self
+            .state(container)
+            .ok()
+            .and_then(|state| state.process.as_ref())
+            .map(|process| process.stopping.is_some())
+            .unwrap_or(false);
+        if stopping {
+            warn!(
+                "Container {} did not exit within the stop timeout",
+                container
+            );
+            self.kill(container, Signal::SIGKILL).await?;
+        }
+        Ok(())
+    }
+
+    /// Shutdown the runtime: stop running applications and umount npks
+    pub(super) async fn shutdown(
+        mut self,
+        event_rx: impl Stream<Item = Event>,
+    ) -> Result<(), Error> {
+        // The containers are restored in the state before the shutdown
+        self.journal.take();
+
+        // Pending starts are dropped
+        for state in self.containers.values_mut() {
+            state.pending_start.take();
+            state.hook_exit.take();
+        }
+
+        let started_containers = self
+            .containers
+            .iter()
+            .filter_map(|(container, state)| state.process.as_ref().map(|_| container.clone()))
+            .collect::<Vec<_>>();
+
+        // Stop dependents before their dependencies. Containers that are not started
+        // are skipped.
+        let graph = self.dependency_graph(&started_containers, true)?;
+        let layers = dependency::layers(&graph).unwrap_or_else(|_| vec![started_containers]);
+
+        // Containers are killed once the shutdown deadline is reached
+        let deadline = time::Instant::now() + self.config.shutdown_timeout;
+        let mut expired = false;
+
+        pin!(event_rx);
+        for layer in layers.iter().rev() {
+            let layer = layer
+                .iter()
+                .filter(|container| {
+                    self.state(container)
+                        .map(|s| s.process.is_some())
+                        .unwrap_or(false)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            // Stop each started container of this layer
+            for container in &layer {
+                if expired {
+                    self.kill(container, Signal::SIGKILL).await?;
+                } else {
+                    self.stop(container, None).await?;
+                }
+            }
+
+            // Wait until all processes of this layer are gone
+            loop {
+                let started = layer
+                    .iter()
+                    .filter(|container| {
+                        self.state(container)
+                            .map(|s| s.process.is_some())
+                            .unwrap_or(false)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if started.is_empty() {
+                    break;
+                }
+
+                select! {
+                    event = event_rx.next() => match event {
+                        Some(Event::Container(container, event)) => {
+                            self.on_event(&container, &event, true).await?
+                        }
+                        Some(Event::StopTimeout(container)) => {
+                            self.on_stop_timeout(&container).await?
+                        }
+                        _ => (),
+                    },
+                    _ = time::sleep_until(deadline), if !expired => {
+                        warn!("Shutdown timeout expired. Killing remaining containers");
+                        expired = true;
+                        for container in &started {
+                            self.kill(container, Signal::SIGKILL).await?;
+                        }
+                    }
+                }
             }
         }
 
@@ -705,6 +2543,10 @@ impl State {
             .collect::<Vec<_>>();
         self.umount_all(&to_umount).await;
 
+        for namespace in self.network_namespaces.drain(..) {
+            namespace.destroy();
+        }
+
         Ok(())
     }
 
@@ -751,105 +2593,840 @@ impl State {
                 ..Default::default()
             },
         );
+
+        // Reject containers that introduce a dependency cycle. Missing dependencies
+        // are fine because they might be installed later.
+        let graph = self.dependency_graph(once(&container), true)?;
+        if dependency::layers(&graph).is_err() {
+            warn!("Container {} introduces a dependency cycle", container);
+            self.containers.remove(&container);
+            self.repositories
+                .get_mut(id)
+                .ok_or_else(|| Error::InvalidRepository(id.to_string()))?
+                .remove(&container)
+                .await?;
+            return Err(Error::DependencyCycle(container));
+        }
+
         info!("Successfully installed {}", container);
 
-        self.container_event(&container, ContainerEvent::Installed);
+        self.schedule(&container);
+
+        if let Some(journal) = self.journal.as_mut() {
+            journal.installed(&container, &id.to_string()).await;
+        }
+
+        self.container_event(&container, ContainerEvent::Installed);
+
+        Ok(container)
+    }
+
+    /// Remove and umount a specific app
+    async fn uninstall(&mut self, container: &Container, wipe: bool) -> Result<(), Error> {
+        info!("Trying to uninstall {}", container);
+
+        let state = self.state(container)?;
+        let repository = state.repository.clone();
+
+        // Umount
+        if state.is_mounted() {
+            self.umount_all(&[container.clone()])
+                .await
+                .pop()
+                .expect("internal error")?;
+        }
+
+        // Remove from repository
+        debug!("Removing {} from {}", container, repository);
+        self.repositories
+            .get_mut(&repository)
+            .expect("Internal error")
+            .remove(container)
+            .await?;
+
+        // Wipe persistent dir if present
+        if wipe {
+            let name: &str = container.name().as_ref();
+            let dir = self.config.data_dir.join(name);
+            if dir.exists() {
+                info!(
+                    "Wiping persistent data dir {} of {}",
+                    dir.display(),
+                    container
+                );
+                if let Err(e) = fs::remove_dir_all(&dir)
+                    .await
+                    .with_context(|| format!("failed to remove {}", dir.display()))
+                {
+                    // If the runtime fails to remove the data dir leave it behind.
+                    // This cannot be handled.
+                    // In theory this should never happen with the cap_dac capability.
+                    warn!("Failed to remove {}: {}", dir.display(), e);
+                }
+            }
+
+            // Core dumps are wiped with the persistent data
+            if let Some(configuration) = &self.config.core_dumps {
+                let dir = core_dumps::dir(configuration, container.name());
+                if dir.exists() {
+                    info!("Wiping core dumps {} of {}", dir.display(), container);
+                    if let Err(e) = fs::remove_dir_all(&dir).await {
+                        warn!("Failed to remove {}: {}", dir.display(), e);
+                    }
+                }
+            }
+        }
+
+        self.containers.remove(container);
+        info!("Uninstalled {}", container);
+
+        if let Some(journal) = self.journal.as_mut() {
+            journal.uninstalled(container).await;
+        }
+        self.history.remove(container).await;
+
+        self.container_event(container, ContainerEvent::Uninstalled);
+
+        Ok(())
+    }
+
+    /// Uninstall `container` once its pre uninstall hooks passed. The hooks can veto the
+    /// uninstall. The response is sent to `reply` if set.
+    async fn request_uninstall(
+        &mut self,
+        container: &Container,
+        wipe: bool,
+        reply: Option<oneshot::Sender<model::Response>>,
+    ) {
+        match self.hooks(container, Stage::PreUninstall) {
+            Ok(hooks) if !hooks.is_empty() => {
+                let action = Action::Uninstall(container.clone(), wipe, reply);
+                let hooks = vec![(container.clone(), hooks)];
+                self.run_hooks(Stage::PreUninstall, hooks, Vec::new(), action);
+            }
+            Ok(_) => {
+                let result = self.uninstall(container, wipe).await;
+                self.reply_uninstall(container, result, reply);
+            }
+            Err(e) => self.reply_uninstall(container, Err(e), reply),
+        }
+    }
+
+    /// Reply to an uninstall request of `container` with `result` or log a failure
+    fn reply_uninstall(
+        &self,
+        container: &Container,
+        result: Result<(), Error>,
+        reply: Option<oneshot::Sender<model::Response>>,
+    ) {
+        let result = match result {
+            Ok(_) => model::UninstallResult::Ok {
+                container: container.clone(),
+            },
+            Err(e) => {
+                warn!("failed to uninstall {}: {}", container, e);
+                model::UninstallResult::Error {
+                    container: container.clone(),
+                    error: e.into(),
+                }
+            }
+        };
+        if let Some(reply) = reply {
+            reply.send(model::Response::Uninstall(result)).ok();
+        }
+    }
+
+    /// Update the started container `from` to the version `to` from the npk received on
+    /// `rx`. The update continues in the background: `from` is stopped and `to` is started.
+    /// If `to` exits or becomes unhealthy within `probation`, `from` is restored.
+    async fn update(
+        &mut self,
+        from: &Container,
+        to: &Container,
+        repository: &str,
+        rx: &mut mpsc::Receiver<Bytes>,
+        probation: Duration,
+    ) -> Result<(), Error> {
+        if from.name() != to.name() {
+            return Err(Error::InvalidArguments(format!(
+                "cannot update {from} to {to} with a different name"
+            )));
+        }
+        if self.updates.contains_key(from.name()) {
+            return Err(Error::InvalidArguments(format!(
+                "update of {} in progress",
+                from.name()
+            )));
+        }
+        let arguments = match self.state(from)?.process.as_ref() {
+            Some(process) => process.arguments.clone(),
+            None => return Err(Error::ContainerNotStarted(from.clone())),
+        };
+
+        let installed = self.install(repository, rx).await?;
+        if &installed != to {
+            self.request_uninstall(&installed, false, None).await;
+            return Err(Error::InvalidArguments(format!(
+                "npk contains {installed} instead of {to}"
+            )));
+        }
+
+        info!(
+            "Updating {} to {} with a probation of {:?}",
+            from, to, probation
+        );
+        let update = Update {
+            from: from.clone(),
+            to: to.clone(),
+            arguments,
+            probation,
+            phase: UpdatePhase::Stopping,
+        };
+        self.updates.insert(from.name().clone(), update);
+        self.container_event(
+            to,
+            ContainerEvent::Update(from.clone(), UpdateProgress::Stopping),
+        );
+        self.stop(from, None).await
+    }
+
+    /// Continue the update of `container` after it exited
+    async fn on_update_exit(&mut self, container: &Container) -> Result<(), Error> {
+        let update = match self.updates.get(container.name()) {
+            Some(update) => update,
+            None => return Ok(()),
+        };
+        let (from, to, probation) = (update.from.clone(), update.to.clone(), update.probation);
+        let arguments = update.arguments.clone();
+
+        match update.phase {
+            // The previous version is gone. Start the new one.
+            UpdatePhase::Stopping if &from == container => {
+                if let Err(e) = self.start(&to, arguments).await {
+                    warn!("Failed to start {}: {}", to, e);
+                    return self.rollback(container.name()).await;
+                }
+
+                let timer = CancellationToken::new();
+                task::spawn({
+                    let timer = timer.clone();
+                    let events_tx = self.events_tx.clone();
+                    let to = to.clone();
+                    async move {
+                        select! {
+                            _ = timer.cancelled() => (),
+                            _ = time::sleep(probation) => {
+                                // The event loop is gone if the runtime shuts down.
+                                events_tx.send(Event::Probation(to)).await.ok();
+                            }
+                        }
+                    }
+                });
+
+                if let Some(update) = self.updates.get_mut(container.name()) {
+                    update.phase = UpdatePhase::Probation {
+                        _timer: timer.drop_guard(),
+                    };
+                }
+                self.container_event(&to, ContainerEvent::Update(from, UpdateProgress::Probation));
+                Ok(())
+            }
+            UpdatePhase::Probation { .. } | UpdatePhase::RollingBack if &to == container => {
+                warn!("Container {} failed within the probation period", to);
+                self.rollback(container.name()).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Uninstall the new version of the update of `name` and restart the previous version
+    async fn rollback(&mut self, name: &Name) -> Result<(), Error> {
+        let Update {
+            from,
+            to,
+            arguments,
+            ..
+        } = match self.updates.remove(name) {
+            Some(update) => update,
+            None => return Ok(()),
+        };
+
+        warn!("Rolling back {} to {}", to, from);
+        self.request_uninstall(&to, false, None).await;
+        if let Err(e) = self.start(&from, arguments).await {
+            warn!("Failed to restart {}: {}", from, e);
+        }
+        self.container_event(
+            &to,
+            ContainerEvent::Update(from, UpdateProgress::RolledBack),
+        );
+        Ok(())
+    }
+
+    /// Complete the update to `container` once it passed the probation period
+    pub(super) async fn on_probation(&mut self, container: &Container) -> Result<(), Error> {
+        match self.updates.get(container.name()) {
+            Some(Update {
+                to,
+                phase: UpdatePhase::Probation { .. },
+                ..
+            }) if to == container => (),
+            _ => return Ok(()),
+        }
+
+        let Update { from, to, .. } = self
+            .updates
+            .remove(container.name())
+            .expect("internal error");
+        info!("Updated {} to {}", from, to);
+
+        // The previous version is no longer needed
+        self.request_uninstall(&from, false, None).await;
+        self.container_event(&to, ContainerEvent::Update(from, UpdateProgress::Completed));
+        Ok(())
+    }
+
+    /// Handle the exit of a container
+    async fn on_exit(
+        &mut self,
+        container: &Container,
+        exit_status: &ExitStatus,
+        reason: ExitReason,
+        is_shutdown: bool,
+    ) -> Result<(), Error> {
+        let manifest = self.manifest(container)?;
+        let autostart = manifest.autostart.clone();
+        let reset_after = manifest.restart.as_ref().map(|restart| restart.reset_after);
+        let health_restart = manifest.health.as_ref().map(|h| h.restart).unwrap_or(false);
+
+        if let Ok(state) = self.state_mut(container) {
+            if let Some(mut process) = state.process.take() {
+                let is_critical = autostart == Some(Autostart::Critical);
+                let is_critical = is_critical && !is_shutdown;
+                let duration = process.started.elapsed();
+
+                // The process ran long enough to not count as a consecutive restart
+                if reset_after.map(|r| duration >= r).unwrap_or(false) {
+                    state.restarts.retries = 0;
+                    state.restarts.critical = 0;
+                }
+
+                if is_critical {
+                    error!(
+                        "Critical process {} exited after {:?} with status {}",
+                        container, duration, exit_status,
+                    );
+                } else {
+                    info!(
+                        "Process {} exited after {:?} with status {}",
+                        container, duration, exit_status,
+                    );
+                }
+
+                let killed = process.killed
+                    || matches!(
+                        (process.kill_signal, exit_status),
+                        (Some(signal), ExitStatus::Signalled(s)) if signal as u8 == *s
+                    );
+                let reason = match process.exit_reason {
+                    Some(reason) => reason,
+                    None if killed => ExitReason::Stopped,
+                    None => reason,
+                };
+                let run = model::Run {
+                    started: (SystemTime::now() - duration)
+                        .duration_since(UNIX_EPOCH)
+                        .map(|started| started.as_millis() as u64)
+                        .unwrap_or_default(),
+                    duration: duration.as_millis() as u64,
+                    exit_status: exit_status.clone().into(),
+                    reason: reason.into(),
+                    oom_kills: process.oom_kills,
+                    arguments: model::JournalStart::from(&process.arguments),
+                };
+                let pid = process.pid;
+                let is_hook = process.arguments.hook;
+                let arguments = process.arguments.clone();
+                let unhealthy = health_restart
+                    && process.health.as_ref().and_then(|monitor| monitor.health)
+                        == Some(Health::Unhealthy);
+                let replies = process
+                    .stopping
+                    .take()
+                    .map(|stopping| stopping.replies)
+                    .unwrap_or_default();
+                let ready_replies = process
+                    .pending_ready
+                    .take()
+                    .map(|pending| pending.replies)
+                    .unwrap_or_default();
+                process.destroy().await;
+
+                self.container_event(container, ContainerEvent::Exit(exit_status.clone(), reason));
+                self.history
+                    .record(container, run, self.config.history.runs)
+                    .await;
+
+                // Remove the oldest core dumps. The container can write to the directory
+                // without crashing.
+                if let Some(configuration) = &self.config.core_dumps {
+                    let dir = core_dumps::dir(configuration, container.name());
+                    let (retention, quota) = (configuration.retention, configuration.quota);
+                    if let Err(e) = core_dumps::retain(&dir, retention, quota).await {
+                        warn!("Failed to remove core dumps of {}: {:#}", container, e);
+                    }
+                }
+
+                // Reply to pending stop requests
+                for reply in replies {
+                    let result = model::StopResult::Ok {
+                        container: container.clone(),
+                    };
+                    reply.send(model::Response::Stop(result)).ok();
+                }
+
+                // Reply to start requests that wait for the readiness
+                for reply in ready_replies {
+                    let error = format!("exited with status {exit_status} before it was ready");
+                    let error = Error::StartContainerFailed(container.clone(), error).into();
+                    reply.send(Self::start_response(container, Err(error))).ok();
+                }
+
+                info!("Container {} exited with status {}", container, exit_status);
+
+                // The hook that started the container waits for its exit status
+                if let Some(hook_exit) = self.state_mut(container)?.hook_exit.take() {
+                    hook_exit.send(exit_status.clone()).ok();
+                }
+
+                // Hooks are not run while the runtime shuts down
+                if !is_shutdown && !is_hook {
+                    let hooks = self.hooks(container, Stage::PostStop)?;
+                    if !hooks.is_empty() {
+                        let env = vec![
+                            (env::PID, pid.to_string()),
+                            (env::EXIT_STATUS, exit_status.to_string()),
+                        ];
+                        let hooks = vec![(container.clone(), hooks)];
+                        self.run_hooks(Stage::PostStop, hooks, env, Action::None);
+                    }
+                }
+
+                // Containers that are terminated on request or during shutdown are not restarted
+                let restart = !is_shutdown
+                    && !killed
+                    && self.schedule_restart(
+                        container,
+                        arguments.clone(),
+                        exit_status.success(),
+                        unhealthy,
+                    );
+
+                // Containers that are not restarted are no longer started in the journal
+                if !restart {
+                    self.journal(container, |entry| entry.started = None).await;
+                }
+
+                // Start a scheduled run that is deferred until the container exited
+                let deferred = self
+                    .state_mut(container)?
+                    .schedule
+                    .as_mut()
+                    .map(|schedule| std::mem::take(&mut schedule.deferred))
+                    .unwrap_or(false);
+                if deferred && !is_shutdown && !restart {
+                    let run = self.start_scheduled(container).await;
+                    self.container_event(container, ContainerEvent::Schedule(run));
+                }
+
+                // Pending starts that depend on this container fail
+                if !is_shutdown {
+                    self.start_pending().await;
+                }
+
+                // This is a critical flagged container that exited with a error exit code. That's not good...
+                if !exit_status.success() && is_critical && !restart {
+                    self.on_critical(container, exit_status, arguments).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Carry out the critical policy of `container` that exited with `exit_status`. The
+    /// chosen action is notified before it is carried out. The runtime exits if no policy
+    /// is configured or the action is not possible.
+    async fn on_critical(
+        &mut self,
+        container: &Container,
+        exit_status: &ExitStatus,
+        arguments: StartArguments,
+    ) -> Result<(), Error> {
+        let action = match self.manifest(container)?.critical.clone() {
+            Some(Critical::Restart { max_retries }) => {
+                let restarts = &mut self.state_mut(container)?.restarts;
+                if restarts.critical < max_retries {
+                    restarts.critical += 1;
+                    CriticalAction::Restart(restarts.critical)
+                } else {
+                    warn!(
+                        "Giving up restarting critical container {} after {} retries",
+                        container, max_retries
+                    );
+                    CriticalAction::Exit
+                }
+            }
+            Some(Critical::RestartRuntime) => CriticalAction::RestartRuntime,
+            Some(Critical::FailureHandler {
+                container: name,
+                version,
+            }) => match self.match_dependency(&name, &version) {
+                Some(handler) => CriticalAction::FailureHandler(handler.clone()),
+                None => {
+                    warn!(
+                        "Failure handler {} {} of {} is not installed",
+                        name,
+                        version.to_string(),
+                        container
+                    );
+                    CriticalAction::Exit
+                }
+            },
+            Some(Critical::Reboot) if self.config.reboot_command.is_some() => {
+                CriticalAction::Reboot
+            }
+            Some(Critical::Reboot) => {
+                warn!(
+                    "Cannot reboot for {}: no reboot command configured",
+                    container
+                );
+                CriticalAction::Exit
+            }
+            None => CriticalAction::Exit,
+        };
+
+        warn!("Critical container {} failed: {:?}", container, action);
+        self.container_event(container, ContainerEvent::Critical(action.clone()));
+
+        let exit = Error::CriticalContainer(container.clone(), exit_status.clone());
+        match action {
+            CriticalAction::Restart(_) => {
+                self.pend_restart(container, arguments, Duration::ZERO);
+                Ok(())
+            }
+            CriticalAction::RestartRuntime => {
+                // The event loop is busy with the current event
+                let events_tx = self.events_tx.clone();
+                let container = container.clone();
+                task::spawn(async move {
+                    events_tx.send(Event::RestartRuntime(container)).await.ok();
+                });
+                Ok(())
+            }
+            CriticalAction::FailureHandler(handler) => {
+                match self.start(&handler, StartArguments::default()).await {
+                    Ok(()) | Err(Error::StartContainerStarted(_)) => Ok(()),
+                    Err(e) => {
+                        error!("Failed to start failure handler {}: {}", handler, e);
+                        Err(exit)
+                    }
+                }
+            }
+            CriticalAction::Reboot => {
+                // The reboot command is not awaited in the event loop. A failure is reported
+                // back with an event.
+                let command = self.config.reboot_command.clone().unwrap_or_default();
+                let events_tx = self.events_tx.clone();
+                let container = container.clone();
+                let exit_status = exit_status.clone();
+                task::spawn(async move {
+                    let (program, args) = command.split_first().expect("empty reboot command");
+                    match process::Command::new(program.as_str())
+                        .args(args.iter().map(|arg| arg.as_str()))
+                        .status()
+                        .await
+                    {
+                        Ok(status) if status.success() => return,
+                        Ok(status) => error!("Reboot command exited with {}", status),
+                        Err(e) => error!("Failed to execute reboot command: {}", e),
+                    }
+                    // The event loop is gone if the runtime shuts down.
+                    events_tx
+                        .send(Event::RebootFailed(container, exit_status))
+                        .await
+                        .ok();
+                });
+                Ok(())
+            }
+            CriticalAction::Exit => Err(exit),
+        }
+    }
+
+    /// Schedule a restart of `container` according to its restart policy. Returns
+    /// true if a restart is scheduled.
+    ///
+    /// Unhealthy containers are restarted regardless of the policy and immediately if no
+    /// restart policy is configured.
+    fn schedule_restart(
+        &mut self,
+        container: &Container,
+        arguments: StartArguments,
+        success: bool,
+        unhealthy: bool,
+    ) -> bool {
+        let restart = match self.manifest(container).map(|m| m.restart.clone()) {
+            Ok(Some(restart)) if unhealthy || restart.applies(success) => restart,
+            Ok(None) if unhealthy => Restart {
+                policy: RestartPolicy::Always,
+                max_retries: None,
+                backoff: Duration::ZERO,
+                max_backoff: Duration::ZERO,
+                reset_after: Duration::ZERO,
+            },
+            _ => return false,
+        };
+
+        let retries = self
+            .state(container)
+            .expect("internal error")
+            .restarts
+            .retries;
+        if restart
+            .max_retries
+            .map(|max| retries >= max)
+            .unwrap_or(false)
+        {
+            warn!(
+                "Giving up restarting {} after {} retries",
+                container, retries
+            );
+            self.container_event(container, ContainerEvent::RestartExhausted(retries));
+            return false;
+        }
+
+        let delay = restart.delay(retries);
+        info!(
+            "Restarting {} in {:?} (retry {})",
+            container,
+            delay,
+            retries + 1
+        );
+
+        self.state_mut(container)
+            .expect("internal error")
+            .restarts
+            .retries = retries + 1;
+        self.pend_restart(container, arguments, delay);
+        true
+    }
+
+    /// Restart `container` with `arguments` after `delay`
+    fn pend_restart(&mut self, container: &Container, arguments: StartArguments, delay: Duration) {
+        let timer = CancellationToken::new();
+        task::spawn({
+            let timer = timer.clone();
+            let events_tx = self.events_tx.clone();
+            let container = container.clone();
+            async move {
+                select! {
+                    _ = timer.cancelled() => (),
+                    _ = time::sleep(delay) => {
+                        // The event loop is gone if the runtime shuts down.
+                        events_tx.send(Event::Restart(container)).await.ok();
+                    }
+                }
+            }
+        });
+
+        let state = self.state_mut(container).expect("internal error");
+        state.restarts.pending = Some(PendingRestart {
+            at: time::Instant::now() + delay,
+            arguments,
+            _timer: timer.drop_guard(),
+        });
+    }
+
+    /// Arm the timer of the next scheduled run of `container` if the manifest contains
+    /// a schedule
+    fn schedule(&mut self, container: &Container) {
+        let schedule = match self.manifest(container).map(|m| m.schedule.clone()) {
+            Ok(Some(schedule)) => schedule,
+            _ => return,
+        };
+
+        let now = SystemTime::now();
+        let next = match schedule.next(now) {
+            Some(next) => next,
+            None => {
+                warn!("Schedule of {} does not trigger", container);
+                return;
+            }
+        };
+        let jitter = match schedule.jitter.as_millis() as u64 {
+            0 => Duration::ZERO,
+            jitter => Duration::from_millis(OsRng.next_u64() % (jitter + 1)),
+        };
+        let next = next + jitter;
+        let delay = next.duration_since(now).unwrap_or_default();
+        debug!("Next scheduled run of {} in {:?}", container, delay);
+
+        let timer = CancellationToken::new();
+        task::spawn({
+            let timer = timer.clone();
+            let events_tx = self.events_tx.clone();
+            let container = container.clone();
+            async move {
+                select! {
+                    _ = timer.cancelled() => (),
+                    _ = time::sleep(delay) => {
+                        // The event loop is gone if the runtime shuts down.
+                        events_tx.send(Event::Schedule(container)).await.ok();
+                    }
+                }
+            }
+        });
+
+        let state = self.state_mut(container).expect("internal error");
+        let deferred = state
+            .schedule
+            .as_ref()
+            .map(|schedule| schedule.deferred)
+            .unwrap_or(false);
+        state.schedule = Some(Scheduled {
+            next,
+            deferred,
+            _timer: timer.drop_guard(),
+        });
+    }
+
+    /// Handle a due scheduled run
+    pub(super) async fn on_schedule(&mut self, container: &Container) -> Result<(), Error> {
+        // The container might be uninstalled in the meantime
+        let skip_if_running = match self.manifest(container).map(|m| m.schedule.as_ref()) {
+            Ok(Some(schedule)) => schedule.skip_if_running,
+            _ => return Ok(()),
+        };
+
+        let run = if self.state(container)?.process.is_none() {
+            self.start_scheduled(container).await
+        } else if skip_if_running {
+            info!("Skipping scheduled run of {}: still running", container);
+            ScheduledRun::Skipped
+        } else {
+            info!("Deferring scheduled run of {} until it exited", container);
+            if let Some(schedule) = self.state_mut(container)?.schedule.as_mut() {
+                schedule.deferred = true;
+            }
+            ScheduledRun::Deferred
+        };
+        self.container_event(container, ContainerEvent::Schedule(run));
 
-        Ok(container)
+        self.schedule(container);
+        Ok(())
     }
 
-    /// Remove and umount a specific app
-    async fn uninstall(&mut self, container: &Container, wipe: bool) -> Result<(), Error> {
-        info!("Trying to uninstall {}", container);
+    /// Start a scheduled run of `container` with the arguments of the manifest
+    async fn start_scheduled(&mut self, container: &Container) -> ScheduledRun {
+        info!("Starting scheduled run of {}", container);
+        match self.start(container, StartArguments::default()).await {
+            Ok(()) => ScheduledRun::Started,
+            Err(e) => {
+                warn!("Failed to start scheduled run of {}: {}", container, e);
+                ScheduledRun::Failed(e.into())
+            }
+        }
+    }
 
-        let state = self.state(container)?;
-        let repository = state.repository.clone();
+    /// Next runs of the containers with a schedule
+    fn schedules(&self) -> HashMap<Container, model::Schedule> {
+        self.containers
+            .iter()
+            .filter_map(|(container, state)| {
+                state.schedule.as_ref().map(|schedule| {
+                    let next = schedule
+                        .next
+                        .duration_since(UNIX_EPOCH)
+                        .map(|next| next.as_millis() as u64)
+                        .unwrap_or_default();
+                    let schedule = model::Schedule {
+                        next,
+                        deferred: schedule.deferred,
+                    };
+                    (container.clone(), schedule)
+                })
+            })
+            .collect()
+    }
 
-        // Umount
-        if state.is_mounted() {
-            self.umount_all(&[container.clone()])
-                .await
-                .pop()
-                .expect("internal error")?;
+    /// Handle a health change reported by the health monitor
+    async fn on_health(&mut self, container: &Container, health: Health) -> Result<(), Error> {
+        let restart = self
+            .manifest(container)?
+            .health
+            .as_ref()
+            .map(|h| h.restart)
+            .unwrap_or(false);
+
+        let process = match self.state_mut(container)?.process.as_mut() {
+            Some(process) => process,
+            // The container exited in the meantime
+            None => return Ok(()),
+        };
+        if let Some(monitor) = process.health.as_mut() {
+            monitor.health = Some(health);
         }
+        let paused = process.paused;
 
-        // Remove from repository
-        debug!("Removing {} from {}", container, repository);
-        self.repositories
-            .get_mut(&repository)
-            .expect("Internal error")
-            .remove(container)
-            .await?;
+        match health {
+            Health::Healthy => info!("Container {} is healthy", container),
+            Health::Unhealthy => warn!("Container {} is unhealthy", container),
+        }
+        self.container_event(container, ContainerEvent::Health(health));
 
-        // Wipe persistent dir if present
-        if wipe {
-            let name: &str = container.name().as_ref();
-            let dir = self.config.data_dir.join(name);
-            if dir.exists() {
-                info!(
-                    "Wiping persistent data dir {} of {}",
-                    dir.display(),
-                    container
-                );
-                if let Err(e) = fs::remove_dir_all(&dir)
-                    .await
-                    .with_context(|| format!("failed to remove {}", dir.display()))
+        // Probes of paused containers are expected to fail
+        if health == Health::Unhealthy && restart && !paused {
+            info!("Terminating unhealthy container {}", container);
+            self.kill(container, Signal::SIGKILL).await?;
+        }
+
+        // An unhealthy container on probation is rolled back once it exited
+        if health == Health::Unhealthy {
+            if let Some(update) = self.updates.get_mut(container.name()) {
+                if &update.to == container && matches!(update.phase, UpdatePhase::Probation { .. })
                 {
-                    // If the runtime fails to remove the data dir leave it behind.
-                    // This cannot be handled.
-                    // In theory this should never happen with the cap_dac capability.
-                    warn!("Failed to remove {}: {}", dir.display(), e);
+                    update.phase = UpdatePhase::RollingBack;
+                    self.stop(container, None).await?;
                 }
             }
         }
-
-        self.containers.remove(container);
-        info!("Uninstalled {}", container);
-
-        self.container_event(container, ContainerEvent::Uninstalled);
-
         Ok(())
     }
 
-    /// Handle the exit of a container
-    async fn on_exit(
-        &mut self,
-        container: &Container,
-        exit_status: &ExitStatus,
-        is_shutdown: bool,
-    ) -> Result<(), Error> {
-        let autostart = self.manifest(container)?.autostart.clone();
-
-        if let Ok(state) = self.state_mut(container) {
-            if let Some(process) = state.process.take() {
-                let is_critical = autostart == Some(Autostart::Critical);
-                let is_critical = is_critical && !is_shutdown;
-                let duration = process.started.elapsed();
-                if is_critical {
-                    error!(
-                        "Critical process {} exited after {:?} with status {}",
-                        container, duration, exit_status,
-                    );
-                } else {
-                    info!(
-                        "Process {} exited after {:?} with status {}",
-                        container, duration, exit_status,
-                    );
-                }
-
-                process.destroy().await;
-
-                self.container_event(container, ContainerEvent::Exit(exit_status.clone()));
+    /// Forward a heartbeat to the health monitor of `container`
+    pub(super) fn on_heartbeat(&mut self, container: &Container) -> Result<(), Error> {
+        if let Some(monitor) = self
+            .state(container)
+            .ok()
+            .and_then(|state| state.process.as_ref())
+            .and_then(|process| process.health.as_ref())
+        {
+            monitor.heartbeat();
+        }
+        Ok(())
+    }
 
-                info!("Container {} exited with status {}", container, exit_status);
+    /// Handle a scheduled restart
+    pub(super) async fn on_restart(&mut self, container: &Container) -> Result<(), Error> {
+        // The container might be uninstalled or started in the meantime
+        let pending = match self.state_mut(container) {
+            Ok(state) if state.process.is_none() => state.restarts.pending.take(),
+            _ => None,
+        };
 
-                // This is a critical flagged container that exited with a error exit code. That's not good...
-                if !exit_status.success() && is_critical {
-                    return Err(Error::CriticalContainer(
-                        container.clone(),
-                        exit_status.clone(),
-                    ));
-                }
+        if let Some(PendingRestart { arguments, .. }) = pending {
+            if let Err(e) = self.start(container, arguments.clone()).await {
+                warn!("Failed to restart {}: {}", container, e);
+                self.schedule_restart(container, arguments, false, false);
             }
         }
         Ok(())
@@ -864,13 +3441,38 @@ impl State {
     ) -> Result<(), Error> {
         match event {
             ContainerEvent::Started => (),
-            ContainerEvent::Exit(exit_status) => {
-                self.on_exit(container, exit_status, is_shutdown).await?;
+            ContainerEvent::Ready => (),
+            ContainerEvent::Exit(exit_status, reason) => {
+                self.on_exit(container, exit_status, *reason, is_shutdown)
+                    .await?;
+                if !is_shutdown {
+                    self.on_update_exit(container).await?;
+                }
             }
             ContainerEvent::Installed => (),
             ContainerEvent::Uninstalled => (),
-            ContainerEvent::CGroup(CGroupEvent::Memory(_)) => {
+            ContainerEvent::RestartExhausted(_) => (),
+            ContainerEvent::Health(health) => self.on_health(container, *health).await?,
+            ContainerEvent::Paused => (),
+            ContainerEvent::Resumed => (),
+            ContainerEvent::Update(..) => (),
+            ContainerEvent::Critical(_) => (),
+            ContainerEvent::Schedule(_) => (),
+            ContainerEvent::CGroup(CGroupEvent::Memory(memory)) => {
                 warn!("Process {} is out of memory", container);
+                if let Some(process) = self
+                    .state_mut(container)
+                    .ok()
+                    .and_then(|state| state.process.as_mut())
+                {
+                    match memory.oom_kill {
+                        // The cgroups v2 memory events count the kills since the start
+                        Some(oom_kill) => process.oom_kills = process.oom_kills.max(oom_kill),
+                        // The cgroups v1 oom control signals each oom event
+                        None if memory.oom.is_some() => process.oom_kills += 1,
+                        None => (),
+                    }
+                }
             }
         }
 
@@ -889,20 +3491,15 @@ impl State {
                     model::Request::List => model::Response::List(self.list_containers()),
                     model::Request::Install { .. } => unreachable!(),
                     model::Request::Mount { containers } => {
-                        let result = self
-                            .mount_all(containers)
-                            .await
-                            .drain(..)
-                            .zip(containers)
-                            .map(|(r, c)| match r {
-                                Ok(r) => model::MountResult::Ok { container: r },
-                                Err(e) => model::MountResult::Error {
-                                    container: c.clone(),
-                                    error: e.into(),
-                                },
-                            })
-                            .collect();
-                        model::Response::Mount(result)
+                        // The reply is sent once the pre mount hooks passed
+                        let hooks = self.pre_mount_hooks(containers);
+                        if !hooks.is_empty() {
+                            let action = Action::Mount(containers.clone(), Some(response));
+                            self.run_hooks(Stage::PreMount, hooks, Vec::new(), action);
+                            return Ok(());
+                        }
+                        let results = self.mount_all(containers).await;
+                        Self::mount_response(containers, results)
                     }
                     model::Request::Umount { containers } => {
                         let result = self
@@ -920,10 +3517,39 @@ impl State {
                             .collect();
                         model::Response::Umount(result)
                     }
+                    model::Request::Journal { clear } => {
+                        let result = match self.journal.as_mut() {
+                            Some(journal) if *clear => model::JournalResult::Ok {
+                                journal: journal.clear().await,
+                            },
+                            Some(journal) => model::JournalResult::Ok {
+                                journal: journal.journal().clone(),
+                            },
+                            None => model::JournalResult::Error {
+                                error: Error::Configuration("journal is disabled".into()).into(),
+                            },
+                        };
+                        model::Response::Journal(result)
+                    }
                     model::Request::Repositories => {
                         let repositories = self.repositories.keys().cloned().collect();
                         model::Response::Repositories(repositories)
                     }
+                    model::Request::Schedules => model::Response::Schedules(self.schedules()),
+                    model::Request::Budgets => model::Response::Budgets(self.budgets()),
+                    model::Request::CoreDumps { container } => {
+                        let result = match self.core_dumps(container).await {
+                            Ok(core_dumps) => model::CoreDumpsResult::Ok {
+                                container: container.clone(),
+                                core_dumps,
+                            },
+                            Err(e) => model::CoreDumpsResult::Error {
+                                container: container.clone(),
+                                error: e.into(),
+                            },
+                        };
+                        model::Response::CoreDumps(result)
+                    }
                     model::Request::Shutdown => {
                         self.events_tx
                             .send(Event::Shutdown)
@@ -936,14 +3562,44 @@ impl State {
                         init,
                         arguments,
                         environment,
+                        max_runtime,
+                        wait_ready,
                     } => {
-                        let result = match self
-                            .start(container, init.clone(), arguments, environment)
-                            .await
-                        {
-                            Ok(_) => model::StartResult::Ok {
-                                container: container.clone(),
-                            },
+                        let arguments = StartArguments {
+                            init: init.clone(),
+                            args: arguments.clone(),
+                            env: environment.clone(),
+                            max_runtime: max_runtime.map(Duration::from_secs),
+                            ..Default::default()
+                        };
+                        let result = match self.start(container, arguments).await {
+                            Ok(_) => {
+                                // A manual start resets the restart bookkeeping
+                                let state = self.state_mut(container)?;
+                                state.restarts = Restarts::default();
+
+                                // The reply is sent once the container is started or ready
+                                if let Some(pending) = state.pending_start.as_mut() {
+                                    if *wait_ready {
+                                        pending.ready_replies.push(response);
+                                    } else {
+                                        pending.replies.push(response);
+                                    }
+                                    return Ok(());
+                                }
+                                if let Some(pending) = state
+                                    .process
+                                    .as_mut()
+                                    .and_then(|process| process.pending_ready.as_mut())
+                                    .filter(|_| *wait_ready)
+                                {
+                                    pending.replies.push(response);
+                                    return Ok(());
+                                }
+                                model::StartResult::Ok {
+                                    container: container.clone(),
+                                }
+                            }
                             Err(e) => {
                                 warn!("failed to start {}: {}", container, e);
                                 model::StartResult::Error {
@@ -957,9 +3613,24 @@ impl State {
                     model::Request::Kill { container, signal } => {
                         let result = match Signal::try_from(*signal) {
                             Ok(signal) => match self.kill(container, signal).await {
-                                Ok(_) => model::KillResult::Ok {
-                                    container: container.clone(),
-                                },
+                                Ok(_) => {
+                                    // Containers terminated on request are not restarted.
+                                    // Other signals might be handled by the container.
+                                    if let Some(process) =
+                                        self.state_mut(container)?.process.as_mut()
+                                    {
+                                        match signal {
+                                            Signal::SIGKILL
+                                            | Signal::SIGTERM
+                                            | Signal::SIGINT
+                                            | Signal::SIGQUIT => process.killed = true,
+                                            signal => process.kill_signal = Some(signal),
+                                        }
+                                    }
+                                    model::KillResult::Ok {
+                                        container: container.clone(),
+                                    }
+                                }
                                 Err(e) => {
                                     error!("failed to kill {} with {}: {}", container, signal, e);
                                     model::KillResult::Error {
@@ -981,21 +3652,80 @@ impl State {
                         };
                         model::Response::Kill(result)
                     }
-                    model::Request::Uninstall { container, wipe } => {
-                        let result = match self.uninstall(container, *wipe).await {
-                            Ok(_) => model::UninstallResult::Ok {
+                    model::Request::Pause { container } => {
+                        let result = match self.pause(container) {
+                            Ok(_) => model::PauseResult::Ok {
+                                container: container.clone(),
+                            },
+                            Err(e) => {
+                                warn!("failed to pause {}: {}", container, e);
+                                model::PauseResult::Error {
+                                    container: container.clone(),
+                                    error: e.into(),
+                                }
+                            }
+                        };
+                        model::Response::Pause(result)
+                    }
+                    model::Request::Resume { container } => {
+                        let result = match self.resume(container) {
+                            Ok(_) => model::ResumeResult::Ok {
                                 container: container.clone(),
                             },
                             Err(e) => {
-                                warn!("failed to uninstall {}: {}", container, e);
-                                model::UninstallResult::Error {
+                                warn!("failed to resume {}: {}", container, e);
+                                model::ResumeResult::Error {
+                                    container: container.clone(),
+                                    error: e.into(),
+                                }
+                            }
+                        };
+                        model::Response::Resume(result)
+                    }
+                    model::Request::Stop { container, timeout } => {
+                        let timeout = timeout.map(Duration::from_secs);
+                        let result = match self.stop(container, timeout).await {
+                            Ok(_) => {
+                                // The reply is sent once the container exited
+                                if let Some(stopping) = self
+                                    .state_mut(container)?
+                                    .process
+                                    .as_mut()
+                                    .and_then(|process| process.stopping.as_mut())
+                                {
+                                    stopping.replies.push(response);
+                                    return Ok(());
+                                }
+                                model::StopResult::Ok {
+                                    container: container.clone(),
+                                }
+                            }
+                            Err(e) => {
+                                warn!("failed to stop {}: {}", container, e);
+                                model::StopResult::Error {
                                     container: container.clone(),
                                     error: e.into(),
                                 }
                             }
                         };
-                        model::Response::Uninstall(result)
+                        model::Response::Stop(result)
+                    }
+                    model::Request::Uninstall { container, wipe } => {
+                        // The reply is sent once the pre uninstall hooks passed
+                        self.request_uninstall(container, *wipe, Some(response))
+                            .await;
+                        return Ok(());
                     }
+                    model::Request::History { container } => match self.state(container) {
+                        Ok(_) => model::Response::History(model::HistoryResult::Ok {
+                            container: container.clone(),
+                            runs: self.history.runs(container),
+                        }),
+                        Err(e) => model::Response::History(model::HistoryResult::Error {
+                            container: container.clone(),
+                            error: e.into(),
+                        }),
+                    },
                     model::Request::Inspect { container } => match self.inspect(container) {
                         Ok(data) => model::Response::Inspect(model::InspectResult::Ok {
                             container: container.clone(),
@@ -1009,6 +3739,14 @@ impl State {
                     model::Request::Ident => unreachable!(), // handled in module console
                     model::Request::TokenCreate { .. } => unreachable!(), // handled in module console
                     model::Request::TokenVerify { .. } => unreachable!(), // handled in module console
+                    model::Request::Heartbeat => unreachable!(), // handled in module console
+                    model::Request::Notify { .. } => unreachable!(), // handled in module console
+                    model::Request::Attach { .. } => unreachable!(), // see Request::Attach
+                    model::Request::Update { .. } => unreachable!(), // see Request::Update
+                    model::Request::Exec { .. } => unreachable!(), // see Request::Exec
+                    model::Request::Logs { .. } => unreachable!(), // see Request::Logs
+                    model::Request::CoreDump { .. } => unreachable!(), // see Request::CoreDump
+                    model::Request::ReloadConfig => unreachable!(), // handled in module console
                 };
 
                 // A error on the response_tx means that the connection
@@ -1029,10 +3767,143 @@ impl State {
                 // was closed in the meantime. Ignore it.
                 response.send(payload).ok();
             }
+            Request::Update {
+                from,
+                to,
+                repository,
+                probation,
+                mut rx,
+            } => {
+                let result = match self
+                    .update(&from, &to, &repository, &mut rx, probation)
+                    .await
+                {
+                    Ok(()) => model::UpdateResult::Ok { from, to },
+                    Err(e) => {
+                        warn!("Failed to update {} to {}: {}", from, to, e);
+                        model::UpdateResult::Error {
+                            container: from,
+                            error: e.into(),
+                        }
+                    }
+                };
+
+                // A error on the response_tx means that the connection
+                // was closed in the meantime. Ignore it.
+                response.send(model::Response::Update(result)).ok();
+            }
+            Request::Attach { container, attach } => {
+                let result = match self.attach(&container) {
+                    Ok(attachment) => {
+                        attach.send(attachment).ok();
+                        model::AttachResult::Ok { container }
+                    }
+                    Err(e) => model::AttachResult::Error {
+                        container,
+                        error: e.into(),
+                    },
+                };
+
+                // A error on the response_tx means that the connection
+                // was closed in the meantime. Ignore it.
+                response.send(model::Response::Attach(result)).ok();
+            }
+            Request::Exec {
+                container,
+                path,
+                args,
+                env,
+                tty,
+                io,
+                exit,
+            } => {
+                let exec = match self.exec_io(&container, tty) {
+                    Ok((fds, exec_io)) => self
+                        .exec(&container, path, args, env, tty, fds, exit)
+                        .await
+                        .map(|pid| (pid, exec_io)),
+                    Err(e) => Err(e),
+                };
+                let result = match exec {
+                    Ok((pid, exec_io)) => {
+                        // The connection might be closed in the meantime
+                        io.send(exec_io).ok();
+                        model::ExecResult::Ok { container, pid }
+                    }
+                    Err(e) => {
+                        warn!("failed to exec in {}: {}", container, e);
+                        model::ExecResult::Error {
+                            container,
+                            error: e.into(),
+                        }
+                    }
+                };
+
+                // A error on the response_tx means that the connection
+                // was closed in the meantime. Ignore it.
+                response.send(model::Response::Exec(result)).ok();
+            }
+            Request::Logs {
+                container,
+                tail,
+                since,
+                follow,
+            } => {
+                let result = match self.logs(&container, tail, since, follow) {
+                    Ok(lines) => model::LogsResult::Ok { container, lines },
+                    Err(e) => model::LogsResult::Error {
+                        container,
+                        error: e.into(),
+                    },
+                };
+
+                // A error on the response_tx means that the connection
+                // was closed in the meantime. Ignore it.
+                response.send(model::Response::Logs(result)).ok();
+            }
+            Request::CoreDump {
+                container,
+                name,
+                file,
+            } => {
+                let result = match self.core_dump(&container, &name, file).await {
+                    Ok(core_dump) => model::CoreDumpResult::Ok {
+                        container,
+                        core_dump,
+                    },
+                    Err(e) => model::CoreDumpResult::Error {
+                        container,
+                        error: e.into(),
+                    },
+                };
+
+                // A error on the response_tx means that the connection
+                // was closed in the meantime. Ignore it.
+                response.send(model::Response::CoreDump(result)).ok();
+            }
         }
         Ok(())
     }
 
+    /// Response to a mount request of `containers` with `results`
+    fn mount_response(
+        containers: &[Container],
+        results: Vec<Result<Container, Error>>,
+    ) -> model::Response {
+        let result = results
+            .into_iter()
+            .zip(containers)
+            .map(|(r, c)| match r {
+                Ok(r) => model::MountResult::Ok { container: r },
+                Err(e) => model::MountResult::Error {
+                    container: c.clone(),
+                    error: e.into(),
+                },
+            })
+            .collect();
+        model::Response::Mount(result)
+    }
+
     /// Try to mount all containers in `containers` in parallel and return the results. The parallelism
     /// is archived by a dedicated thread pool that executes the blocking mount operations on n threads
     /// as configured in the runtime configuration.
@@ -1067,6 +3938,7 @@ impl State {
                     let state = self.state_mut(container).expect("Internal error");
                     state.root = Some(root);
                     info!("Mounted {container}");
+                    self.journal(container, |entry| entry.mounted = true).await;
                     result.push(Ok(container.clone()));
                 }
                 Err(e) => {
@@ -1172,6 +4044,7 @@ impl State {
                     let state = self.state_mut(container).expect("Internal error");
                     state.root = None;
                     info!("Umounted {}", container);
+                    self.journal(container, |entry| entry.mounted = false).await;
                     result.push(Ok(container.clone()));
                 }
                 Err(e) => {
@@ -1191,6 +4064,56 @@ impl State {
         result
     }
 
+    /// Find the container that satisfies the dependency `name` and `version`. Started
+    /// containers are preferred.
+    fn match_dependency(&self, name: &Name, version: &VersionReq) -> Option<&Container> {
+        let started = self
+            .containers
+            .iter()
+            .filter(|(_, state)| state.process.is_some())
+            .map(|(container, _)| container);
+        State::match_container(name, version, started)
+            .or_else(|| State::match_container(name, version, self.containers.keys()))
+    }
+
+    /// Build the dependency graph of `containers` and their transitive dependencies.
+    /// Missing dependencies are an error unless `ignore_missing` is set.
+    fn dependency_graph<'a, I: IntoIterator<Item = &'a Container>>(
+        &self,
+        containers: I,
+        ignore_missing: bool,
+    ) -> Result<Graph, Error> {
+        let mut graph = Graph::new();
+        let mut queue = containers.into_iter().cloned().collect::<Vec<_>>();
+
+        while let Some(container) = queue.pop() {
+            if graph.contains_key(&container) {
+                continue;
+            }
+
+            let mut dependencies = HashSet::new();
+            for (name, dependency) in &self.manifest(&container)?.depends_on {
+                match self.match_dependency(name, &dependency.version) {
+                    Some(dependency) => {
+                        dependencies.insert(dependency.clone());
+                        queue.push(dependency.clone());
+                    }
+                    None if ignore_missing => (),
+                    None => {
+                        return Err(Error::StartContainerMissingDependency(
+                            container.clone(),
+                            name.clone(),
+                            dependency.version.to_string(),
+                        ))
+                    }
+                }
+            }
+            graph.insert(container, dependencies);
+        }
+
+        Ok(graph)
+    }
+
     /// Find a resource container that best matches the given version requirement.
     pub fn match_container<'a, I: Iterator<Item = &'a Container>>(
         name: &Name,
@@ -1215,19 +4138,73 @@ impl State {
         let process = runtime_info.map(|context| api::model::Process {
             pid: context.pid,
             uptime: context.started.elapsed().as_nanos() as u64,
+            paused: context.paused,
+            ready: context.ready,
+            status: context.status.clone(),
             statistics: context.cgroups.stats(),
         });
         let repository = state.repository.clone();
         let mounted = state.is_mounted();
+        let health = manifest
+            .health
+            .as_ref()
+            .and(state.process.as_ref())
+            .map(
+                |process| match process.health.as_ref().and_then(|monitor| monitor.health) {
+                    Some(health) => health.into(),
+                    None => api::model::Health::Unknown,
+                },
+            );
+        let restart = manifest.restart.as_ref().map(|_| api::model::Restart {
+            retries: state.restarts.retries,
+            next: state.restarts.pending.as_ref().map(|pending| {
+                pending
+                    .at
+                    .saturating_duration_since(time::Instant::now())
+                    .as_nanos() as u64
+            }),
+        });
 
         Ok(api::model::ContainerData {
             manifest,
             repository,
             mounted,
             process,
+            restart,
+            health,
         })
     }
 
+    /// Usage of the admission budgets by the started containers
+    fn budgets(&self) -> model::Budgets {
+        let per_repository = model::BudgetUsage {
+            used: 0,
+            limit: self.config.budgets.containers_per_repository,
+        };
+        let mut usage = budgets::usage(&self.config.budgets);
+        usage.repositories = self
+            .repositories
+            .keys()
+            .map(|repository| (repository.clone(), per_repository))
+            .collect();
+
+        for (container, state) in &self.containers {
+            if state.process.is_none() {
+                continue;
+            }
+            let demand = self.manifest(container).map(Demand::of).unwrap_or_default();
+            usage.memory.used += demand.memory.unwrap_or_default();
+            usage.cpu.used += demand.cpu.unwrap_or_default();
+            usage.containers.used += 1;
+            usage
+                .repositories
+                .entry(state.repository.clone())
+                .or_insert(per_repository)
+                .used += 1;
+        }
+        usage
+    }
+
     fn list_containers(&self) -> Vec<api::model::Container> {
         self.containers.keys().cloned().collect()
     }
@@ -1271,6 +4248,21 @@ impl State {
     }
 }
 
+/// Create the repository described by `configuration`
+async fn load_repository(configuration: &config::Repository) -> Result<Repository> {
+    let repository = match &configuration.r#type {
+        RepositoryType::Fs { dir } => {
+            let repository = DirRepository::new(dir, configuration).await?;
+            Box::new(repository) as Repository
+        }
+        RepositoryType::Memory => {
+            let repository = MemRepository::new(configuration).await?;
+            Box::new(repository) as Repository
+        }
+    };
+    Ok(repository)
+}
+
 /// Returns true if SELinux is enabled on the host system.
 fn is_selinux_enabled() -> bool {
     let enabled = Path::new("/sys/fs/selinux/enforce").exists();
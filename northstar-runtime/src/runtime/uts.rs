@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    common::container::Container,
+    npk::manifest::{uts, Manifest},
+};
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use nix::mount::{self, MntFlags, MsFlags};
+use tokio::fs;
+
+/// Options of the tmpfs with the generated files
+const TMPFS_OPTIONS: &str = "size=64k,mode=755";
+
+/// Tmpfs with the generated `/etc/hostname` and `/etc/hosts` of a container. The tmpfs
+/// is unmounted and removed on drop.
+#[derive(Debug)]
+pub(crate) struct Uts {
+    dir: PathBuf,
+}
+
+impl Drop for Uts {
+    fn drop(&mut self) {
+        debug!("Unmounting {}", self.dir.display());
+        if let Err(e) = mount::umount2(&self.dir, MntFlags::MNT_DETACH) {
+            warn!("Failed to unmount {}: {}", self.dir.display(), e);
+        }
+        if let Err(e) = std::fs::remove_dir(&self.dir) {
+            warn!("Failed to remove {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+/// Directory of the generated files of `container`
+pub(super) fn dir(run_dir: &Path, container: &Container) -> PathBuf {
+    run_dir.join(format!("{container}.uts"))
+}
+
+/// Hostname of `manifest` with the placeholders replaced
+pub(super) fn hostname(manifest: &Manifest) -> Option<String> {
+    manifest
+        .hostname
+        .as_ref()
+        .map(|template| uts::render(template, &manifest.name, &manifest.version))
+}
+
+/// Domainname of `manifest` with the placeholders replaced
+pub(super) fn domainname(manifest: &Manifest) -> Option<String> {
+    manifest
+        .domainname
+        .as_ref()
+        .map(|template| uts::render(template, &manifest.name, &manifest.version))
+}
+
+/// Mount a tmpfs and generate `hostname` and `hosts` if `manifest` has a hostname
+pub(super) async fn setup(run_dir: &Path, manifest: &Manifest) -> Result<Option<Uts>> {
+    let hostname = match hostname(manifest) {
+        Some(hostname) => hostname,
+        None => return Ok(None),
+    };
+    let dir = dir(run_dir, &manifest.container());
+
+    debug!("Mounting tmpfs on {}", dir.display());
+    fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("failed to create {}", dir.display()))?;
+    if let Err(e) = mount::mount(
+        Some("tmpfs"),
+        &dir,
+        Some("tmpfs"),
+        MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC,
+        Some(TMPFS_OPTIONS),
+    ) {
+        fs::remove_dir(&dir).await.ok();
+        return Err(e).with_context(|| format!("failed to mount tmpfs on {}", dir.display()));
+    }
+    // Unmounted on drop if the generation of the files fails
+    let uts = Uts { dir };
+
+    let hosts = hosts(&hostname, domainname(manifest).as_deref());
+    for (file, content) in [("hostname", format!("{hostname}\n")), ("hosts", hosts)] {
+        let path = uts.dir.join(file);
+        fs::write(&path, content)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+
+    Ok(Some(uts))
+}
+
+/// Content of `/etc/hosts` with the loopback addresses and the hostname
+fn hosts(hostname: &str, domainname: Option<&str>) -> String {
+    let names = match domainname {
+        Some(domainname) => format!("{hostname}.{domainname} {hostname}"),
+        None => hostname.to_string(),
+    };
+    format!("127.0.0.1\tlocalhost\n::1\tlocalhost ip6-localhost ip6-loopback\n127.0.1.1\t{names}\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hosts_file() {
+        assert!(hosts("hello", None).ends_with("127.0.1.1\thello\n"));
+        assert!(hosts("hello", Some("northstar")).ends_with("127.0.1.1\thello.northstar hello\n"));
+    }
+}
@@ -15,7 +15,10 @@ use northstar_runtime::{
 };
 use std::{
     convert::{TryFrom, TryInto},
-    env, fs, iter,
+    env,
+    fmt::Write,
+    fs, iter,
+    path::{Path, PathBuf},
 };
 use tempfile::TempDir;
 use tokio::{fs::remove_file, net::UnixStream, pin, select, time};
@@ -26,8 +29,8 @@ pub fn console_url() -> url::Url {
 }
 
 pub enum Runtime {
-    Created(Northstar, TempDir),
-    Started(Northstar, TempDir),
+    Created(Northstar, TempDir, config::Config),
+    Started(Northstar, TempDir, config::Config),
 }
 
 impl Runtime {
@@ -125,6 +128,26 @@ impl Runtime {
             event_buffer_size: 128,
             notification_buffer_size: 128,
             loop_device_timeout: time::Duration::from_secs(10),
+            shutdown_timeout: time::Duration::from_secs(10),
+            logs: config::Logs::default(),
+            journal: true,
+            reboot_command: None,
+            hooks: Default::default(),
+            budgets: Default::default(),
+            history: Default::default(),
+            core_dumps: None,
+            network: None,
+            network_namespaces: Default::default(),
+            user_namespaces: Some(config::UserNamespaces {
+                uids: vec![config::IdRange {
+                    start: 100000,
+                    size: 65536,
+                }],
+                gids: vec![config::IdRange {
+                    start: 100000,
+                    size: 65536,
+                }],
+            }),
             cgroup: NonNulString::try_from(format!("northstar-{}", nanoid!())).unwrap(),
             repositories,
             console: Console {
@@ -139,24 +162,96 @@ impl Runtime {
                 commands: vec!["sudo strace -c -p <PID>".into()],
             }),
         };
-        let runtime = Northstar::new(config)?;
+        // The configuration file is written on demand with `Runtime::write_config`
+        let runtime = Northstar::new(config.clone())?.with_config_file(config_file(tmpdir.path()));
 
-        Ok(Runtime::Created(runtime, tmpdir))
+        Ok(Runtime::Created(runtime, tmpdir, config))
     }
 
     pub async fn start(self) -> Result<(Runtime, Client)> {
-        if let Runtime::Created(launcher, tmpdir) = self {
+        if let Runtime::Created(launcher, tmpdir, config) = self {
             let runtime = launcher.start().await?;
             logger::assume("Runtime up and running", 10u64).await?;
 
             let client = Client::connect(&console_url()).await?;
 
-            Ok((Runtime::Started(runtime, tmpdir), client))
+            Ok((Runtime::Started(runtime, tmpdir, config), client))
         } else {
             anyhow::bail!("Runtime is already started")
         }
     }
 
+    /// Write the configuration of the runtime modified by `f` to the configuration file that
+    /// is read on a reload. Only the settings used by the tests are written.
+    pub fn write_config<F: FnOnce(&mut config::Config)>(&self, f: F) -> Result<()> {
+        let (tmpdir, mut config) = match self {
+            Runtime::Created(_, tmpdir, config) | Runtime::Started(_, tmpdir, config) => {
+                (tmpdir, config.clone())
+            }
+        };
+        f(&mut config);
+
+        let mut toml = String::new();
+        writeln!(toml, "run_dir = {:?}", config.run_dir.display().to_string())?;
+        writeln!(
+            toml,
+            "data_dir = {:?}",
+            config.data_dir.display().to_string()
+        )?;
+        writeln!(
+            toml,
+            "socket_dir = {:?}",
+            config.socket_dir.display().to_string()
+        )?;
+        writeln!(toml, "cgroup = {:?}", config.cgroup.as_str())?;
+        writeln!(toml, "event_buffer_size = {}", config.event_buffer_size)?;
+        writeln!(
+            toml,
+            "notification_buffer_size = {}",
+            config.notification_buffer_size
+        )?;
+        writeln!(
+            toml,
+            "loop_device_timeout = \"{}s\"",
+            config.loop_device_timeout.as_secs()
+        )?;
+        writeln!(
+            toml,
+            "shutdown_timeout = \"{}s\"",
+            config.shutdown_timeout.as_secs()
+        )?;
+        writeln!(toml, "journal = {}", config.journal)?;
+        if let Some(global) = &config.console.global {
+            anyhow::ensure!(global.permissions == Permissions::full());
+            writeln!(toml, "[console.global]")?;
+            writeln!(toml, "bind = {:?}", global.bind.as_str())?;
+            writeln!(toml, "permissions = \"full\"")?;
+        }
+        for (id, repository) in &config.repositories {
+            writeln!(toml, "[repositories.{id}]")?;
+            match &repository.r#type {
+                config::RepositoryType::Fs { dir } => writeln!(
+                    toml,
+                    "type = {{ fs = {{ dir = {:?} }} }}",
+                    dir.display().to_string()
+                )?,
+                config::RepositoryType::Memory => writeln!(toml, "type = \"mem\"")?,
+            }
+            if let Some(key) = &repository.key {
+                writeln!(toml, "key = {:?}", key.display().to_string())?;
+            }
+            writeln!(toml, "mount_on_start = {}", repository.mount_on_start)?;
+            if let Some(capacity_num) = repository.capacity_num {
+                writeln!(toml, "capacity_num = {capacity_num}")?;
+            }
+            if let Some(capacity_size) = repository.capacity_size {
+                writeln!(toml, "capacity_size = \"{capacity_size}\"")?;
+            }
+        }
+
+        fs::write(config_file(tmpdir.path()), toml).context("failed to write config")
+    }
+
     pub async fn shutdown(self) -> Result<()> {
         drop(self);
         remove_file(console_url().path()).await?;
@@ -164,6 +259,11 @@ impl Runtime {
     }
 }
 
+/// Configuration file of the runtime in `tmpdir`
+fn config_file(tmpdir: &Path) -> PathBuf {
+    tmpdir.join("northstar.toml")
+}
+
 pub struct Client {
     client: northstar_client::Client<UnixStream>,
 }
@@ -220,10 +320,12 @@ impl Client {
     }
 
     pub async fn stop(&mut self, container: &str, timeout: u64) -> Result<()> {
-        self.client.kill(container, 15).await?;
+        self.client.stop(container, Some(timeout)).await?;
         let container: Container = container.try_into()?;
         self.assume_notification(
-            |n| n == &Notification::Exit(container.clone(), ExitStatus::Signalled { signal: 15 }),
+            |n| {
+                matches!(n, Notification::Exit(c, ExitStatus::Signalled { signal: 15 }, _) if c == &container)
+            },
             timeout,
         )
         .await?;
@@ -317,7 +419,7 @@ impl Client {
         timeout: u64,
     ) -> Result<()> {
         let container = Container::try_from(container)?;
-        let n = |n: &Notification| matches!(n, Notification::Exit(c, s) if container == *c && exit_status == *s);
+        let n = |n: &Notification| matches!(n, Notification::Exit(c, s, _) if container == *c && exit_status == *s);
         self.assume_notification(n, timeout).await
     }
 
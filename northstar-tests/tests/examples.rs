@@ -9,7 +9,7 @@ fn crashing() -> Result<()> {
     client.start(EXAMPLE_CRASHING).await?;
     client
         .assume_notification(
-            |n| matches!(n, Notification::Exit(_, ExitStatus::Exit { code: 101 })),
+            |n| matches!(n, Notification::Exit(_, ExitStatus::Exit { code: 101 }, _)),
             20,
         )
         .await
@@ -56,7 +56,7 @@ fn hello_ferris() -> Result<()> {
     // will try to shutdown the application which is already exited.
     client
         .assume_notification(
-            |n| matches!(n, Notification::Exit(_, ExitStatus::Exit { code: 0 },)),
+            |n| matches!(n, Notification::Exit(_, ExitStatus::Exit { code: 0 }, _)),
             15,
         )
         .await
@@ -1,10 +1,35 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::Result;
 use log::debug;
-use northstar_runtime::api::model::ExitStatus;
+use northstar_runtime::{
+    api::model::{
+        CriticalAction, ExitReason, ExitStatus, Health, Io, Log, LogStream, Name, Notification,
+        ScheduledRun, UpdateProgress,
+    },
+    common::version::{Version, VersionReq},
+    npk::manifest::{
+        autostart::Autostart,
+        console::{Console, Permissions},
+        critical::Critical,
+        dependency::Dependency,
+        health::{self, Probe},
+        hooks::Hook,
+        ready::Ready,
+        restart::{Policy, Restart},
+        schedule::Schedule,
+        signal::Signal,
+        user_namespace::{IdMap, UserNamespace},
+    },
+};
 use northstar_tests::{
-    containers::{with_manifest, TEST_CONTAINER, TEST_CONTAINER_NPK, TEST_RESOURCE},
+    containers::{
+        with_manifest, EXAMPLE_CONSOLE, EXAMPLE_CONSOLE_NPK, TEST_CONTAINER, TEST_CONTAINER_NPK,
+        TEST_RESOURCE,
+    },
     logger::assume,
     runtime_test,
 };
@@ -59,6 +84,27 @@ async fn try_to_start_container_that_misses_a_resource() -> Result<()> {
     Ok(())
 }
 
+// Try to start a container where a dependency is missing
+#[runtime_test]
+async fn try_to_start_container_that_misses_a_dependency() -> Result<()> {
+    client.install_test_resource().await?;
+    let name = Name::try_from("unknown")?;
+    let version = VersionReq::from_str(">=0.0.1")?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        let dependency = Dependency {
+            version,
+            ready: false,
+        };
+        m.depends_on.insert(name, dependency);
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    assert!(client.start(TEST_CONTAINER).await.is_err());
+
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
 // Start a container that uses a resource
 #[runtime_test]
 async fn check_test_container_resource_usage() -> Result<()> {
@@ -129,6 +175,640 @@ async fn container_crash_exit() -> Result<()> {
     client.uninstall_test_resource().await
 }
 
+// Restart a crashing container until the retries are exhausted
+#[runtime_test]
+async fn container_crash_restart() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.restart = Some(Restart {
+            policy: Policy::OnFailure,
+            max_retries: Some(2),
+            backoff: time::Duration::from_millis(100),
+            max_backoff: time::Duration::from_secs(1),
+            reset_after: time::Duration::from_secs(60),
+        })
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client.start_with_args(TEST_CONTAINER, ["crash"]).await?;
+    const EXIT_STATUS: ExitStatus = ExitStatus::Exit { code: 101 };
+    for _ in 0..3 {
+        client.assume_exit(TEST_CONTAINER, EXIT_STATUS, 5).await?;
+    }
+    client
+        .assume_notification(|n| matches!(n, Notification::RestartExhausted(_, 2)), 5)
+        .await?;
+
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Restart a container that crashes after a kill request with a signal it handles
+#[runtime_test]
+async fn container_crash_restart_after_kill() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.restart = Some(Restart {
+            policy: Policy::OnFailure,
+            max_retries: Some(1),
+            backoff: time::Duration::from_millis(100),
+            max_backoff: time::Duration::from_secs(1),
+            reset_after: time::Duration::from_secs(60),
+        })
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client
+        .start_with_args(TEST_CONTAINER, ["crash-on-signal"])
+        .await?;
+    assume("Waiting for SIGUSR1", 5).await?;
+    (*client).kill(TEST_CONTAINER, 10).await?;
+    const EXIT_STATUS: ExitStatus = ExitStatus::Exit { code: 101 };
+    client.assume_exit(TEST_CONTAINER, EXIT_STATUS, 5).await?;
+    client
+        .assume_notification(
+            |n| matches!(n, Notification::Started(c) if c.name().as_ref() == "test-container"),
+            5,
+        )
+        .await?;
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Start the failure handler of a failed critical container
+#[runtime_test]
+async fn critical_failure_handler() -> Result<()> {
+    client.install_test_resource().await?;
+    let handler = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.name = "test-handler".try_into().expect("invalid name");
+    })?;
+    client.install(&handler, "mem").await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.autostart = Some(Autostart::Critical);
+        m.critical = Some(Critical::FailureHandler {
+            container: "test-handler".try_into().expect("invalid name"),
+            version: VersionReq::from_str(">=0.0.1").expect("invalid version"),
+        });
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client.start_with_args(TEST_CONTAINER, ["crash"]).await?;
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Critical {
+                        action: CriticalAction::FailureHandler { container },
+                        ..
+                    } if container.name().as_ref() == "test-handler"
+                )
+            },
+            5,
+        )
+        .await?;
+    client
+        .assume_notification(
+            |n| matches!(n, Notification::Started(c) if c.name().as_ref() == "test-handler"),
+            5,
+        )
+        .await?;
+
+    client.stop("test-handler:0.0.1", 5).await?;
+    client.uninstall("test-handler:0.0.1", true).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Run a hook container before the container and veto the start if it is missing or
+// exits with a non zero exit code
+#[runtime_test]
+async fn pre_start_hook() -> Result<()> {
+    client.install_test_resource().await?;
+    let with_hook = |exit_code: &str| {
+        let exit_code = exit_code.to_string();
+        with_manifest(&TEST_CONTAINER_NPK, move |m| {
+            m.hooks.pre_start = vec![Hook::Container {
+                container: "test-hook".try_into().expect("invalid name"),
+                version: VersionReq::from_str(">=0.0.1").expect("invalid version"),
+                args: vec![
+                    "exit".try_into().expect("invalid argument"),
+                    exit_code.try_into().expect("invalid argument"),
+                ],
+                timeout: time::Duration::from_secs(60),
+            }];
+        })
+    };
+    client.install(&with_hook("0")?, "mem").await?;
+    assert!(client.start(TEST_CONTAINER).await.is_err());
+
+    let hook = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.name = "test-hook".try_into().expect("invalid name");
+    })?;
+    client.install(&hook, "mem").await?;
+
+    // The hook container exits after each run
+    for _ in 0..2 {
+        client.start(TEST_CONTAINER).await?;
+        assert!(client.inspect("test-hook:0.0.1").await?.process.is_none());
+        client.stop(TEST_CONTAINER, 5).await?;
+    }
+
+    client.uninstall_test_container().await?;
+    client.install(&with_hook("1")?, "mem").await?;
+    assert!(client.start(TEST_CONTAINER).await.is_err());
+
+    client.uninstall("test-hook:0.0.1", true).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Kill a container that does not report its readiness within the ready timeout
+#[runtime_test]
+async fn container_not_ready() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.console = Some(Console {
+            permissions: Permissions::full(),
+        });
+        m.ready = Some(Ready::Notify);
+        m.ready_timeout = Some(time::Duration::from_millis(500));
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    assert!(client.start_ready(TEST_CONTAINER).await.is_err());
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Exit(
+                        _,
+                        ExitStatus::Signalled { signal: 9 },
+                        ExitReason::NotReady
+                    )
+                )
+            },
+            5,
+        )
+        .await?;
+
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Start a container that reports its readiness on the console
+#[runtime_test]
+async fn container_ready() -> Result<()> {
+    client.install(&EXAMPLE_CONSOLE_NPK, "mem").await?;
+    client.start_ready(EXAMPLE_CONSOLE).await?;
+    client
+        .assume_notification(
+            |n| matches!(n, Notification::Ready(c) if c.name().as_ref() == "console"),
+            5,
+        )
+        .await?;
+
+    // The console example kills itself once it is ready
+    client
+        .assume_notification(
+            |n| matches!(n, Notification::Exit(c, _, _) if c.name().as_ref() == "console"),
+            5,
+        )
+        .await?;
+    client.uninstall(EXAMPLE_CONSOLE, false).await?;
+    Ok(())
+}
+
+// Start a container in a user namespace. The host ids must lie within the pools of the
+// runtime configuration and must not be mapped by another started container.
+#[runtime_test]
+async fn container_user_namespace() -> Result<()> {
+    client.install_test_resource().await?;
+    let with_user_namespace = |name: &'static str, host: u32| {
+        with_manifest(&TEST_CONTAINER_NPK, move |m| {
+            m.name = name.try_into().expect("invalid name");
+            // Scheduling policies that require privileges are not available
+            m.sched = None;
+            let map = vec![IdMap {
+                container: 0,
+                host,
+                size: 32768,
+            }];
+            m.user_namespace = Some(UserNamespace {
+                uid_map: map.clone(),
+                gid_map: map,
+            });
+        })
+    };
+
+    client
+        .install(&with_user_namespace("test-container", 200000)?, "mem")
+        .await?;
+    assert!(client.start(TEST_CONTAINER).await.is_err());
+    client.uninstall_test_container().await?;
+
+    client
+        .install(&with_user_namespace("test-container", 100000)?, "mem")
+        .await?;
+    client.start(TEST_CONTAINER).await?;
+    assert!(client.inspect(TEST_CONTAINER).await?.process.is_some());
+
+    // The host ids are mapped by the started test container
+    client
+        .install(&with_user_namespace("test-overlap", 116384)?, "mem")
+        .await?;
+    assert!(client.start("test-overlap:0.0.1").await.is_err());
+    client.uninstall("test-overlap:0.0.1", false).await?;
+
+    client
+        .install(&with_user_namespace("test-disjoint", 132768)?, "mem")
+        .await?;
+    client.start("test-disjoint:0.0.1").await?;
+    client.stop("test-disjoint:0.0.1", 5).await?;
+    client.uninstall("test-disjoint:0.0.1", false).await?;
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Stop a container once its maximum run time elapsed
+#[runtime_test]
+async fn container_max_runtime() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.max_runtime = Some(time::Duration::from_secs(1));
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client.start(TEST_CONTAINER).await?;
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Exit(
+                        _,
+                        ExitStatus::Signalled { signal: 15 },
+                        ExitReason::MaxRuntime
+                    )
+                )
+            },
+            5,
+        )
+        .await?;
+
+    // The per start maximum run time overrides the manifest
+    client.start_max_runtime(TEST_CONTAINER, 60).await?;
+    time::sleep(time::Duration::from_secs(2)).await;
+    assert!(client.inspect(TEST_CONTAINER).await?.process.is_some());
+    client.stop(TEST_CONTAINER, 5).await?;
+
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Record the terminated runs of a container
+#[runtime_test]
+async fn container_history() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+    assert!(client.history(TEST_CONTAINER).await?.is_empty());
+
+    client.start(TEST_CONTAINER).await?;
+    client.stop(TEST_CONTAINER, 5).await?;
+
+    let runs = client.history(TEST_CONTAINER).await?;
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].exit_status, ExitStatus::Signalled { signal: 15 });
+    assert_eq!(runs[0].reason, ExitReason::Stopped);
+
+    client.uninstall_test_container().await?;
+    assert!(client.history(TEST_CONTAINER).await.is_err());
+    client.uninstall_test_resource().await
+}
+
+// Start a container from its schedule and skip runs while it is still running
+#[runtime_test]
+async fn scheduled_run() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.schedule = Some(Schedule {
+            cron: None,
+            interval: Some(time::Duration::from_secs(1)),
+            jitter: time::Duration::ZERO,
+            skip_if_running: true,
+        })
+    })?;
+    client.install(&test_container, "mem").await?;
+    assert!(client
+        .schedules()
+        .await?
+        .contains_key(&TEST_CONTAINER.try_into()?));
+
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Schedule {
+                        run: ScheduledRun::Started,
+                        ..
+                    }
+                )
+            },
+            5,
+        )
+        .await?;
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Schedule {
+                        run: ScheduledRun::Skipped,
+                        ..
+                    }
+                )
+            },
+            5,
+        )
+        .await?;
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Report a container with a failing health probe as unhealthy
+#[runtime_test]
+async fn container_unhealthy() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.health = Some(health::Health {
+            probe: Probe::Exec {
+                path: "/does/not/exist".try_into().expect("invalid path"),
+                args: Vec::new(),
+            },
+            interval: time::Duration::from_millis(100),
+            timeout: time::Duration::from_millis(100),
+            failure_threshold: 2,
+            restart: false,
+        })
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    client
+        .assume_notification(
+            |n| matches!(n, Notification::Health(_, Health::Unhealthy)),
+            5,
+        )
+        .await?;
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Report a container with a succeeding exec probe as healthy. The probe is spawned
+// in the container.
+#[runtime_test]
+async fn container_healthy() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.health = Some(health::Health {
+            probe: Probe::Exec {
+                path: "/test-container".try_into().expect("invalid path"),
+                args: vec![
+                    "exit".try_into().expect("invalid arg"),
+                    "0".try_into().expect("invalid arg"),
+                ],
+            },
+            interval: time::Duration::from_secs(1),
+            timeout: time::Duration::from_secs(1),
+            failure_threshold: 2,
+            restart: false,
+        })
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    client
+        .assume_notification(|n| matches!(n, Notification::Health(_, Health::Healthy)), 5)
+        .await?;
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Stop a container with the stop signal from the manifest
+#[runtime_test]
+async fn container_stop_signal() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.stop_signal = Some(Signal::SIGUSR1);
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    assume("Sleeping", 5u64).await?;
+    (*client).stop(TEST_CONTAINER, Some(5)).await?;
+    let status = ExitStatus::Signalled { signal: 10 };
+    client.assume_exit(TEST_CONTAINER, status, 5).await?;
+
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Update a started container and complete the update after the probation
+#[runtime_test]
+async fn container_update() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    assume("Sleeping", 5u64).await?;
+
+    let update = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.version = Version::parse("0.0.2").expect("invalid version");
+    })?;
+    client
+        .update(
+            TEST_CONTAINER,
+            "test-container:0.0.2",
+            &update[..],
+            update.len() as u64,
+            "mem",
+            1,
+        )
+        .await?;
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Update {
+                        progress: UpdateProgress::Completed,
+                        ..
+                    }
+                )
+            },
+            10,
+        )
+        .await?;
+
+    let containers = client.list().await?;
+    assert!(!containers.contains(&TEST_CONTAINER.try_into()?));
+
+    client.stop("test-container:0.0.2", 5).await?;
+    client.uninstall("test-container:0.0.2", false).await?;
+    client.uninstall_test_resource().await
+}
+
+// Roll back an update of a container whose new version fails during the probation
+#[runtime_test]
+async fn container_update_rollback() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    assume("Sleeping", 5u64).await?;
+
+    let update = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        m.version = Version::parse("0.0.2").expect("invalid version");
+        m.args = vec!["crash".try_into().expect("invalid argument")];
+    })?;
+    client
+        .update(
+            TEST_CONTAINER,
+            "test-container:0.0.2",
+            &update[..],
+            update.len() as u64,
+            "mem",
+            10,
+        )
+        .await?;
+    client
+        .assume_notification(
+            |n| {
+                matches!(
+                    n,
+                    Notification::Update {
+                        progress: UpdateProgress::RolledBack,
+                        ..
+                    }
+                )
+            },
+            10,
+        )
+        .await?;
+
+    let containers = client.list().await?;
+    assert!(!containers.contains(&"test-container:0.0.2".try_into()?));
+    assert!(client.inspect(TEST_CONTAINER).await?.process.is_some());
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Pause and resume a container
+#[runtime_test]
+async fn container_pause_resume() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    assume("Sleeping", 5u64).await?;
+
+    client.pause(TEST_CONTAINER).await?;
+    client
+        .assume_notification(|n| matches!(n, Notification::Paused(_)), 5)
+        .await?;
+    let process = client.inspect(TEST_CONTAINER).await?.process;
+    assert!(process.map(|p| p.paused).unwrap_or(false));
+    assert!(client.pause(TEST_CONTAINER).await.is_err());
+
+    client.resume(TEST_CONTAINER).await?;
+    client
+        .assume_notification(|n| matches!(n, Notification::Resumed(_)), 5)
+        .await?;
+    assert!(client.resume(TEST_CONTAINER).await.is_err());
+
+    client.stop(TEST_CONTAINER, 5).await
+}
+
+// Execute a process in a started container and collect its output and exit status
+#[runtime_test]
+async fn container_exec() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    assume("Sleeping", 5u64).await?;
+
+    let env = [("FOO", "bar")];
+    client
+        .exec(TEST_CONTAINER, "/test-container", ["exit", "7"], env, false)
+        .await?;
+    let mut stdout = Vec::new();
+    let exit_status = loop {
+        match client.io().await? {
+            Io::Stdout(data) => stdout.extend(data),
+            Io::Exit(exit_status) => break exit_status,
+            _ => (),
+        }
+    };
+    assert!(String::from_utf8_lossy(&stdout).contains("Executing \"Exit { code: 7 }\""));
+    assert_eq!(exit_status, ExitStatus::Exit { code: 7 });
+
+    client.stop(TEST_CONTAINER, 5).await
+}
+
+// Attach to the terminal of a container
+#[runtime_test]
+async fn container_attach() -> Result<()> {
+    client.install_test_resource().await?;
+    let test_container = with_manifest(&TEST_CONTAINER_NPK, |m| {
+        if let Some(io) = m.io.as_mut() {
+            io.tty = true;
+        }
+    })?;
+    client.install(&test_container, "mem").await?;
+
+    client
+        .start_with_args(TEST_CONTAINER, ["cat", "/dev/stdin"])
+        .await?;
+    assume("Executing", 5u64).await?;
+
+    client.attach(TEST_CONTAINER).await?;
+    client.resize(24, 80).await?;
+
+    // The terminal echoes the input and cat copies it. The end of transmission
+    // terminates cat and the container sleeps.
+    client.stdin(b"hello\n").await?;
+    client.stdin(&[]).await?;
+    let mut output = String::new();
+    while !output.contains("Sleeping") {
+        match client.io().await? {
+            Io::Stdout(data) => output.push_str(&String::from_utf8_lossy(&data)),
+            io => panic!("unexpected io {io:?}"),
+        }
+    }
+    assert_eq!(output.matches("hello").count(), 2);
+
+    client.detach().await?;
+    while client.io().await? != Io::Detach {}
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await
+}
+
 // Check uid. In the manifest of the test container the uid
 // is set to 1000
 #[runtime_test]
@@ -394,6 +1074,100 @@ async fn stdout_stderr() -> Result<()> {
     client.stop(TEST_CONTAINER, 5).await
 }
 
+// Capture the output of a container
+#[runtime_test]
+async fn container_logs() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+
+    let args = ["print", "--io", "stderr", "hello logs"];
+    client.start_with_args(TEST_CONTAINER, args).await?;
+    assume("hello logs", 10).await?;
+
+    let lines = client.logs(TEST_CONTAINER, false, None, None).await?;
+    assert!(lines
+        .iter()
+        .any(|l| l.stream == LogStream::Stderr && l.line == "hello logs"));
+    let lines = client.logs(TEST_CONTAINER, false, Some(1), None).await?;
+    assert_eq!(lines.len(), 1);
+
+    client.stop(TEST_CONTAINER, 5).await?;
+
+    // The buffer is kept after the container exited
+    let lines = client.logs(TEST_CONTAINER, false, None, None).await?;
+    assert!(lines.iter().any(|l| l.line == "hello logs"));
+
+    // Following a stopped container ends immediately
+    client.logs(TEST_CONTAINER, true, Some(0), None).await?;
+    assert_eq!(client.log().await?, Log::End);
+    Ok(())
+}
+
+// Record the desired state of a container in the journal
+#[runtime_test]
+async fn journal() -> Result<()> {
+    client.install_test_container().await?;
+    client.install_test_resource().await?;
+    let container = TEST_CONTAINER.try_into()?;
+
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+    assume("Sleeping", 5u64).await?;
+
+    let journal = client.journal().await?;
+    let entry = &journal.containers[&container];
+    assert!(entry.mounted);
+    let started = entry.started.as_ref().expect("container is not started");
+    assert_eq!(started.arguments, vec!["sleep".try_into()?]);
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    let journal = client.journal().await?;
+    assert!(journal.containers[&container].started.is_none());
+
+    assert!(!client.clear_journal().await?.containers.is_empty());
+    assert!(client.journal().await?.containers.is_empty());
+
+    client.uninstall_test_container().await?;
+    client.uninstall_test_resource().await
+}
+
+// Try to reload the configuration of a runtime whose configuration file does not exist
+#[runtime_test]
+async fn try_to_reload_config_without_file() -> Result<()> {
+    assert!(client.reload_config().await.is_err());
+    // The runtime is still responsive
+    client.list().await?;
+    Ok(())
+}
+
+// Reload a configuration that adds and removes a repository
+#[runtime_test]
+async fn reload_config_repositories() -> Result<()> {
+    runtime.write_config(|config| {
+        config.repositories.remove("fs");
+        let repository = config.repositories["mem"].clone();
+        config.repositories.insert("reloaded".into(), repository);
+    })?;
+    client.reload_config().await?;
+
+    let repositories = client.repositories().await?;
+    assert!(repositories.contains("reloaded"));
+    assert!(!repositories.contains("fs"));
+
+    // The added repository is usable
+    client.install(&TEST_CONTAINER_NPK, "reloaded").await?;
+    client.start_with_args(TEST_CONTAINER, ["sleep"]).await?;
+
+    // A repository with a started container cannot be removed. The configuration is kept.
+    runtime.write_config(|config| {
+        config.repositories.remove("fs");
+    })?;
+    assert!(client.reload_config().await.is_err());
+    assert!(client.repositories().await?.contains("reloaded"));
+
+    client.stop(TEST_CONTAINER, 5).await?;
+    client.uninstall_test_container().await
+}
+
 /// Installation
 mod install {
     use anyhow::Result;
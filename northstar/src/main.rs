@@ -5,7 +5,7 @@
 
 use anyhow::{anyhow, Context, Error};
 use clap::Parser;
-use log::{debug, info};
+use log::{debug, info, warn};
 use nix::{
     mount::{mount, MsFlags},
     sched::unshare,
@@ -13,11 +13,11 @@ use nix::{
 use northstar_runtime::{runtime, runtime::Runtime as Northstar};
 use runtime::config::Config;
 use std::{
-    env,
-    fs::{self, read_to_string},
+    env, fs,
+    os::unix::process::CommandExt,
     panic,
     path::{Path, PathBuf},
-    process::exit,
+    process::{exit, Command},
 };
 use tokio::{select, signal::unix::SignalKind};
 
@@ -62,10 +62,10 @@ fn main() -> Result<(), Error> {
     }));
 
     // Parse command line arguments and prepare the environment
-    let config = init()?;
+    let (config, config_file) = init()?;
 
     // Create the runtime launcher. This must be done *before* spawning the tokio threadpool.
-    let northstar = Northstar::new(config)?;
+    let northstar = Northstar::new(config)?.with_config_file(config_file);
 
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -75,12 +75,9 @@ fn main() -> Result<(), Error> {
         .block_on(run(northstar))
 }
 
-fn init() -> Result<Config, Error> {
+fn init() -> Result<(Config, PathBuf), Error> {
     let opt = Opt::parse();
-    let config = read_to_string(&opt.config)
-        .with_context(|| format!("failed to read configuration file {}", opt.config.display()))?;
-    let config: Config = toml::from_str(&config)
-        .with_context(|| format!("failed to read configuration file {}", opt.config.display()))?;
+    let config = Config::load(&opt.config)?;
 
     fs::create_dir_all(&config.data_dir).context("failed to create data_dir")?;
     fs::create_dir_all(&config.run_dir).context("failed to create run_dir")?;
@@ -104,7 +101,7 @@ fn init() -> Result<Config, Error> {
         debug!("Mount namespace is disabled");
     }
 
-    Ok(config)
+    Ok((config, opt.config))
 }
 
 async fn run(northstar: Northstar) -> Result<(), Error> {
@@ -120,24 +117,36 @@ async fn run(northstar: Northstar) -> Result<(), Error> {
     let mut sighup = tokio::signal::unix::signal(SignalKind::hangup())
         .context("failed to install sighup handler")?;
 
-    let status = select! {
-        _ = sigint.recv() => {
-            info!("Received SIGINT. Stopping Northstar runtime");
-            runtime.shutdown().await
+    let status = loop {
+        select! {
+            _ = sigint.recv() => {
+                info!("Received SIGINT. Stopping Northstar runtime");
+                break runtime.shutdown().await;
+            }
+            _ = sigterm.recv() => {
+                info!("Received SIGTERM. Stopping Northstar runtime");
+                break runtime.shutdown().await;
+            }
+            _ = sighup.recv() => {
+                info!("Received SIGHUP. Reloading configuration");
+                if let Err(e) = runtime.reload_config().await {
+                    warn!("Failed to reload configuration: {:?}", e);
+                }
+            }
+            status = runtime.stopped() => break status,
         }
-        _ = sigterm.recv() => {
-            info!("Received SIGTERM. Stopping Northstar runtime");
-            runtime.shutdown().await
-        }
-        _ = sighup.recv() => {
-            info!("Received SIGHUP. Stopping Northstar runtime");
-            runtime.shutdown().await
-        }
-        status = runtime.stopped() => status,
     };
 
     match status {
         Ok(_) => exit(0),
+        Err(e) if e.is_restart() => {
+            info!("Restarting Northstar runtime");
+            let error = env::current_exe()
+                .map(|exe| Command::new(exe).args(env::args_os().skip(1)).exec())
+                .unwrap_or_else(|e| e);
+            eprintln!("Failed to restart runtime: {error:?}");
+            exit(1);
+        }
         Err(e) => {
             eprintln!("Runtime exited with {:?}", e);
             exit(1);